@@ -0,0 +1,148 @@
+use std::collections::BTreeSet;
+
+use avl_tree::{arena, AVL};
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+
+const SIZES: [usize; 3] = [1_000, 10_000, 100_000];
+
+/// The operations being compared, so each benchmark can be written once.
+trait Set {
+    const NAME: &'static str;
+    fn new () -> Self;
+    fn insert (&mut self, value: u64);
+    fn contains (&self, value: &u64) -> bool;
+    fn remove (&mut self, value: &u64);
+    fn sum (&self) -> u64;
+}
+impl Set for AVL<u64> {
+    const NAME: &'static str = "boxed";
+    fn new () -> Self {
+        AVL::new()
+    }
+    fn insert (&mut self, value: u64) {
+        AVL::insert(self, value);
+    }
+    fn contains (&self, value: &u64) -> bool {
+        self.find(value)
+    }
+    fn remove (&mut self, value: &u64) {
+        self.delete(value);
+    }
+    fn sum (&self) -> u64 {
+        let mut sum = 0;
+        self.for_each(&mut |value| sum += value);
+        sum
+    }
+}
+impl Set for arena::AVL<u64> {
+    const NAME: &'static str = "arena";
+    fn new () -> Self {
+        arena::AVL::new()
+    }
+    fn insert (&mut self, value: u64) {
+        arena::AVL::insert(self, value);
+    }
+    fn contains (&self, value: &u64) -> bool {
+        self.find(value)
+    }
+    fn remove (&mut self, value: &u64) {
+        self.delete(value);
+    }
+    fn sum (&self) -> u64 {
+        let mut sum = 0;
+        self.for_each(&mut |value| sum += value);
+        sum
+    }
+}
+impl Set for BTreeSet<u64> {
+    const NAME: &'static str = "btree_set";
+    fn new () -> Self {
+        BTreeSet::new()
+    }
+    fn insert (&mut self, value: u64) {
+        BTreeSet::insert(self, value);
+    }
+    fn contains (&self, value: &u64) -> bool {
+        BTreeSet::contains(self, value)
+    }
+    fn remove (&mut self, value: &u64) {
+        BTreeSet::remove(self, value);
+    }
+    fn sum (&self) -> u64 {
+        self.iter().sum()
+    }
+}
+
+/// xorshift, so every run sees the same keys.
+fn next (state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+/// Key sequences of each size: ascending, a shuffled permutation, and
+/// uniformly random keys with duplicates.
+fn distributions (size: usize) -> Vec<(&'static str, Vec<u64>)> {
+    let mut state = 0x2545_f491_4f6c_dd1d;
+    let sequential: Vec<u64> = (0..size as u64).collect();
+
+    let mut shuffled = sequential.clone();
+    for i in (1..size).rev() {
+        let j = (next(&mut state) % (i as u64 + 1)) as usize;
+        shuffled.swap(i, j);
+    }
+
+    let random = (0..size).map(|_| next(&mut state) % size as u64).collect();
+    vec![("sequential", sequential), ("shuffled", shuffled), ("random", random)]
+}
+
+fn build<S: Set> (keys: &[u64]) -> S {
+    let mut set = S::new();
+    for &key in keys {
+        set.insert(key);
+    }
+    set
+}
+
+fn bench_set<S: Set> (c: &mut Criterion) {
+    for &size in SIZES.iter() {
+        for (distribution, keys) in distributions(size) {
+            let parameter = format!("{}/{}", distribution, size);
+
+            c.benchmark_group("insert").bench_with_input(
+                BenchmarkId::new(S::NAME, &parameter), &keys,
+                |b, keys| b.iter(|| build::<S>(keys)));
+
+            let set = build::<S>(&keys);
+            c.benchmark_group("lookup").bench_with_input(
+                BenchmarkId::new(S::NAME, &parameter), &keys,
+                |b, keys| b.iter(|| keys.iter().filter(|key| set.contains(key)).count()));
+
+            c.benchmark_group("delete").bench_with_input(
+                BenchmarkId::new(S::NAME, &parameter), &keys,
+                |b, keys| b.iter_batched(
+                    || build::<S>(keys),
+                    |mut set| {
+                        for key in keys {
+                            set.remove(key);
+                        }
+                        set
+                    },
+                    BatchSize::LargeInput));
+
+            c.benchmark_group("iterate").bench_with_input(
+                BenchmarkId::new(S::NAME, &parameter), &set,
+                |b, set| b.iter(|| set.sum()));
+        }
+    }
+}
+
+fn sets (c: &mut Criterion) {
+    bench_set::<AVL<u64>>(c);
+    bench_set::<arena::AVL<u64>>(c);
+    bench_set::<BTreeSet<u64>>(c);
+}
+
+criterion_group!(benches, sets);
+criterion_main!(benches);
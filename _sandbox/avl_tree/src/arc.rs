@@ -0,0 +1,300 @@
+//! An AVL tree whose children are shared through `Arc`. Cloning a tree is
+//! cheap and gives a snapshot that can be sent to other threads, while the
+//! original keeps being modified: only the nodes on the path to a change
+//! are copied, the rest stay shared with the snapshot.
+use alloc::sync::Arc;
+use core::cmp::{max, Ordering};
+
+#[derive(Debug, Clone)]
+pub enum ArcAvl<A> {
+    Leaf,
+    Node(Arc<ArcAvl<A>>, A, Arc<ArcAvl<A>>, i32),
+}
+impl<A: Ord + Clone> Default for ArcAvl<A> {
+    fn default() -> Self {
+        ArcAvl::new()
+    }
+}
+impl<A: Ord + Clone> ArcAvl<A> {
+    pub fn new () -> Self {
+        ArcAvl::Leaf
+    }
+    pub fn singleton (value: A) -> Self {
+        ArcAvl::node(Arc::new(ArcAvl::Leaf), value, Arc::new(ArcAvl::Leaf))
+    }
+    /// A copy of the tree as it is now, sharing all of its nodes.
+    pub fn snapshot (&self) -> Self {
+        self.clone()
+    }
+    /// Adds `input` if there is no equal value already. A tree that has
+    /// one is left as it is, still sharing all its nodes.
+    pub fn insert (&mut self, input: A) {
+        if self.find(&input) {
+            return;
+        }
+        self.add(input);
+    }
+    pub fn find (&self, input: &A) -> bool {
+        match *self {
+            ArcAvl::Leaf => false,
+            ArcAvl::Node(ref left, ref value, ref right, _) => {
+                match input.cmp(value) {
+                    Ordering::Less => left.find(input),
+                    Ordering::Greater => right.find(input),
+                    Ordering::Equal => true,
+                }
+            }
+        }
+    }
+    /// Removes the value equal to `input`, returning it if there was one.
+    /// A tree without it is left as it is, still sharing all its nodes.
+    pub fn delete (&mut self, input: &A) -> Option<A> {
+        if !self.find(input) {
+            return None;
+        }
+        self.remove(input)
+    }
+    pub fn remove_leftmost (&mut self) -> Option<A> {
+        let result = match core::mem::take(self) {
+            ArcAvl::Leaf => None,
+            ArcAvl::Node(mut left, value, right, _) => {
+                if let Some(leftmost) = Arc::make_mut(&mut left).remove_leftmost() {
+                    *self = ArcAvl::node(left, value, right);
+                    Some(leftmost)
+                }
+                else {
+                    *self = unshare(right);
+                    Some(value)
+                }
+            }
+        };
+        self.balance();
+        result
+    }
+    pub fn get_leftmost (&self) -> Option<&A> {
+        match *self {
+            ArcAvl::Leaf => None,
+            ArcAvl::Node(ref left, ref value, _, _) => left.get_leftmost().or(Some(value)),
+        }
+    }
+    pub fn get_rightmost (&self) -> Option<&A> {
+        match *self {
+            ArcAvl::Leaf => None,
+            ArcAvl::Node(_, ref value, ref right, _) => right.get_rightmost().or(Some(value)),
+        }
+    }
+    pub fn for_each<F: FnMut(&A)> (&self, func: &mut F) {
+        match *self {
+            ArcAvl::Leaf => (),
+            ArcAvl::Node(ref left, ref value, ref right, _) => {
+                left.for_each(func);
+                func(value);
+                right.for_each(func);
+            }
+        }
+    }
+
+    /// As `insert`, for a value that is not in the tree.
+    fn add (&mut self, input: A) {
+        match *self {
+            ArcAvl::Leaf => *self = ArcAvl::singleton(input),
+            ArcAvl::Node(ref mut left, ref value, ref mut right, _) => {
+                match input.cmp(value) {
+                    Ordering::Less => Arc::make_mut(left).add(input),
+                    Ordering::Greater => Arc::make_mut(right).add(input),
+                    Ordering::Equal => return,
+                }
+            }
+        }
+        self.balance();
+    }
+    /// As `delete`, for a value that is in the tree.
+    fn remove (&mut self, input: &A) -> Option<A> {
+        let ordering = match *self {
+            ArcAvl::Leaf => return None,
+            ArcAvl::Node(_, ref value, _, _) => input.cmp(value),
+        };
+        let removed = match ordering {
+            Ordering::Less => self.left_mut().remove(input),
+            Ordering::Greater => self.right_mut().remove(input),
+            Ordering::Equal => {
+                let (left, value, mut right, _) = core::mem::take(self).unwrap();
+                match Arc::make_mut(&mut right).remove_leftmost() {
+                    Some(leftmost) => *self = ArcAvl::node(left, leftmost, right),
+                    None => *self = unshare(left),
+                }
+                Some(value)
+            }
+        };
+        self.balance();
+        removed
+    }
+    fn node (left: Arc<ArcAvl<A>>, value: A, right: Arc<ArcAvl<A>>) -> Self {
+        let height = max(left.height(), right.height()) + 1;
+        ArcAvl::Node(left, value, right, height)
+    }
+    fn unwrap (self) -> (Arc<ArcAvl<A>>, A, Arc<ArcAvl<A>>, i32) {
+        match self {
+            ArcAvl::Node(left, value, right, height) => (left, value, right, height),
+            ArcAvl::Leaf => panic!("Unexpected leaf"),
+        }
+    }
+    fn left_mut (&mut self) -> &mut ArcAvl<A> {
+        match *self {
+            ArcAvl::Node(ref mut left, _, _, _) => Arc::make_mut(left),
+            ArcAvl::Leaf => panic!("Unexpected leaf"),
+        }
+    }
+    fn right_mut (&mut self) -> &mut ArcAvl<A> {
+        match *self {
+            ArcAvl::Node(_, _, ref mut right, _) => Arc::make_mut(right),
+            ArcAvl::Leaf => panic!("Unexpected leaf"),
+        }
+    }
+    fn height (&self) -> i32 {
+        match *self {
+            ArcAvl::Leaf => 0,
+            ArcAvl::Node(_, _, _, height) => height,
+        }
+    }
+    /// positive number for right heavy, negative for left heavy.
+    fn get_balance (&self) -> i32 {
+        match *self {
+            ArcAvl::Leaf => 0,
+            ArcAvl::Node(ref left, _, ref right, _) => right.height() - left.height(),
+        }
+    }
+    fn rotate_left (&mut self) {
+        let (left, left_val, child, _) = core::mem::take(self).unwrap();
+        let (middle, right_val, right, _) = unshare(child).unwrap();
+        let child = ArcAvl::node(left, left_val, middle);
+        *self = ArcAvl::node(Arc::new(child), right_val, right);
+    }
+    fn rotate_right (&mut self) {
+        let (child, right_val, right, _) = core::mem::take(self).unwrap();
+        let (left, left_val, middle, _) = unshare(child).unwrap();
+        let child = ArcAvl::node(middle, right_val, right);
+        *self = ArcAvl::node(left, left_val, Arc::new(child));
+    }
+    /// it is assumed that the children hold the AVL property. This node may
+    /// not have the AVL property or the correct height.
+    fn balance (&mut self) {
+        if let ArcAvl::Node(ref left, _, ref right, ref mut height) = *self {
+            *height = max(left.height(), right.height()) + 1;
+        }
+        let balance = self.get_balance();
+        if balance > 1 {
+            if self.right_mut().get_balance() < 0 {
+                self.right_mut().rotate_right();
+            }
+            self.rotate_left();
+        }
+        else if balance < -1 {
+            if self.left_mut().get_balance() > 0 {
+                self.left_mut().rotate_left();
+            }
+            self.rotate_right();
+        }
+    }
+}
+
+/// Takes a subtree out of its `Arc`, copying the node only if a snapshot
+/// still shares it.
+fn unshare<A: Clone> (tree: Arc<ArcAvl<A>>) -> ArcAvl<A> {
+    Arc::try_unwrap(tree).unwrap_or_else(|tree| (*tree).clone())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::vec::Vec;
+
+    fn check (tree: &ArcAvl<u32>) -> i32 {
+        match *tree {
+            ArcAvl::Leaf => 0,
+            ArcAvl::Node(ref left, _, ref right, height) => {
+                let (left, right) = (check(left), check(right));
+                assert_eq!(height, max(left, right) + 1);
+                assert!((left - right).abs() <= 1);
+                height
+            }
+        }
+    }
+    fn values (tree: &ArcAvl<u32>) -> Vec<u32> {
+        let mut values = Vec::new();
+        tree.for_each(&mut |&value| values.push(value));
+        values
+    }
+
+    /// Needs `std` for the reader thread.
+    #[cfg(feature = "std")]
+    #[test]
+    fn snapshots_are_unaffected () {
+        let mut tree = ArcAvl::new();
+        for x in 0..1000 {
+            tree.insert(x);
+        }
+        let snapshot = tree.snapshot();
+        let reader = std::thread::spawn(move || {
+            assert!((0..1000).all(|x| snapshot.find(&x)));
+            snapshot
+        });
+
+        for x in 0..500 {
+            assert_eq!(tree.delete(&(x * 2)), Some(x * 2));
+        }
+        for x in 1000..1200 {
+            tree.insert(x);
+        }
+        let snapshot = reader.join().unwrap();
+
+        check(&tree);
+        check(&snapshot);
+        assert_eq!(values(&snapshot), (0..1000).collect::<Vec<_>>());
+        let expected: Vec<u32> = (0..500).map(|x| x * 2 + 1).chain(1000..1200).collect();
+        assert_eq!(values(&tree), expected);
+        assert_eq!(tree.get_leftmost(), Some(&1));
+        assert_eq!(tree.get_rightmost(), Some(&1199));
+    }
+
+    #[test]
+    fn copies_only_the_changed_path () {
+        let mut tree = ArcAvl::new();
+        for x in 0..1023 {
+            tree.insert(x);
+        }
+        let snapshot = tree.snapshot();
+        tree.insert(2000);
+        check(&tree);
+        assert_eq!(values(&snapshot), (0..1023).collect::<Vec<_>>());
+        let (left, _, _, _) = tree.unwrap();
+        let (old_left, _, _, _) = snapshot.unwrap();
+        assert!(Arc::ptr_eq(&left, &old_left));
+    }
+
+    #[test]
+    fn duplicate_insert_copies_nothing () {
+        let mut tree = ArcAvl::new();
+        for x in 0..1023 {
+            tree.insert(x);
+        }
+        let snapshot = tree.snapshot();
+        tree.insert(500);
+        let (left, _, right, _) = tree.unwrap();
+        let (old_left, _, old_right, _) = snapshot.unwrap();
+        assert!(Arc::ptr_eq(&left, &old_left) && Arc::ptr_eq(&right, &old_right));
+    }
+
+    #[test]
+    fn missing_delete_copies_nothing () {
+        let mut tree = ArcAvl::new();
+        for x in 0..1023 {
+            tree.insert(x * 2);
+        }
+        let snapshot = tree.snapshot();
+        assert_eq!(tree.delete(&501), None);
+        let (left, _, right, _) = tree.unwrap();
+        let (old_left, _, old_right, _) = snapshot.unwrap();
+        assert!(Arc::ptr_eq(&left, &old_left) && Arc::ptr_eq(&right, &old_right));
+    }
+}
@@ -0,0 +1,372 @@
+//! An AVL tree whose nodes live side by side in a single `Vec`, linked by
+//! index rather than by `Box`. Deleted slots are threaded onto a free list
+//! and reused by later insertions.
+use alloc::vec::Vec;
+use core::cmp::{max, Ordering};
+use core::mem::size_of;
+
+use crate::heap_size::{HeapSize, Seen};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct NodeRef(usize);
+
+#[derive(Debug)]
+enum Node<A> {
+    /// An unused slot, pointing to the next unused slot.
+    Null(Option<NodeRef>),
+    Node(Option<NodeRef>, A, Option<NodeRef>, i32),
+}
+
+#[derive(Debug)]
+pub struct AVL<A> {
+    data: Vec<Node<A>>,
+    root: Option<NodeRef>,
+    free: Option<NodeRef>,
+    len: usize,
+}
+impl<A: Ord> Default for AVL<A> {
+    fn default() -> Self {
+        AVL::new()
+    }
+}
+impl<A: Ord> AVL<A> {
+    pub fn new () -> Self {
+        AVL {
+            data: Vec::new(),
+            root: None,
+            free: None,
+            len: 0,
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    pub fn insert (&mut self, input: A) {
+        let root = self.root;
+        self.root = Some(self.insert_at(root, input));
+        debug_assert!(self.is_avl(self.root));
+    }
+    /// Removes the value equal to `input`, returning it if there was one.
+    pub fn delete (&mut self, input: &A) -> Option<A> {
+        let root = self.root;
+        let (root, removed) = self.delete_at(root, input);
+        self.root = root;
+        debug_assert!(self.is_avl(self.root));
+        removed
+    }
+    pub fn find (&self, input: &A) -> bool {
+        let mut node = self.root;
+        while let Some(index) = node {
+            let (left, value, right) = self.get(index);
+            node = match input.cmp(value) {
+                Ordering::Less => left,
+                Ordering::Greater => right,
+                Ordering::Equal => return true,
+            };
+        }
+        false
+    }
+    pub fn get_leftmost(&self) -> Option<&A> {
+        let mut node = self.root?;
+        loop {
+            let (left, value, _) = self.get(node);
+            match left {
+                Some(left) => node = left,
+                None => return Some(value),
+            }
+        }
+    }
+    pub fn get_rightmost(&self) -> Option<&A> {
+        let mut node = self.root?;
+        loop {
+            let (_, value, right) = self.get(node);
+            match right {
+                Some(right) => node = right,
+                None => return Some(value),
+            }
+        }
+    }
+    pub fn for_each<F: FnMut(&A)> (&self, func: &mut F) {
+        self.for_each_at(self.root, func);
+    }
+
+    fn for_each_at<F: FnMut(&A)> (&self, node: Option<NodeRef>, func: &mut F) {
+        if let Some(node) = node {
+            let (left, value, right) = self.get(node);
+            self.for_each_at(left, func);
+            func(value);
+            self.for_each_at(right, func);
+        }
+    }
+
+    fn get(&self, node: NodeRef) -> (Option<NodeRef>, &A, Option<NodeRef>) {
+        match self.data[node.0] {
+            Node::Node(left, ref value, right, _) => (left, value, right),
+            Node::Null(_) => panic!("Tried to read a freed slot"),
+        }
+    }
+    fn height(&self, node: Option<NodeRef>) -> i32 {
+        match node {
+            None => 0,
+            Some(node) => match self.data[node.0] {
+                Node::Node(_, _, _, height) => height,
+                Node::Null(_) => panic!("Tried to get height of a freed slot"),
+            },
+        }
+    }
+
+    /// Stores a node in a free slot, or at the end if there are none.
+    fn alloc(&mut self, node: Node<A>) -> NodeRef {
+        self.len += 1;
+        match self.free {
+            Some(slot) => {
+                self.free = match self.data[slot.0] {
+                    Node::Null(next) => next,
+                    Node::Node(_, _, _, _) => panic!("Free list points to a live node"),
+                };
+                self.data[slot.0] = node;
+                slot
+            }
+            None => {
+                self.data.push(node);
+                NodeRef(self.data.len() - 1)
+            }
+        }
+    }
+    /// Returns a node's slot to the free list, handing back its value.
+    fn release(&mut self, node: NodeRef) -> A {
+        self.len -= 1;
+        let old = core::mem::replace(&mut self.data[node.0], Node::Null(self.free));
+        self.free = Some(node);
+        match old {
+            Node::Node(_, value, _, _) => value,
+            Node::Null(_) => panic!("Tried to free a slot twice"),
+        }
+    }
+    fn set_children(&mut self, node: NodeRef, new_left: Option<NodeRef>, new_right: Option<NodeRef>) {
+        let height = max(self.height(new_left), self.height(new_right)) + 1;
+        match self.data[node.0] {
+            Node::Node(ref mut left, _, ref mut right, ref mut old_height) => {
+                *left = new_left;
+                *right = new_right;
+                *old_height = height;
+            }
+            Node::Null(_) => panic!("Tried to modify a freed slot"),
+        }
+    }
+
+    /// Inserts into the subtree at `node`, returning the root of the new
+    /// subtree.
+    fn insert_at(&mut self, node: Option<NodeRef>, input: A) -> NodeRef {
+        let node = match node {
+            None => return self.alloc(Node::Node(None, input, None, 1)),
+            Some(node) => node,
+        };
+        let (left, value, right) = self.get(node);
+        match input.cmp(value) {
+            Ordering::Less => {
+                let left = self.insert_at(left, input);
+                self.set_children(node, Some(left), right);
+            }
+            Ordering::Greater => {
+                let right = self.insert_at(right, input);
+                self.set_children(node, left, Some(right));
+            }
+            Ordering::Equal => (),
+        }
+        self.balance(node)
+    }
+    /// Deletes from the subtree at `node`, returning the root of the new
+    /// subtree and the removed value.
+    fn delete_at(&mut self, node: Option<NodeRef>, input: &A) -> (Option<NodeRef>, Option<A>) {
+        let node = match node {
+            None => return (None, None),
+            Some(node) => node,
+        };
+        let (left, value, right) = self.get(node);
+        let removed = match input.cmp(value) {
+            Ordering::Less => {
+                let (left, removed) = self.delete_at(left, input);
+                self.set_children(node, left, right);
+                removed
+            }
+            Ordering::Greater => {
+                let (right, removed) = self.delete_at(right, input);
+                self.set_children(node, left, right);
+                removed
+            }
+            Ordering::Equal => match right {
+                None => return (left, Some(self.release(node))),
+                Some(right) => {
+                    let (right, leftmost) = self.remove_leftmost_at(right);
+                    self.set_children(node, left, right);
+                    match self.data[node.0] {
+                        Node::Node(_, ref mut value, _, _) => Some(core::mem::replace(value, leftmost)),
+                        Node::Null(_) => panic!("Tried to modify a freed slot"),
+                    }
+                }
+            },
+        };
+        (Some(self.balance(node)), removed)
+    }
+    fn remove_leftmost_at(&mut self, node: NodeRef) -> (Option<NodeRef>, A) {
+        let (left, _, right) = self.get(node);
+        match left {
+            None => (right, self.release(node)),
+            Some(left) => {
+                let (left, leftmost) = self.remove_leftmost_at(left);
+                self.set_children(node, left, right);
+                (Some(self.balance(node)), leftmost)
+            }
+        }
+    }
+
+    /// positive number for right heavy, negative for left heavy.
+    fn get_balance(&self, node: NodeRef) -> i32 {
+        let (left, _, right) = self.get(node);
+        self.height(right) - self.height(left)
+    }
+    fn rotate_left(&mut self, node: NodeRef) -> NodeRef {
+        let (left, _, child) = self.get(node);
+        let child = child.expect("Tree could not be rotated left");
+        let (middle, _, right) = self.get(child);
+        self.set_children(node, left, middle);
+        self.set_children(child, Some(node), right);
+        child
+    }
+    fn rotate_right(&mut self, node: NodeRef) -> NodeRef {
+        let (child, _, right) = self.get(node);
+        let child = child.expect("Tree could not be rotated right");
+        let (left, _, middle) = self.get(child);
+        self.set_children(node, middle, right);
+        self.set_children(child, left, Some(node));
+        child
+    }
+    /// it is assumed that the children hold the AVL property and that the
+    /// height of `node` is up to date. Returns the root of the balanced
+    /// subtree.
+    fn balance(&mut self, node: NodeRef) -> NodeRef {
+        let balance = self.get_balance(node);
+        let (left, _, right) = self.get(node);
+        if balance > 1 {
+            let right = right.expect("Node is right heavy but has no right child");
+            if self.get_balance(right) < 0 {
+                let right = self.rotate_right(right);
+                self.set_children(node, left, Some(right));
+            }
+            self.rotate_left(node)
+        }
+        else if balance < -1 {
+            let left = left.expect("Node is left heavy but has no left child");
+            if self.get_balance(left) > 0 {
+                let left = self.rotate_left(left);
+                self.set_children(node, Some(left), right);
+            }
+            self.rotate_right(node)
+        }
+        else {
+            node
+        }
+    }
+
+    /// checks to see if the subtree at `node` holds the avl property
+    fn is_avl(&self, node: Option<NodeRef>) -> bool {
+        match node {
+            None => true,
+            Some(node) => {
+                let (left, value, right) = self.get(node);
+                let correct_height = max(self.height(left), self.height(right)) + 1 == self.height(Some(node));
+                let is_balanced = (self.height(left) - self.height(right)).abs() <= 1;
+                let is_sorted_left = left.is_none_or(|left| self.get(left).1 < value);
+                let is_sorted_right = right.is_none_or(|right| self.get(right).1 > value);
+                correct_height && is_balanced && is_sorted_left && is_sorted_right &&
+                    self.is_avl(left) && self.is_avl(right)
+            }
+        }
+    }
+}
+
+/// The whole of the `Vec`, free slots too, and what the values hold.
+impl<A: HeapSize> HeapSize for AVL<A> {
+    fn heap_size_shared(&self, seen: &mut Seen) -> usize {
+        let values: usize = self.data.iter()
+            .map(|node| match *node {
+                Node::Node(_, ref value, _, _) => value.heap_size_shared(seen),
+                Node::Null(_) => 0,
+            })
+            .sum();
+        self.data.capacity() * size_of::<Node<A>>() + values
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn matches_boxed () {
+        let mut arena = AVL::new();
+        let mut boxed = crate::AVL::new();
+
+        // a small linear congruential generator keeps the test deterministic.
+        let mut seed: u32 = 12345;
+        for step in 0..2000 {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            let value = (seed >> 16) % 200;
+            if step % 3 == 0 {
+                assert_eq!(arena.delete(&value), boxed.delete(&value));
+            }
+            else {
+                arena.insert(value);
+                boxed.insert(value);
+            }
+            assert_eq!(arena.find(&value), boxed.find(&value));
+        }
+
+        assert!(arena.is_avl(arena.root));
+        assert_eq!(arena.get_leftmost(), boxed.get_leftmost());
+        assert_eq!(arena.get_rightmost(), boxed.get_rightmost());
+        let mut arena_values = Vec::new();
+        arena.for_each(&mut |&value| arena_values.push(value));
+        let mut boxed_values = Vec::new();
+        boxed.for_each(&mut |&value| boxed_values.push(value));
+        assert_eq!(arena_values, boxed_values);
+        assert_eq!(arena_values.len(), arena.len());
+    }
+
+    #[test]
+    fn reuses_slots () {
+        let mut tree = AVL::new();
+        for x in 0..100 {
+            tree.insert(x);
+        }
+        for x in 0..50 {
+            tree.delete(&x);
+        }
+        assert_eq!(tree.len(), 50);
+        for x in 100..150 {
+            tree.insert(x);
+        }
+        assert_eq!(tree.data.len(), 100);
+        assert!(tree.is_avl(tree.root));
+        assert_eq!(tree.get_leftmost(), Some(&50));
+        assert_eq!(tree.get_rightmost(), Some(&149));
+    }
+
+    #[test]
+    fn heap_size () {
+        let mut tree = AVL::new();
+        for x in 0..100u32 {
+            tree.insert(x);
+        }
+        let slots = tree.data.capacity();
+        assert_eq!(tree.heap_size(), slots * size_of::<Node<u32>>());
+        for x in 0..50 {
+            tree.delete(&x);
+        }
+        assert_eq!(tree.heap_size(), slots * size_of::<Node<u32>>());
+    }
+}
@@ -0,0 +1,322 @@
+//! Trees built from the same nodes as `AVL`, but with the rebalancing policy
+//! chosen by a type parameter. The `i32` in each node holds whatever rank
+//! the policy balances on: the height for the AVL variants, or the number
+//! of values below it for the weight balanced tree.
+use alloc::boxed::Box;
+use core::cmp::{max, Ordering};
+use core::marker::PhantomData;
+
+use super::AVL;
+
+/// A rebalancing policy. A leaf always has rank 0.
+pub trait Balance {
+    /// The rank of a node whose children have the given ranks.
+    fn rank(left: i32, right: i32) -> i32;
+    /// Whether a node whose children have the given ranks needs rotating.
+    fn is_balanced(left: i32, right: i32) -> bool;
+    /// Given the ranks of the inner and outer children of the heavy child,
+    /// whether that child must be rotated first to make a double rotation.
+    fn needs_double_rotation(inner: i32, outer: i32) -> bool;
+}
+
+/// The children's heights differ by at most one, as in `AVL`.
+pub struct StrictAvl;
+impl Balance for StrictAvl {
+    fn rank(left: i32, right: i32) -> i32 {
+        max(left, right) + 1
+    }
+    fn is_balanced(left: i32, right: i32) -> bool {
+        (left - right).abs() <= 1
+    }
+    fn needs_double_rotation(inner: i32, outer: i32) -> bool {
+        inner > outer
+    }
+}
+
+/// The children's heights may differ by two, trading a deeper tree for
+/// fewer rotations.
+pub struct RelaxedAvl;
+impl Balance for RelaxedAvl {
+    fn rank(left: i32, right: i32) -> i32 {
+        max(left, right) + 1
+    }
+    fn is_balanced(left: i32, right: i32) -> bool {
+        (left - right).abs() <= 2
+    }
+    fn needs_double_rotation(inner: i32, outer: i32) -> bool {
+        inner > outer
+    }
+}
+
+/// Neither child holds more than three times the weight of the other, where
+/// the weight is one more than the number of values in the subtree. Uses
+/// the (3, 2) parameters of Adams' trees, which are known to be sound.
+pub struct WeightBalanced;
+impl WeightBalanced {
+    const DELTA: i32 = 3;
+    const GAMMA: i32 = 2;
+}
+impl Balance for WeightBalanced {
+    fn rank(left: i32, right: i32) -> i32 {
+        left + right + 1
+    }
+    fn is_balanced(left: i32, right: i32) -> bool {
+        let (left, right) = (left + 1, right + 1);
+        WeightBalanced::DELTA * left >= right && WeightBalanced::DELTA * right >= left
+    }
+    fn needs_double_rotation(inner: i32, outer: i32) -> bool {
+        inner + 1 >= WeightBalanced::GAMMA * (outer + 1)
+    }
+}
+
+/// A set kept balanced by the policy `B`, counting the rotations it makes.
+#[derive(Debug)]
+pub struct BalancedTree<A, B> {
+    tree: AVL<A>,
+    len: usize,
+    rotations: usize,
+    balance: PhantomData<B>,
+}
+impl<A: Ord, B: Balance> Default for BalancedTree<A, B> {
+    fn default() -> Self {
+        BalancedTree::new()
+    }
+}
+impl<A: Ord, B: Balance> BalancedTree<A, B> {
+    pub fn new () -> Self {
+        BalancedTree {
+            tree: AVL::new(),
+            len: 0,
+            rotations: 0,
+            balance: PhantomData,
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// The number of single rotations made so far. A double rotation counts
+    /// as two.
+    pub fn rotations(&self) -> usize {
+        self.rotations
+    }
+    /// The number of nodes on the longest path from the root to a leaf.
+    pub fn depth(&self) -> usize {
+        depth(&self.tree)
+    }
+    /// The underlying nodes. Only the AVL policies store heights, so the
+    /// result is only a valid `AVL` under `StrictAvl`.
+    pub fn as_tree(&self) -> &AVL<A> {
+        &self.tree
+    }
+    pub fn find (&self, input: &A) -> bool {
+        self.tree.find(input)
+    }
+    pub fn for_each<F: FnMut(&A)> (&self, func: &mut F) {
+        self.tree.for_each(func)
+    }
+    /// Returns whether the value was newly inserted.
+    pub fn insert (&mut self, input: A) -> bool {
+        let inserted = insert::<A, B>(&mut self.tree, input, &mut self.rotations);
+        if inserted {
+            self.len += 1;
+        }
+        inserted
+    }
+    pub fn delete (&mut self, input: &A) -> Option<A> {
+        let removed = delete::<A, B>(&mut self.tree, input, &mut self.rotations);
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+}
+
+fn depth<A> (tree: &AVL<A>) -> usize {
+    match *tree {
+        AVL::Leaf => 0,
+        AVL::Node(ref left, _, ref right, _) => max(depth(left), depth(right)) + 1,
+    }
+}
+
+fn node<A: Ord, B: Balance> (left: Box<AVL<A>>, value: A, right: Box<AVL<A>>) -> AVL<A> {
+    let rank = B::rank(left.height(), right.height());
+    AVL::Node(left, value, right, rank)
+}
+
+fn insert<A: Ord, B: Balance> (tree: &mut AVL<A>, input: A, rotations: &mut usize) -> bool {
+    let inserted = match *tree {
+        AVL::Leaf => {
+            *tree = node::<A, B>(Box::new(AVL::Leaf), input, Box::new(AVL::Leaf));
+            return true;
+        }
+        AVL::Node(ref mut left, ref value, ref mut right, _) => {
+            match input.cmp(value) {
+                Ordering::Less => insert::<A, B>(left, input, rotations),
+                Ordering::Greater => insert::<A, B>(right, input, rotations),
+                Ordering::Equal => false,
+            }
+        }
+    };
+    rebalance::<A, B>(tree, rotations);
+    inserted
+}
+
+fn delete<A: Ord, B: Balance> (tree: &mut AVL<A>, input: &A, rotations: &mut usize) -> Option<A> {
+    let removed = match core::mem::take(tree) {
+        AVL::Leaf => None,
+        AVL::Node(mut left, value, mut right, _) => {
+            match input.cmp(&value) {
+                Ordering::Less => {
+                    let removed = delete::<A, B>(&mut left, input, rotations);
+                    *tree = node::<A, B>(left, value, right);
+                    removed
+                }
+                Ordering::Greater => {
+                    let removed = delete::<A, B>(&mut right, input, rotations);
+                    *tree = node::<A, B>(left, value, right);
+                    removed
+                }
+                Ordering::Equal => {
+                    match remove_leftmost::<A, B>(&mut right, rotations) {
+                        Some(leftmost) => *tree = node::<A, B>(left, leftmost, right),
+                        None => *tree = *left,
+                    }
+                    Some(value)
+                }
+            }
+        }
+    };
+    rebalance::<A, B>(tree, rotations);
+    removed
+}
+
+fn remove_leftmost<A: Ord, B: Balance> (tree: &mut AVL<A>, rotations: &mut usize) -> Option<A> {
+    let result = match core::mem::take(tree) {
+        AVL::Leaf => None,
+        AVL::Node(mut left, value, right, _) => {
+            if let Some(leftmost) = remove_leftmost::<A, B>(&mut left, rotations) {
+                *tree = node::<A, B>(left, value, right);
+                Some(leftmost)
+            }
+            else {
+                *tree = *right;
+                Some(value)
+            }
+        }
+    };
+    rebalance::<A, B>(tree, rotations);
+    result
+}
+
+/// The ranks of a node's left and right children.
+fn child_ranks<A: Ord> (tree: &AVL<A>) -> (i32, i32) {
+    match *tree {
+        AVL::Leaf => (0, 0),
+        AVL::Node(ref left, _, ref right, _) => (left.height(), right.height()),
+    }
+}
+
+fn rotate_left<A: Ord, B: Balance> (tree: &mut AVL<A>) {
+    let (left, left_val, mut child, _) = core::mem::take(tree).unwrap();
+    let (middle, right_val, right, _) = core::mem::take(&mut *child).unwrap();
+    *child = node::<A, B>(left, left_val, middle);
+    *tree = node::<A, B>(child, right_val, right);
+}
+
+fn rotate_right<A: Ord, B: Balance> (tree: &mut AVL<A>) {
+    let (mut child, right_val, right, _) = core::mem::take(tree).unwrap();
+    let (left, left_val, middle, _) = core::mem::take(&mut *child).unwrap();
+    *child = node::<A, B>(middle, right_val, right);
+    *tree = node::<A, B>(left, left_val, child);
+}
+
+/// it is assumed that the children are balanced under `B`. Recomputes the
+/// rank of this node and rotates it if needed.
+fn rebalance<A: Ord, B: Balance> (tree: &mut AVL<A>, rotations: &mut usize) {
+    let (left_rank, right_rank) = child_ranks(tree);
+    let (left, right) = match *tree {
+        AVL::Leaf => return,
+        AVL::Node(ref mut left, _, ref mut right, ref mut rank) => {
+            *rank = B::rank(left_rank, right_rank);
+            (left, right)
+        }
+    };
+    if B::is_balanced(left_rank, right_rank) {
+        return;
+    }
+    if right_rank > left_rank {
+        let (inner, outer) = child_ranks(right);
+        if B::needs_double_rotation(inner, outer) {
+            rotate_right::<A, B>(right);
+            *rotations += 1;
+        }
+        rotate_left::<A, B>(tree);
+    }
+    else {
+        let (outer, inner) = child_ranks(left);
+        if B::needs_double_rotation(inner, outer) {
+            rotate_left::<A, B>(left);
+            *rotations += 1;
+        }
+        rotate_right::<A, B>(tree);
+    }
+    *rotations += 1;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Checks ranks, balance and order of every node, returning the rank.
+    fn check<A: Ord, B: Balance> (tree: &AVL<A>, lower: Option<&A>, upper: Option<&A>) -> i32 {
+        match *tree {
+            AVL::Leaf => 0,
+            AVL::Node(ref left, ref value, ref right, rank) => {
+                assert!(lower.is_none_or(|lower| lower < value));
+                assert!(upper.is_none_or(|upper| value < upper));
+                let left = check::<A, B>(left, lower, Some(value));
+                let right = check::<A, B>(right, Some(value), upper);
+                assert_eq!(rank, B::rank(left, right));
+                assert!(B::is_balanced(left, right));
+                rank
+            }
+        }
+    }
+
+    fn exercise<B: Balance> () -> BalancedTree<u32, B> {
+        let mut tree = BalancedTree::<u32, B>::new();
+        let mut seed: u32 = 98765;
+        for step in 0..5000 {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            let value = (seed >> 16) % 500;
+            if step % 3 == 0 {
+                tree.delete(&value);
+            }
+            else {
+                tree.insert(value);
+            }
+            check::<u32, B>(&tree.tree, None, None);
+        }
+        for value in 0..1000 {
+            tree.insert(value);
+        }
+        check::<u32, B>(&tree.tree, None, None);
+        assert_eq!(tree.len(), 1000);
+        tree
+    }
+
+    #[test]
+    fn policies_stay_balanced () {
+        let strict = exercise::<StrictAvl>();
+        let relaxed = exercise::<RelaxedAvl>();
+        let weighted = exercise::<WeightBalanced>();
+
+        assert_eq!(strict.as_tree().validate(), Ok(()));
+        assert!(relaxed.rotations() < strict.rotations());
+        assert!(strict.depth() <= relaxed.depth());
+        assert!(weighted.depth() <= 2 * strict.depth());
+    }
+}
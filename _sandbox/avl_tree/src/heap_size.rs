@@ -0,0 +1,65 @@
+//! `HeapSize` for the trees, from the `heap_size` crate the other
+//! collection crates share. Nodes behind an `Arc` are counted the first
+//! time they are reached and skipped after that, so snapshots measured
+//! together only pay for the nodes they share once.
+pub use heap_size::{HeapSize, Seen};
+
+use crate::arc::ArcAvl;
+use crate::AVL;
+
+impl<A: HeapSize> HeapSize for AVL<A> {
+    fn heap_size_shared(&self, seen: &mut Seen) -> usize {
+        match *self {
+            AVL::Leaf => 0,
+            AVL::Node(ref left, ref value, ref right, _) =>
+                left.heap_size_shared(seen) + value.heap_size_shared(seen) + right.heap_size_shared(seen),
+        }
+    }
+}
+impl<A: HeapSize> HeapSize for ArcAvl<A> {
+    fn heap_size_shared(&self, seen: &mut Seen) -> usize {
+        match *self {
+            ArcAvl::Leaf => 0,
+            ArcAvl::Node(ref left, ref value, ref right, _) =>
+                left.heap_size_shared(seen) + value.heap_size_shared(seen) + right.heap_size_shared(seen),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::string::String;
+    use alloc::vec;
+    use core::mem::size_of;
+    use heap_size::arc_allocation;
+
+    #[test]
+    fn boxed_tree() {
+        let mut tree = AVL::new();
+        for value in 0..100u32 {
+            tree.insert(value);
+        }
+        // every node holds two boxes, to a node or a leaf of its own.
+        assert_eq!(tree.heap_size(), (2 * 100) * size_of::<AVL<u32>>());
+
+        let strings = AVL::from(vec![String::with_capacity(100)]);
+        assert_eq!(strings.heap_size(), 100 + 2 * size_of::<AVL<String>>());
+    }
+
+    #[test]
+    fn arc_snapshots() {
+        let mut tree = ArcAvl::new();
+        for value in 0..100u32 {
+            tree.insert(value);
+        }
+        let node = arc_allocation::<ArcAvl<u32>>();
+        assert_eq!(tree.heap_size(), (2 * 100) * node);
+
+        let mut changed = tree.snapshot();
+        changed.insert(1000);
+        let mut seen = Seen::new();
+        tree.heap_size_shared(&mut seen);
+        assert!(changed.heap_size_shared(&mut seen) < 20 * node);
+    }
+}
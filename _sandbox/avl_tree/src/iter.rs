@@ -0,0 +1,249 @@
+//! Borrowing iterators over an `AVL`, and the set operations built on them.
+//! The set operations walk both trees in order side by side, so they take
+//! time linear in the size of the two trees rather than a lookup per value.
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::iter::Peekable;
+
+use super::AVL;
+
+/// The values of an `AVL` in ascending order.
+pub struct Iter<'a, A> {
+    /// Nodes whose value and right subtree are still to be visited, with the
+    /// next node on top.
+    stack: Vec<&'a AVL<A>>,
+}
+impl<'a, A> Iter<'a, A> {
+    fn new(tree: &'a AVL<A>) -> Self {
+        let mut iter = Iter { stack: Vec::new() };
+        iter.push_left(tree);
+        iter
+    }
+    fn push_left(&mut self, mut tree: &'a AVL<A>) {
+        while let AVL::Node(ref left, _, _, _) = *tree {
+            self.stack.push(tree);
+            tree = left;
+        }
+    }
+}
+impl<'a, A> Iterator for Iter<'a, A> {
+    type Item = &'a A;
+    fn next(&mut self) -> Option<&'a A> {
+        match *self.stack.pop()? {
+            AVL::Leaf => unreachable!("only nodes are pushed"),
+            AVL::Node(_, ref value, ref right, _) => {
+                self.push_left(right);
+                Some(value)
+            }
+        }
+    }
+}
+impl<'a, A: Ord> IntoIterator for &'a AVL<A> {
+    type Item = &'a A;
+    type IntoIter = Iter<'a, A>;
+    fn into_iter(self) -> Iter<'a, A> {
+        self.iter()
+    }
+}
+
+/// The values in both of two trees, see `AVL::intersection`.
+pub struct Intersection<'a, A> {
+    left: Peekable<Iter<'a, A>>,
+    right: Peekable<Iter<'a, A>>,
+}
+impl<'a, A: Ord> Iterator for Intersection<'a, A> {
+    type Item = &'a A;
+    fn next(&mut self) -> Option<&'a A> {
+        loop {
+            match self.left.peek()?.cmp(self.right.peek()?) {
+                Ordering::Less => {
+                    self.left.next();
+                }
+                Ordering::Greater => {
+                    self.right.next();
+                }
+                Ordering::Equal => {
+                    self.right.next();
+                    return self.left.next();
+                }
+            }
+        }
+    }
+}
+
+/// The values in one tree but not another, see `AVL::difference`.
+pub struct Difference<'a, A> {
+    left: Peekable<Iter<'a, A>>,
+    right: Peekable<Iter<'a, A>>,
+}
+impl<'a, A: Ord> Iterator for Difference<'a, A> {
+    type Item = &'a A;
+    fn next(&mut self) -> Option<&'a A> {
+        loop {
+            let order = match (self.left.peek()?, self.right.peek()) {
+                (_, None) => Ordering::Less,
+                (left, Some(right)) => left.cmp(right),
+            };
+            match order {
+                Ordering::Less => return self.left.next(),
+                Ordering::Greater => {
+                    self.right.next();
+                }
+                Ordering::Equal => {
+                    self.left.next();
+                    self.right.next();
+                }
+            }
+        }
+    }
+}
+
+/// The values in exactly one of two trees, see
+/// `AVL::symmetric_difference`.
+pub struct SymmetricDifference<'a, A> {
+    left: Peekable<Iter<'a, A>>,
+    right: Peekable<Iter<'a, A>>,
+}
+impl<'a, A: Ord> Iterator for SymmetricDifference<'a, A> {
+    type Item = &'a A;
+    fn next(&mut self) -> Option<&'a A> {
+        loop {
+            let order = match (self.left.peek(), self.right.peek()) {
+                (None, None) => return None,
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(left), Some(right)) => left.cmp(right),
+            };
+            match order {
+                Ordering::Less => return self.left.next(),
+                Ordering::Greater => return self.right.next(),
+                Ordering::Equal => {
+                    self.left.next();
+                    self.right.next();
+                }
+            }
+        }
+    }
+}
+
+impl<A: Ord> AVL<A> {
+    pub fn iter(&self) -> Iter<'_, A> {
+        Iter::new(self)
+    }
+    /// The values in both `self` and `other`, in ascending order.
+    pub fn intersection<'a>(&'a self, other: &'a AVL<A>) -> Intersection<'a, A> {
+        Intersection {
+            left: self.iter().peekable(),
+            right: other.iter().peekable(),
+        }
+    }
+    /// The values in `self` but not in `other`, in ascending order.
+    pub fn difference<'a>(&'a self, other: &'a AVL<A>) -> Difference<'a, A> {
+        Difference {
+            left: self.iter().peekable(),
+            right: other.iter().peekable(),
+        }
+    }
+    /// The values in `self` or `other` but not both, in ascending order.
+    pub fn symmetric_difference<'a>(&'a self, other: &'a AVL<A>) -> SymmetricDifference<'a, A> {
+        SymmetricDifference {
+            left: self.iter().peekable(),
+            right: other.iter().peekable(),
+        }
+    }
+    /// Whether every value in `self` is also in `other`. Stops at the first
+    /// value found missing.
+    pub fn is_subset(&self, other: &AVL<A>) -> bool {
+        let mut others = other.iter().peekable();
+        for value in self.iter() {
+            loop {
+                match others.peek() {
+                    None => return false,
+                    Some(&other) => match other.cmp(value) {
+                        Ordering::Less => {
+                            others.next();
+                        }
+                        Ordering::Equal => break,
+                        Ordering::Greater => return false,
+                    },
+                }
+            }
+        }
+        true
+    }
+    /// Whether every value in `other` is also in `self`.
+    pub fn is_superset(&self, other: &AVL<A>) -> bool {
+        other.is_subset(self)
+    }
+    /// Whether `self` and `other` have no values in common. Stops at the
+    /// first shared value.
+    pub fn is_disjoint(&self, other: &AVL<A>) -> bool {
+        self.intersection(other).next().is_none()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::vec;
+
+    fn tree<I: IntoIterator<Item = u32>> (values: I) -> AVL<u32> {
+        let mut tree = AVL::new();
+        for value in values {
+            tree.insert(value);
+        }
+        tree
+    }
+
+    #[test]
+    fn in_order () {
+        let tree = tree((0..100).rev());
+        assert!(tree.iter().copied().eq(0..100));
+        assert_eq!(AVL::<u32>::new().iter().next(), None);
+    }
+
+    #[test]
+    fn intersection_and_difference () {
+        let evens = tree((0..50).map(|x| x * 2));
+        let threes = tree((0..34).map(|x| x * 3));
+
+        let both: Vec<u32> = evens.intersection(&threes).copied().collect();
+        assert_eq!(both, (0..17).map(|x| x * 6).collect::<Vec<_>>());
+
+        let only_evens: Vec<u32> = evens.difference(&threes).copied().collect();
+        assert_eq!(only_evens, (0..50).map(|x| x * 2).filter(|x| x % 3 != 0).collect::<Vec<_>>());
+
+        let empty = AVL::new();
+        assert_eq!(evens.intersection(&empty).next(), None);
+        assert!(evens.difference(&empty).eq(evens.iter()));
+        assert_eq!(empty.difference(&evens).next(), None);
+    }
+    #[test]
+    fn relationships () {
+        let small = tree(vec![2, 4, 6]);
+        let large = tree(0..10);
+        let odds = tree((0..5).map(|x| x * 2 + 1));
+        let empty = AVL::new();
+
+        assert!(small.is_subset(&large));
+        assert!(!large.is_subset(&small));
+        assert!(large.is_superset(&small));
+        assert!(empty.is_subset(&small));
+        assert!(!small.is_subset(&empty));
+        assert!(!odds.is_subset(&small));
+
+        assert!(small.is_disjoint(&odds));
+        assert!(!large.is_disjoint(&odds));
+        assert!(empty.is_disjoint(&large));
+    }
+    #[test]
+    fn symmetric_difference () {
+        let low = tree(0..10);
+        let high = tree(5..15);
+        let either: Vec<u32> = low.symmetric_difference(&high).copied().collect();
+        assert_eq!(either, vec![0, 1, 2, 3, 4, 10, 11, 12, 13, 14]);
+        assert!(high.symmetric_difference(&low).eq(either.iter()));
+        assert_eq!(low.symmetric_difference(&low).next(), None);
+        assert!(low.symmetric_difference(&AVL::new()).eq(low.iter()));
+    }
+}
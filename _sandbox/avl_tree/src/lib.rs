@@ -0,0 +1,1092 @@
+//! An AVL tree and its variants. Everything but `par` builds without `std`
+//! when the default `std` feature is turned off, needing only `alloc`.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::cmp::{max, Ordering};
+
+pub mod arc;
+pub mod arena;
+pub mod balance;
+pub mod heap_size;
+pub mod iter;
+#[cfg(feature = "rayon")]
+mod par;
+
+#[derive(Debug)]
+pub enum AVL<A> {
+    Leaf,
+    Node(Box<AVL<A>>, A, Box<AVL<A>>, i32),
+}
+/// A step from a node to one of its children.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Right,
+}
+
+/// The ways a tree can fail to be an AVL tree, see `AVL::validate`. Each
+/// carries the path from the root to the offending node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AvlViolation {
+    /// The stored height is not one more than the taller child's.
+    HeightMismatch { path: Vec<Direction>, stored: i32, actual: i32 },
+    /// The children's heights differ by more than one. Positive for right
+    /// heavy, negative for left heavy.
+    Imbalance { path: Vec<Direction>, balance: i32 },
+    /// The value is not between the values of its ancestors.
+    Ordering { path: Vec<Direction> },
+}
+impl AvlViolation {
+    pub fn path(&self) -> &[Direction] {
+        match *self {
+            AvlViolation::HeightMismatch { ref path, .. } => path,
+            AvlViolation::Imbalance { ref path, .. } => path,
+            AvlViolation::Ordering { ref path } => path,
+        }
+    }
+}
+impl core::fmt::Display for AvlViolation {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match *self {
+            AvlViolation::HeightMismatch { stored, actual, .. } =>
+                write!(f, "stored height {} should be {}", stored, actual)?,
+            AvlViolation::Imbalance { balance, .. } =>
+                write!(f, "balance factor {} is out of range", balance)?,
+            AvlViolation::Ordering { .. } =>
+                write!(f, "value is out of order")?,
+        }
+        write!(f, " at root")?;
+        for direction in self.path() {
+            match *direction {
+                Direction::Left => write!(f, ".left")?,
+                Direction::Right => write!(f, ".right")?,
+            }
+        }
+        Ok(())
+    }
+}
+#[cfg(feature = "std")]
+impl std::error::Error for AvlViolation {}
+
+pub struct AVLView<'a, A>{
+    stack: Vec<&'a AVL<A>>,
+    tree: &'a AVL<A>,
+}
+impl<A: Ord> Default for AVL<A> {
+    fn default() -> Self {
+        AVL::new()
+    }
+}
+impl<A: Ord> AVL<A> {
+    pub fn new () -> Self {
+        AVL::Leaf
+    }
+    pub fn singleton (value: A) -> Self {
+        AVL::node(Box::new(AVL::Leaf), value, Box::new(AVL::Leaf))
+    }
+    pub fn insert (&mut self, input: A) {
+        assert!(self.is_avl());
+        match *self {
+            AVL::Leaf => *self = AVL::singleton(input),
+            AVL::Node(ref mut left, ref value, ref mut right, _) => {
+                if &input < value {
+                    left.insert(input);
+                }
+                else if &input > value {
+                    right.insert(input);
+                }
+            }
+        }
+        self.balance();
+    }
+    /// Inserts `input`, returning the equal value it displaced if there was
+    /// one. Unlike `insert`, an existing value is overwritten.
+    pub fn replace (&mut self, input: A) -> Option<A> {
+        if let Some(value) = self.find_by_mut(&|value| input.cmp(value)) {
+            return Some(core::mem::replace(value, input));
+        }
+        self.insert(input);
+        None
+    }
+    pub fn find (&self, input: &A) -> bool {
+        self.find_by(&|value| input.cmp(value)).is_some()
+    }
+    /// Removes the value equal to `input`, returning it if there was one.
+    pub fn delete (&mut self, input: &A) -> Option<A> {
+        self.take(input)
+    }
+    /// Removes and returns the stored value equal to `input`. As with
+    /// `HashSet::take`, the value handed back is the one from the tree, not
+    /// the probe, which matters when only part of a value is compared.
+    pub fn take (&mut self, input: &A) -> Option<A> {
+        self.delete_by(&|value| input.cmp(value))
+    }
+    #[allow(clippy::mem_replace_with_default)]
+    pub fn remove_leftmost(&mut self) -> Option<A>{
+        assert!(self.is_avl());
+        let node = core::mem::replace(self, AVL::new());
+        let result = match node {
+            AVL::Leaf => None,
+            AVL::Node(mut left, value, right, _) => {
+                if let Some(leftmost) = left.remove_leftmost() {
+                    *self = AVL::node(left, value, right);
+                    Some(leftmost)
+                }
+                else {
+                    *self = *right;
+                    Some(value)
+                }
+            }
+        };
+        self.balance();
+        result
+    }
+    #[allow(clippy::mem_replace_with_default)]
+    pub fn remove_rightmost(&mut self) -> Option<A>{
+        assert!(self.is_avl());
+        let node = core::mem::replace(self, AVL::new());
+        let result = match node {
+            AVL::Leaf => None,
+            AVL::Node(left, value, mut right, _) => {
+                if let Some(rightmost) = right.remove_rightmost() {
+                    *self = AVL::node(left, value, right);
+                    Some(rightmost)
+                }
+                else {
+                    *self = *left;
+                    Some(value)
+                }
+            }
+        };
+        self.balance();
+        result
+    }
+    pub fn get_leftmost(&self) -> Option<&A> {
+        match *self {
+            AVL::Leaf => None,
+            AVL::Node(ref left, ref value, _, _) => {
+                if let Some(leftmost) = left.get_leftmost() {
+                    Some(leftmost)
+                }
+                else {
+                    Some(value)
+                }
+            }
+        }
+    }
+    pub fn get_rightmost(&self) -> Option<&A> {
+        match *self {
+            AVL::Leaf => None,
+            AVL::Node(_, ref value, ref right, _) => {
+                if let Some(rightmost) = right.get_rightmost() {
+                    Some(rightmost)
+                }
+                else {
+                    Some(value)
+                }
+            }
+        }
+    }
+    /// The smallest value, as with `BTreeSet::first`.
+    pub fn first (&self) -> Option<&A> {
+        self.get_leftmost()
+    }
+    /// The largest value, as with `BTreeSet::last`.
+    pub fn last (&self) -> Option<&A> {
+        self.get_rightmost()
+    }
+    /// Removes and returns the smallest value, so the tree can serve as a
+    /// min-priority queue.
+    pub fn pop_first (&mut self) -> Option<A> {
+        self.remove_leftmost()
+    }
+    /// Removes and returns the largest value, so the tree can serve as a
+    /// max-priority queue.
+    pub fn pop_last (&mut self) -> Option<A> {
+        self.remove_rightmost()
+    }
+    pub fn for_each<F: FnMut(&A)> (&self, func: &mut F) {
+        match *self {
+            AVL::Leaf => (),
+            AVL::Node(ref left, ref value, ref right, _) => {
+                left.for_each(func);
+                func(value);
+                right.for_each(func);
+            }
+        }
+    }
+    
+    /// Consumes the tree, returning its values in ascending order.
+    pub fn into_sorted_vec(self) -> Vec<A> {
+        let mut values = Vec::new();
+        self.drain_sorted(&mut values);
+        values
+    }
+
+    /// Runs `func` on the value equal to `input`, for changing the parts of
+    /// it that do not affect ordering, and returns its result. The value is
+    /// changed in place, then checked against its neighbours. If it is no
+    /// longer between them, it is taken out of the tree and returned as the
+    /// error, so no other value is overwritten. `None` if there is no value
+    /// equal to `input`.
+    pub fn get_mut_with<R, F: FnOnce(&mut A) -> R> (&mut self, input: &A, func: F) -> Option<Result<R, A>> {
+        let mut path = Vec::new();
+        {
+            let guard = self.guard(input, None, None, &mut path)?;
+            let result = func(&mut *guard.value);
+            if guard.in_order() {
+                return Some(Ok(result));
+            }
+        }
+        // the value can't be found by comparing with it any more, but the
+        // nodes above it haven't changed, so follow the way down to it.
+        let step = core::cell::Cell::new(0);
+        let taken = self.delete_by(&|_| {
+            step.set(step.get() + 1);
+            path[step.get() - 1]
+        });
+        Some(Err(taken.expect("the path leads to the changed value")))
+    }
+
+    /// Looks up a value using `compare`, which orders the value being
+    /// searched for relative to each value in the tree.
+    fn find_by<F: Fn(&A) -> Ordering>(&self, compare: &F) -> Option<&A> {
+        match *self {
+            AVL::Leaf => None,
+            AVL::Node(ref left, ref value, ref right, _) => {
+                match compare(value) {
+                    Ordering::Less => left.find_by(compare),
+                    Ordering::Greater => right.find_by(compare),
+                    Ordering::Equal => Some(value),
+                }
+            }
+        }
+    }
+    /// The caller must not change the value in a way that affects its order.
+    fn find_by_mut<F: Fn(&A) -> Ordering>(&mut self, compare: &F) -> Option<&mut A> {
+        match *self {
+            AVL::Leaf => None,
+            AVL::Node(ref mut left, ref mut value, ref mut right, _) => {
+                match compare(value) {
+                    Ordering::Less => left.find_by_mut(compare),
+                    Ordering::Greater => right.find_by_mut(compare),
+                    Ordering::Equal => Some(value),
+                }
+            }
+        }
+    }
+    fn delete_by<F: Fn(&A) -> Ordering>(&mut self, compare: &F) -> Option<A> {
+        assert!(self.is_avl());
+        let mut node = AVL::new();
+        core::mem::swap(&mut node, self);
+        let result = match node {
+            AVL::Leaf => None,
+            AVL::Node(mut left, value, mut right, _) => {
+                match compare(&value) {
+                    Ordering::Less => {
+                        let result = left.delete_by(compare);
+                        *self = AVL::node(left, value, right);
+                        result
+                    }
+                    Ordering::Greater => {
+                        let result = right.delete_by(compare);
+                        *self = AVL::node(left, value, right);
+                        result
+                    }
+                    Ordering::Equal => {
+                        if let Some(leftmost) = right.remove_leftmost() {
+                            *self = AVL::node(left, leftmost, right);
+                        }
+                        else if let Some(rightmost) = left.remove_rightmost() {
+                            *self = AVL::node(left, rightmost, right);
+                        }
+                        // no children, leave self as a leaf.
+                        Some(value)
+                    }
+                }
+            }
+        };
+        self.balance();
+        result
+    }
+
+    /// Finds the value equal to `input`, with the values either side of it,
+    /// recording the way down to it in `path`.
+    fn guard<'a> (&'a mut self, input: &A, lower: Option<&'a A>, upper: Option<&'a A>, path: &mut Vec<Ordering>)
+        -> Option<OrderGuard<'a, A>>
+    {
+        match *self {
+            AVL::Leaf => None,
+            AVL::Node(ref mut left, ref mut value, ref mut right, _) => {
+                let ordering = input.cmp(value);
+                path.push(ordering);
+                match ordering {
+                    Ordering::Less => {
+                        let value: &'a A = value;
+                        left.guard(input, lower, Some(value), path)
+                    }
+                    Ordering::Greater => {
+                        let value: &'a A = value;
+                        right.guard(input, Some(value), upper, path)
+                    }
+                    Ordering::Equal => {
+                        let left: &'a AVL<A> = left;
+                        let right: &'a AVL<A> = right;
+                        Some(OrderGuard {
+                            value,
+                            lower: left.get_rightmost().or(lower),
+                            upper: right.get_leftmost().or(upper),
+                        })
+                    }
+                }
+            }
+        }
+    }
+    fn drain_sorted(self, out: &mut Vec<A>) {
+        if let AVL::Node(left, value, right, _) = self {
+            left.drain_sorted(out);
+            out.push(value);
+            right.drain_sorted(out);
+        }
+    }
+    /// Builds a balanced tree from the next `len` values of `iter`, which
+    /// must be sorted and distinct. Splitting the values as evenly as
+    /// possible keeps the heights of the two halves within one of each
+    /// other.
+    fn from_sorted_iter<I: Iterator<Item = A>>(len: usize, iter: &mut I) -> Self {
+        if len == 0 {
+            return AVL::Leaf;
+        }
+        let left = AVL::from_sorted_iter(len / 2, iter);
+        let value = iter.next().expect("fewer values than expected");
+        let right = AVL::from_sorted_iter(len - len / 2 - 1, iter);
+        AVL::node(Box::new(left), value, Box::new(right))
+    }
+    fn node(left: Box<AVL<A>>, value: A, right: Box<AVL<A>>) -> Self {
+        let height = max(left.height(), right.height()) + 1;
+        AVL::Node(left, value, right, height)
+    }
+    /// Builds a node from two AVL trees whose heights may differ by any
+    /// amount, with `value` between them. The shorter tree is joined into
+    /// the spine of the taller, which is rebalanced on the way back up.
+    fn join(left: Box<AVL<A>>, value: A, right: Box<AVL<A>>) -> Self {
+        let mut node = if left.height() > right.height() + 1 {
+            let (left_left, left_value, left_right, _) = left.unwrap();
+            let joined = AVL::join(left_right, value, right);
+            AVL::node(left_left, left_value, Box::new(joined))
+        }
+        else if right.height() > left.height() + 1 {
+            let (right_left, right_value, right_right, _) = right.unwrap();
+            let joined = AVL::join(left, value, right_left);
+            AVL::node(Box::new(joined), right_value, right_right)
+        }
+        else {
+            AVL::node(left, value, right)
+        };
+        node.balance();
+        node
+    }
+    fn unwrap (self) -> (Box<AVL<A>>, A, Box<AVL<A>>, i32) {
+        match self {
+            AVL::Node(left, value, right, height) =>
+                (left, value, right, height),
+            AVL::Leaf => panic!("Unexpected leaf"),
+        }
+    }
+    fn height(&self) -> i32 {
+        match *self {
+            AVL::Leaf => 0,
+            AVL::Node(_, _, _, height) => height,
+        }
+    }
+    
+    /// checks quickly to see if a node hold the avl property, but does not
+    /// check recursively.
+    fn is_avl(&self) -> bool {
+        match *self {
+            AVL::Leaf => true,
+            AVL::Node(ref left, _, ref right, ref height) => {
+                let correct_height = max(left.height(), right.height()) + 1 == *height;
+                let is_balanced = (left.height() - right.height()).abs() <= 1;
+                correct_height && is_balanced
+            }
+        }
+    }
+    /// Checks the whole tree for the AVL property, reporting the first node
+    /// found to break it.
+    pub fn validate(&self) -> Result<(), AvlViolation> {
+        self.validate_at(&mut Vec::new(), None, None)
+    }
+    fn validate_at(&self, path: &mut Vec<Direction>, lower: Option<&A>, upper: Option<&A>)
+        -> Result<(), AvlViolation>
+    {
+        match *self {
+            AVL::Leaf => Ok(()),
+            AVL::Node(ref left, ref value, ref right, height) => {
+                if !is_between(lower, value, upper) {
+                    return Err(AvlViolation::Ordering { path: path.clone() });
+                }
+
+                path.push(Direction::Left);
+                left.validate_at(path, lower, Some(value))?;
+                path.pop();
+                path.push(Direction::Right);
+                right.validate_at(path, Some(value), upper)?;
+                path.pop();
+
+                let actual = max(left.height(), right.height()) + 1;
+                if actual != height {
+                    return Err(AvlViolation::HeightMismatch { path: path.clone(), stored: height, actual });
+                }
+                let balance = right.height() - left.height();
+                if balance.abs() > 1 {
+                    return Err(AvlViolation::Imbalance { path: path.clone(), balance });
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// positive number for right heavy, negative for left heavy. 
+    /// Readjusts height too
+    fn get_balance(&mut self) -> i32 {
+        match *self {
+            AVL::Leaf => 0,
+            AVL::Node(ref left, _, ref right, ref mut height) => {
+                let l_height = left.height();
+                let r_height = right.height();
+                *height = max(l_height, r_height) + 1;
+                right.height() - left.height()
+            }
+        }
+    }
+    #[allow(clippy::mem_replace_with_default)]
+    fn rotate_left(&mut self) {
+        let node = core::mem::replace(self, AVL::new());
+        let (left, left_val, mut child, _) = node.unwrap();
+
+        let node_child = core::mem::replace(&mut *child, AVL::new());
+        let (middle, right_val, right, _) = node_child.unwrap();
+
+        *child = AVL::node(left, left_val, middle);
+        assert!(child.is_avl());
+        *self = AVL::node(child, right_val, right);
+    }
+    #[allow(clippy::mem_replace_with_default)]
+    fn rotate_right(&mut self) {
+        let node = core::mem::replace(self, AVL::new());
+        let (mut child, right_val, right, _) = node.unwrap();
+
+        let node_child = core::mem::replace(&mut *child, AVL::new());
+        let (left, left_val, middle, _) = node_child.unwrap();
+
+        *child = AVL::node(middle, right_val, right);
+        assert!(child.is_avl());
+        *self = AVL::node(left, left_val, child);
+    }
+    
+    /// it is assumed that the children hold the AVL property. This node may not
+    /// have the AVL property or the correct height
+    fn balance(&mut self) {
+        let balance = self.get_balance();
+        if balance.abs() <= 1 {
+            return;
+        }
+        else if balance > 1 {
+            if let AVL::Node(_, _, ref mut right, _) = *self {
+                if right.get_balance() < 0 {
+                    // the right child is only balanced again once self
+                    // has been rotated too.
+                    right.rotate_right();
+                }
+            }
+            else {
+                panic!("Node is right heavy but has no right child");
+            }
+            self.rotate_left();
+        }
+        else if balance < 1 {
+            if let AVL::Node(ref mut left, _, _, _) = *self {
+                if left.get_balance() > 0 {
+                    left.rotate_left();
+                }
+            }
+            else {
+                panic!("Node is left heavy but has no left child");
+            }
+            self.rotate_right();
+        }
+        assert!(self.is_avl());
+    }
+}
+
+/// Builds a balanced tree in one go. Of several equal values, the first is
+/// kept, as if they had been inserted in order.
+impl<A: Ord> From<Vec<A>> for AVL<A> {
+    fn from(mut values: Vec<A>) -> Self {
+        values.sort();
+        values.dedup();
+        let len = values.len();
+        AVL::from_sorted_iter(len, &mut values.into_iter())
+    }
+}
+impl<A: Ord> From<AVL<A>> for Vec<A> {
+    fn from(tree: AVL<A>) -> Self {
+        tree.into_sorted_vec()
+    }
+}
+
+impl<'a, A> AVLView<'a, A> {
+    #[allow(clippy::redundant_field_names)]
+    pub fn new(tree: &'a AVL<A>) -> Self {
+        AVLView {
+            stack: Vec::new(),
+            tree: tree,
+        }
+    }
+
+    pub fn go_left(&mut self) -> bool {
+        match *self.tree {
+            AVL::Leaf => false,
+            AVL::Node(ref left, _, _, _) => {
+                self.stack.push(self.tree);
+                self.tree = left;
+                true
+            },
+        }
+    }
+    pub fn go_right(&mut self) -> bool {
+        match *self.tree {
+            AVL::Leaf => false,
+            AVL::Node(_, _, ref right, _) => {
+                self.stack.push(self.tree);
+                self.tree = right;
+                true
+            },
+        }
+    }
+    pub fn go_up(&mut self) -> bool {
+        match self.stack.pop() {
+            None => false,
+            Some(tree) => {
+                self.tree = tree;
+                true
+            },
+        }
+    }
+    pub fn value(&self) -> Option<&A> {
+        match *self.tree {
+            AVL::Leaf => None,
+            AVL::Node(_, ref value, _, _) => Some(value),
+        }
+    }
+}
+
+/// Mutable access to a single value in an `AVL`, with the values either
+/// side of it, see `AVL::get_mut_with`.
+struct OrderGuard<'a, A> {
+    value: &'a mut A,
+    lower: Option<&'a A>,
+    upper: Option<&'a A>,
+}
+impl<'a, A: Ord> OrderGuard<'a, A> {
+    /// Whether the value is still between its neighbours.
+    fn in_order(&self) -> bool {
+        is_between(self.lower, self.value, self.upper)
+    }
+}
+
+/// A value paired with the number of times it was inserted. Only the value
+/// takes part in comparisons, so the count can change in place.
+#[derive(Debug)]
+struct Counted<A> {
+    value: A,
+    count: usize,
+}
+impl<A: Ord> PartialEq for Counted<A> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+impl<A: Ord> Eq for Counted<A> {}
+impl<A: Ord> PartialOrd for Counted<A> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl<A: Ord> Ord for Counted<A> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.value.cmp(&other.value)
+    }
+}
+
+/// An AVL tree that keeps duplicates. Equal values share a single node which
+/// counts how many copies are held.
+#[derive(Debug)]
+pub struct AvlMultiSet<A> {
+    tree: AVL<Counted<A>>,
+    len: usize,
+}
+impl<A: Ord> Default for AvlMultiSet<A> {
+    fn default() -> Self {
+        AvlMultiSet::new()
+    }
+}
+impl<A: Ord> AvlMultiSet<A> {
+    pub fn new () -> Self {
+        AvlMultiSet {
+            tree: AVL::new(),
+            len: 0,
+        }
+    }
+    /// The total number of values held, counting duplicates.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    pub fn insert (&mut self, input: A) {
+        self.len += 1;
+        let found = match self.tree.find_by_mut(&|counted| input.cmp(&counted.value)) {
+            Some(counted) => {
+                counted.count += 1;
+                true
+            }
+            None => false,
+        };
+        if !found {
+            self.tree.insert(Counted { value: input, count: 1 });
+        }
+    }
+    pub fn count (&self, input: &A) -> usize {
+        self.tree.find_by(&|counted| input.cmp(&counted.value))
+            .map_or(0, |counted| counted.count)
+    }
+    /// Removes a single copy of `input`, returning whether there was one.
+    pub fn remove_one (&mut self, input: &A) -> bool {
+        let compare = |counted: &Counted<A>| input.cmp(&counted.value);
+        let remaining = match self.tree.find_by_mut(&compare) {
+            None => return false,
+            Some(counted) => {
+                counted.count -= 1;
+                counted.count
+            }
+        };
+        if remaining == 0 {
+            self.tree.delete_by(&compare);
+        }
+        self.len -= 1;
+        true
+    }
+    /// Removes every copy of `input`, returning how many there were.
+    pub fn remove_all (&mut self, input: &A) -> usize {
+        let removed = self.tree.delete_by(&|counted| input.cmp(&counted.value))
+            .map_or(0, |counted| counted.count);
+        self.len -= removed;
+        removed
+    }
+    /// Calls `func` once for every copy of every value, in order.
+    pub fn for_each<F: FnMut(&A)> (&self, func: &mut F) {
+        self.tree.for_each(&mut |counted| {
+            for _ in 0..counted.count {
+                func(&counted.value);
+            }
+        });
+    }
+}
+
+/// The path from the focus of an `AVLListView` back to the root. Each step
+/// holds the parent's value and the sibling subtree that was not taken.
+enum AVLPath<A> {
+    Top,
+    Left(A, Box<AVL<A>>, Box<AVLPath<A>>),
+    Right(Box<AVL<A>>, A, Box<AVLPath<A>>),
+}
+
+/// A zipper over an owned tree. The focus can be moved around and edited in
+/// place, and the tree is rebuilt and rebalanced on the way back up.
+pub struct AVLListView<A> {
+    focus: AVL<A>,
+    path: AVLPath<A>,
+}
+impl<A: Ord> AVLListView<A> {
+    pub fn new(tree: AVL<A>) -> Self {
+        AVLListView {
+            focus: tree,
+            path: AVLPath::Top,
+        }
+    }
+
+    pub fn go_left(&mut self) -> bool {
+        let focus = core::mem::take(&mut self.focus);
+        match focus {
+            AVL::Leaf => false,
+            AVL::Node(left, value, right, _) => {
+                let path = core::mem::replace(&mut self.path, AVLPath::Top);
+                self.path = AVLPath::Left(value, right, Box::new(path));
+                self.focus = *left;
+                true
+            },
+        }
+    }
+    pub fn go_right(&mut self) -> bool {
+        let focus = core::mem::take(&mut self.focus);
+        match focus {
+            AVL::Leaf => false,
+            AVL::Node(left, value, right, _) => {
+                let path = core::mem::replace(&mut self.path, AVLPath::Top);
+                self.path = AVLPath::Right(left, value, Box::new(path));
+                self.focus = *right;
+                true
+            },
+        }
+    }
+    /// Moves to the parent, rebalancing it if the subtree below was edited.
+    /// The focus afterwards may therefore hold a different value than the
+    /// parent did on the way down.
+    pub fn go_up(&mut self) -> bool {
+        let path = core::mem::replace(&mut self.path, AVLPath::Top);
+        let focus = core::mem::take(&mut self.focus);
+        match path {
+            AVLPath::Top => {
+                self.focus = focus;
+                false
+            },
+            AVLPath::Left(value, right, parent) => {
+                self.focus = AVL::join(Box::new(focus), value, right);
+                self.path = *parent;
+                true
+            },
+            AVLPath::Right(left, value, parent) => {
+                self.focus = AVL::join(left, value, Box::new(focus));
+                self.path = *parent;
+                true
+            },
+        }
+    }
+    pub fn value(&self) -> Option<&A> {
+        match self.focus {
+            AVL::Leaf => None,
+            AVL::Node(_, ref value, _, _) => Some(value),
+        }
+    }
+
+    /// Replaces the value at the focus, returning the old one. Fails and
+    /// hands the value back if the focus is a leaf or if the new value does
+    /// not sit strictly between its neighbours.
+    pub fn replace(&mut self, value: A) -> Result<A, A> {
+        let fits = match self.focus {
+            AVL::Leaf => false,
+            AVL::Node(ref left, _, ref right, _) => {
+                let (lower, upper) = self.bounds();
+                let lower = left.get_rightmost().or(lower);
+                let upper = right.get_leftmost().or(upper);
+                is_between(lower, &value, upper)
+            },
+        };
+        match self.focus {
+            AVL::Node(_, ref mut old, _, _) if fits => Ok(core::mem::replace(old, value)),
+            _ => Err(value),
+        }
+    }
+    /// Inserts a value at the focus, which must be a leaf. Fails and hands
+    /// the value back if the focus is not a leaf or if the value does not
+    /// belong at this position.
+    pub fn insert(&mut self, value: A) -> Result<(), A> {
+        let (lower, upper) = self.bounds();
+        match self.focus {
+            AVL::Node(_, _, _, _) => Err(value),
+            AVL::Leaf => {
+                if !is_between(lower, &value, upper) {
+                    return Err(value);
+                }
+                self.focus = AVL::singleton(value);
+                Ok(())
+            },
+        }
+    }
+    /// Unwinds the zipper back to the root, returning the rebuilt tree.
+    pub fn into_tree(mut self) -> AVL<A> {
+        while self.go_up() {}
+        self.focus
+    }
+
+    /// The closest ancestor values below and above the focus.
+    fn bounds(&self) -> (Option<&A>, Option<&A>) {
+        let mut lower = None;
+        let mut upper = None;
+        let mut path = &self.path;
+        loop {
+            match *path {
+                AVLPath::Top => return (lower, upper),
+                AVLPath::Left(ref value, _, ref parent) => {
+                    upper = upper.or(Some(value));
+                    path = parent;
+                },
+                AVLPath::Right(_, ref value, ref parent) => {
+                    lower = lower.or(Some(value));
+                    path = parent;
+                },
+            }
+        }
+    }
+}
+
+fn is_between<A: Ord>(lower: Option<&A>, value: &A, upper: Option<&A>) -> bool {
+    lower.is_none_or(|lower| lower < value) && upper.is_none_or(|upper| value < upper)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    #[test]
+    fn in_order_insertion () {
+        let mut tree = AVL::new();
+        for x in 0..100 {
+            tree.insert(x);
+        }
+        assert_eq!(tree.get_leftmost(), Some(&0));
+        assert_eq!(tree.get_rightmost(), Some(&99));
+        assert_eq!(tree.validate(), Ok(()));
+        
+        for x in 0..50 {
+            assert_eq!(tree.delete(&x), Some(x));
+        }
+        assert_eq!(tree.delete(&0), None);
+        assert_eq!(tree.get_leftmost(), Some(&50));
+        assert_eq!(tree.get_rightmost(), Some(&99));
+        assert_eq!(tree.validate(), Ok(()));
+    }
+    fn zipper_insert (tree: AVL<i32>, input: i32) -> AVL<i32> {
+        let mut view = AVLListView::new(tree);
+        while let Some(&value) = view.value() {
+            if input < value {
+                view.go_left();
+            }
+            else {
+                view.go_right();
+            }
+        }
+        assert_eq!(view.insert(input), Ok(()));
+        view.into_tree()
+    }
+
+    #[test]
+    fn zipper_insertion () {
+        let mut tree = AVL::new();
+        for x in 0..100 {
+            tree = zipper_insert(tree, x);
+            assert_eq!(tree.validate(), Ok(()));
+        }
+        assert_eq!(tree.get_leftmost(), Some(&0));
+        assert_eq!(tree.get_rightmost(), Some(&99));
+    }
+
+    #[test]
+    fn zipper_insertion_below_inserted () {
+        // each value goes below the one inserted before it, so the subtree
+        // at the first leaf grows by several levels before going up.
+        let mut view = AVLListView::new(AVL::from(vec![100]));
+        view.go_right();
+        assert_eq!(view.insert(370), Ok(()));
+        view.go_right();
+        assert_eq!(view.insert(1953), Ok(()));
+        view.go_left();
+        assert_eq!(view.insert(1898), Ok(()));
+        view.go_left();
+        assert_eq!(view.insert(1074), Ok(()));
+        view.go_right();
+        assert_eq!(view.insert(1615), Ok(()));
+        let tree = view.into_tree();
+        assert_eq!(tree.validate(), Ok(()));
+        assert_eq!(tree.into_sorted_vec(), vec![100, 370, 1074, 1615, 1898, 1953]);
+    }
+
+    #[test]
+    fn zipper_keeps_order () {
+        let mut tree = AVL::new();
+        for x in 0..10 {
+            tree.insert(x * 10);
+        }
+        let mut view = AVLListView::new(tree);
+        let root = *view.value().unwrap();
+        assert_eq!(view.replace(1000), Err(1000));
+        assert_eq!(view.replace(root + 1), Ok(root));
+        assert_eq!(view.insert(root), Err(root));
+
+        view.go_left();
+        view.go_right();
+        let below = *view.value().unwrap();
+        assert_eq!(view.replace(root + 2), Err(root + 2));
+        assert_eq!(view.replace(below + 1), Ok(below));
+
+        let tree = view.into_tree();
+        assert_eq!(tree.validate(), Ok(()));
+        let mut values = Vec::new();
+        tree.for_each(&mut |&value| values.push(value));
+        assert_eq!(values.len(), 10);
+        assert!(values.contains(&(root + 1)));
+        assert!(values.contains(&(below + 1)));
+    }
+    #[test]
+    fn multiset_duplicates () {
+        let mut set = AvlMultiSet::new();
+        for x in 0..20 {
+            set.insert(x % 5);
+        }
+        assert_eq!(set.len(), 20);
+        assert_eq!(set.count(&3), 4);
+        assert_eq!(set.count(&7), 0);
+
+        assert!(set.remove_one(&3));
+        assert_eq!(set.count(&3), 3);
+        assert_eq!(set.remove_all(&3), 3);
+        assert!(!set.remove_one(&3));
+        assert_eq!(set.remove_all(&3), 0);
+        assert_eq!(set.len(), 16);
+
+        let mut values = Vec::new();
+        set.for_each(&mut |&value| values.push(value));
+        assert_eq!(values, vec![0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 4, 4, 4, 4]);
+    }
+    #[derive(Debug)]
+    struct Entry {
+        key: u32,
+        payload: &'static str,
+    }
+    impl PartialEq for Entry {
+        fn eq(&self, other: &Self) -> bool {
+            self.key == other.key
+        }
+    }
+    impl Eq for Entry {}
+    impl PartialOrd for Entry {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+    impl Ord for Entry {
+        fn cmp(&self, other: &Self) -> Ordering {
+            self.key.cmp(&other.key)
+        }
+    }
+    fn entries () -> AVL<Entry> {
+        let mut tree = AVL::new();
+        for key in 0..10 {
+            tree.insert(Entry { key: key * 10, payload: "old" });
+        }
+        tree
+    }
+
+    #[test]
+    fn guarded_mutation () {
+        let mut tree = entries();
+        let probe = Entry { key: 30, payload: "" };
+        let old = tree.get_mut_with(&probe, |entry| {
+            entry.key += 5;
+            core::mem::replace(&mut entry.payload, "new")
+        });
+        assert_eq!(old, Some(Ok("old")));
+        assert_eq!(tree.find_by(&|entry| 35.cmp(&entry.key)).unwrap().payload, "new");
+        assert!(tree.get_mut_with(&probe, |_| ()).is_none());
+        assert_eq!(tree.validate(), Ok(()));
+    }
+
+    #[test]
+    fn guarded_mutation_out_of_order () {
+        let mut tree = entries();
+        let moved = tree.get_mut_with(&Entry { key: 30, payload: "" }, |entry| {
+            entry.key = 50;
+            entry.payload = "moved";
+        });
+        // taken out rather than overwriting the 50 already there.
+        let moved = moved.unwrap().unwrap_err();
+        assert_eq!((moved.key, moved.payload), (50, "moved"));
+        assert_eq!(tree.validate(), Ok(()));
+        assert!(!tree.find(&Entry { key: 30, payload: "" }));
+        assert_eq!(tree.find_by(&|entry| 50.cmp(&entry.key)).unwrap().payload, "old");
+        // nine left and the one handed back: none of the ten is lost.
+        assert_eq!(tree.into_sorted_vec().len(), 9);
+    }
+    #[test]
+    fn take_returns_stored_value () {
+        let mut tree = entries();
+        let taken = tree.take(&Entry { key: 30, payload: "probe" }).unwrap();
+        assert_eq!((taken.key, taken.payload), (30, "old"));
+        assert!(tree.take(&Entry { key: 30, payload: "probe" }).is_none());
+        assert!(!tree.find(&Entry { key: 30, payload: "" }));
+        assert_eq!(tree.validate(), Ok(()));
+    }
+    #[test]
+    fn replace_overwrites () {
+        let mut tree = entries();
+        let old = tree.replace(Entry { key: 30, payload: "new" }).unwrap();
+        assert_eq!(old.payload, "old");
+        assert_eq!(tree.find_by(&|entry| 30.cmp(&entry.key)).unwrap().payload, "new");
+        assert!(tree.replace(Entry { key: 35, payload: "new" }).is_none());
+        assert!(tree.find(&Entry { key: 35, payload: "" }));
+        assert_eq!(tree.validate(), Ok(()));
+    }
+    #[test]
+    fn priority_queue () {
+        let mut tree = AVL::from(vec![5, 3, 8, 1, 9, 2]);
+        assert_eq!(tree.first(), Some(&1));
+        assert_eq!(tree.last(), Some(&9));
+        assert_eq!(tree.pop_first(), Some(1));
+        assert_eq!(tree.pop_last(), Some(9));
+        assert_eq!(tree.pop_first(), Some(2));
+        assert_eq!(tree.validate(), Ok(()));
+        assert_eq!(tree.into_sorted_vec(), vec![3, 5, 8]);
+
+        let mut tree = AVL::<i32>::new();
+        assert_eq!(tree.first(), None);
+        assert_eq!(tree.pop_last(), None);
+    }
+    fn leaf () -> Box<AVL<i32>> {
+        Box::new(AVL::Leaf)
+    }
+    fn single (value: i32) -> Box<AVL<i32>> {
+        Box::new(AVL::singleton(value))
+    }
+
+    #[test]
+    fn validate_reports_violations () {
+        let tree = AVL::Node(single(1), 2, Box::new(AVL::Node(single(3), 4, leaf(), 1)), 3);
+        assert_eq!(tree.validate(), Err(AvlViolation::HeightMismatch {
+            path: vec![Direction::Right],
+            stored: 1,
+            actual: 2,
+        }));
+
+        let tree = AVL::Node(leaf(), 1, Box::new(AVL::Node(leaf(), 2, single(3), 2)), 3);
+        let violation = tree.validate().unwrap_err();
+        assert_eq!(violation, AvlViolation::Imbalance { path: vec![], balance: 2 });
+        assert_eq!(violation.to_string(), "balance factor 2 is out of range at root");
+
+        let tree = AVL::Node(single(1), 5, Box::new(AVL::Node(single(4), 6, leaf(), 2)), 3);
+        let violation = tree.validate().unwrap_err();
+        assert_eq!(violation.path(), &[Direction::Right, Direction::Left]);
+        assert_eq!(violation.to_string(), "value is out of order at root.right.left");
+    }
+    #[test]
+    fn vec_conversions () {
+        let values: Vec<u32> = (0..1000).map(|x| (x * 389) % 500).collect();
+        let tree = AVL::from(values);
+        assert_eq!(tree.validate(), Ok(()));
+        let sorted: Vec<u32> = tree.into();
+        assert_eq!(sorted, (0..500).collect::<Vec<_>>());
+
+        let mut tree = AVL::from(Vec::new());
+        tree.insert(3);
+        tree.insert(1);
+        assert_eq!(tree.into_sorted_vec(), vec![1, 3]);
+    }
+}
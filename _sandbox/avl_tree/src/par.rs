@@ -0,0 +1,89 @@
+//! Bulk loading on the rayon thread pool. Input is sorted in parallel and the
+//! balanced tree is then built bottom up, with large subtrees built on
+//! separate workers.
+use std::cmp::Ordering;
+
+use rayon::prelude::*;
+
+use super::AVL;
+
+/// Below this many values a subtree is built on the current thread.
+const SEQUENTIAL_CUTOFF: usize = 4096;
+
+impl<A: Ord + Send> AVL<A> {
+    pub fn par_from_iter<I: IntoParallelIterator<Item = A>> (iter: I) -> Self {
+        par_build(sorted(iter))
+    }
+    /// Adds every value from `iter`, rebuilding the tree in one go. Values
+    /// already in the tree are kept in preference to equal new ones.
+    pub fn par_extend<I: IntoParallelIterator<Item = A>> (&mut self, iter: I) {
+        let new = sorted(iter);
+        let old = std::mem::take(self).into_sorted_vec();
+
+        let mut merged = Vec::with_capacity(old.len() + new.len());
+        let mut old = old.into_iter().peekable();
+        let mut new = new.into_iter().peekable();
+        loop {
+            let order = match (old.peek(), new.peek()) {
+                (None, None) => break,
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(a), Some(b)) => a.cmp(b),
+            };
+            match order {
+                Ordering::Less => merged.extend(old.next()),
+                Ordering::Greater => merged.extend(new.next()),
+                Ordering::Equal => {
+                    merged.extend(old.next());
+                    new.next();
+                }
+            }
+        }
+        *self = par_build(merged);
+    }
+}
+
+fn sorted<A: Ord + Send, I: IntoParallelIterator<Item = A>> (iter: I) -> Vec<A> {
+    let mut values: Vec<A> = iter.into_par_iter().collect();
+    values.par_sort_unstable();
+    values.dedup();
+    values
+}
+
+/// Builds a balanced tree from sorted, distinct values.
+fn par_build<A: Ord + Send> (mut values: Vec<A>) -> AVL<A> {
+    if values.len() <= SEQUENTIAL_CUTOFF {
+        let len = values.len();
+        return AVL::from_sorted_iter(len, &mut values.into_iter());
+    }
+    let middle = values.len() / 2;
+    let right = values.split_off(middle + 1);
+    let value = values.pop().expect("middle value");
+    let (left, right) = rayon::join(|| par_build(values), || par_build(right));
+    AVL::node(Box::new(left), value, Box::new(right))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bulk_load () {
+        let values: Vec<u32> = (0..100_000).map(|x| (x * 7919) % 50_000).collect();
+        let mut tree = AVL::par_from_iter(values);
+        assert_eq!(tree.validate(), Ok(()));
+        assert_eq!(tree.get_leftmost(), Some(&0));
+        assert_eq!(tree.get_rightmost(), Some(&49_999));
+
+        tree.par_extend((25_000..75_000).into_par_iter());
+        assert_eq!(tree.validate(), Ok(()));
+        let mut count = 0;
+        let mut previous = None;
+        tree.for_each(&mut |&value| {
+            assert!(previous.is_none_or(|previous| previous < value));
+            previous = Some(value);
+            count += 1;
+        });
+        assert_eq!(count, 75_000);
+    }
+}
@@ -0,0 +1,107 @@
+use graph_map::{cycles, raw_list};
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+
+const SIZES: [usize; 3] = [1_000, 10_000, 100_000];
+
+/// The cursor operations being compared, so each benchmark can be written
+/// once.
+trait CursorList {
+    const NAME: &'static str;
+    fn new () -> Self;
+    fn advance (&mut self);
+    fn insert_after (&mut self, value: u64);
+    fn remove_before (&mut self) -> Option<u64>;
+    fn splice_after (&mut self, other: Self);
+}
+impl CursorList for cycles::List<u64> {
+    const NAME: &'static str = "rc";
+    fn new () -> Self {
+        cycles::List::new()
+    }
+    fn advance (&mut self) {
+        cycles::List::advance(self);
+    }
+    fn insert_after (&mut self, value: u64) {
+        cycles::List::insert_after(self, value);
+    }
+    fn remove_before (&mut self) -> Option<u64> {
+        cycles::List::remove_before(self)
+    }
+    fn splice_after (&mut self, other: Self) {
+        cycles::List::splice_after(self, other);
+    }
+}
+impl CursorList for raw_list::List<u64> {
+    const NAME: &'static str = "raw";
+    fn new () -> Self {
+        raw_list::List::new()
+    }
+    fn advance (&mut self) {
+        raw_list::List::advance(self);
+    }
+    fn insert_after (&mut self, value: u64) {
+        raw_list::List::insert_after(self, value);
+    }
+    fn remove_before (&mut self) -> Option<u64> {
+        raw_list::List::remove_before(self)
+    }
+    fn splice_after (&mut self, other: Self) {
+        raw_list::List::splice_after(self, other);
+    }
+}
+
+/// A list of `0..size`, with the cursor at the tail.
+fn build<L: CursorList> (size: usize) -> L {
+    let mut list = L::new();
+    for value in 0..size as u64 {
+        list.insert_after(value);
+        list.advance();
+    }
+    list.advance();
+    list
+}
+
+fn bench_list<L: CursorList> (c: &mut Criterion) {
+    for &size in SIZES.iter() {
+        c.benchmark_group("insert").bench_with_input(
+            BenchmarkId::new(L::NAME, size), &size,
+            |b, &size| b.iter(|| build::<L>(size)));
+
+        c.benchmark_group("remove").bench_with_input(
+            BenchmarkId::new(L::NAME, size), &size,
+            |b, &size| b.iter_batched(
+                || build::<L>(size),
+                |mut list| {
+                    while list.remove_before().is_some() {}
+                    list
+                },
+                BatchSize::LargeInput));
+
+        // the other list's elements go in one at a time in the middle.
+        c.benchmark_group("splice").bench_with_input(
+            BenchmarkId::new(L::NAME, size), &size,
+            |b, &size| b.iter_batched(
+                || {
+                    let mut list = L::new();
+                    list.insert_after(0);
+                    list.advance();
+                    list.insert_after(1);
+                    (list, (0..size).map(|_| build::<L>(1)).collect::<Vec<L>>())
+                },
+                |(mut list, others)| {
+                    for other in others {
+                        list.splice_after(other);
+                    }
+                    list
+                },
+                BatchSize::LargeInput));
+    }
+}
+
+fn lists (c: &mut Criterion) {
+    bench_list::<cycles::List<u64>>(c);
+    bench_list::<raw_list::List<u64>>(c);
+}
+
+criterion_group!(benches, lists);
+criterion_main!(benches);
@@ -0,0 +1,148 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+
+use crate::Graph;
+
+impl<T: Eq + Hash, E, K: Eq + Hash + Copy> Graph<T, E, K> {
+    /// Splits the nodes in two so that every edge joins one side to the
+    /// other, whichever way it goes, or returns `None` if that can't be
+    /// done because of a cycle of odd length.
+    pub fn is_bipartite(&self) -> Option<(HashSet<K>, HashSet<K>)> {
+        let mut sides: HashMap<K, bool> = HashMap::new();
+        for key in self.nodes.keys() {
+            if sides.contains_key(key) {
+                continue;
+            }
+            sides.insert(*key, false);
+            let mut queue = VecDeque::new();
+            queue.push_back(*key);
+            while let Some(current) = queue.pop_front() {
+                let side = sides[&current];
+                for next in self.adjacent(&current) {
+                    match sides.get(next) {
+                        Some(&next_side) if next_side == side => return None,
+                        Some(_) => {}
+                        None => {
+                            sides.insert(*next, !side);
+                            queue.push_back(*next);
+                        }
+                    }
+                }
+            }
+        }
+        let side = |wanted| sides.iter()
+            .filter(|(_, side)| **side == wanted)
+            .map(|(key, _)| *key)
+            .collect();
+        Some((side(false), side(true)))
+    }
+    /// As many edges as can be chosen without two sharing a node, like
+    /// tasks given to workers who can do them, with an edge between each
+    /// task and each worker who can do it. Each pair is the start and end of
+    /// a chosen edge. Uses Hopcroft and Karp's algorithm. Returns `None` if
+    /// the graph isn't bipartite.
+    pub fn maximum_matching(&self) -> Option<Vec<(K, K)>> {
+        let (left, _) = self.is_bipartite()?;
+        // each matched node's partner, on both sides.
+        let mut mates: HashMap<K, K> = HashMap::new();
+        loop {
+            // the length of the shortest alternating path to each node on
+            // the left from an unmatched one.
+            let mut distances: HashMap<K, usize> = HashMap::new();
+            let mut queue: VecDeque<K> = left.iter()
+                .filter(|key| !mates.contains_key(key))
+                .copied()
+                .collect();
+            for key in &queue {
+                distances.insert(*key, 0);
+            }
+            let mut augmentable = false;
+            while let Some(current) = queue.pop_front() {
+                for next in self.adjacent(&current) {
+                    match mates.get(next) {
+                        None => augmentable = true,
+                        Some(mate) if !distances.contains_key(mate) => {
+                            distances.insert(*mate, distances[&current] + 1);
+                            queue.push_back(*mate);
+                        }
+                        Some(_) => {}
+                    }
+                }
+            }
+            if !augmentable {
+                break;
+            }
+            for key in &left {
+                if !mates.contains_key(key) {
+                    self.augment(key, &mut distances, &mut mates);
+                }
+            }
+        }
+
+        let matching = left.iter()
+            .filter_map(|key| {
+                let mate = *mates.get(key)?;
+                Some(if self.has_edge(key, &mate) { (*key, mate) } else { (mate, *key) })
+            })
+            .collect();
+        Some(matching)
+    }
+
+    /// The nodes joined to `key` by an edge either way.
+    fn adjacent<'a>(&'a self, key: &K) -> impl Iterator<Item = &'a K> {
+        let node = &self.nodes[key];
+        node.outgoing.iter().chain(node.incoming.iter())
+    }
+    /// Looks for a path from `key`, on the left, to an unmatched node on
+    /// the right, going one step further from the start each time it comes
+    /// back to the left, and flips the matching along it.
+    fn augment(&self, key: &K, distances: &mut HashMap<K, usize>, mates: &mut HashMap<K, K>) -> bool {
+        let distance = distances[key];
+        for next in self.adjacent(key) {
+            let found = match mates.get(next).copied() {
+                None => true,
+                Some(mate) => distances.get(&mate) == Some(&(distance + 1)) && self.augment(&mate, distances, mates),
+            };
+            if found {
+                mates.insert(*key, *next);
+                mates.insert(*next, *key);
+                return true;
+            }
+        }
+        // nothing to find from here this round.
+        distances.remove(key);
+        false
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn matching () {
+        let mut graph: Graph<&str, (), &str> = Graph::new();
+        for key in &["paint", "roof", "wire", "alice", "bob", "carol"] {
+            graph.insert_node(key, key);
+        }
+        for (task, worker) in &[("paint", "alice"), ("paint", "bob"), ("roof", "alice"), ("wire", "alice")] {
+            graph.add_edge(task, worker).unwrap();
+        }
+        let (first, second) = graph.is_bipartite().unwrap();
+        assert!(graph.edges().all(|(start, end, _)| first.contains(start) != first.contains(end)));
+        assert_eq!(first.len() + second.len(), 6);
+
+        let matching = graph.maximum_matching().unwrap();
+        assert_eq!(matching.len(), 2);
+        assert!(matching.contains(&("paint", "bob")));
+
+        graph.add_edge(&"carol", &"wire").unwrap();
+        let matching = graph.maximum_matching().unwrap();
+        assert_eq!(matching.len(), 3);
+        assert!(matching.iter().all(|(start, end)| graph.has_edge(start, end)));
+
+        graph.add_edge(&"bob", &"alice").unwrap();
+        assert_eq!(graph.is_bipartite(), None);
+        assert_eq!(graph.maximum_matching(), None);
+    }
+}
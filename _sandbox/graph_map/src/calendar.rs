@@ -0,0 +1,106 @@
+//! Dates for putting a schedule on a calendar, where each unit of time is
+//! a day.
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A day in the proleptic Gregorian calendar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    year: i32,
+    month: u32,
+    day: u32,
+}
+impl Date {
+    /// Returns `None` if there is no such day, like the 30th of February.
+    pub fn new (year: i32, month: u32, day: u32) -> Option<Self> {
+        let date = Date { year, month, day };
+        if (1..=12).contains(&month) && day >= 1 && Date::from_days(date.days()) == date {
+            Some(date)
+        }
+        else {
+            None
+        }
+    }
+    pub fn year (&self) -> i32 {
+        self.year
+    }
+    pub fn month (&self) -> u32 {
+        self.month
+    }
+    pub fn day (&self) -> u32 {
+        self.day
+    }
+    /// The date `days` days later.
+    pub fn add_days (&self, days: u32) -> Self {
+        Date::from_days(self.days() + i64::from(days))
+    }
+
+    /// Days since 1970-01-01, after Howard Hinnant's `days_from_civil`.
+    fn days (&self) -> i64 {
+        let year = i64::from(self.year) - if self.month <= 2 { 1 } else { 0 };
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let month = i64::from(self.month);
+        let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(self.day) - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+    fn from_days (days: i64) -> Self {
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days - era * 146_097;
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+        let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+        Date { year: year as i32, month: month as u32, day: day as u32 }
+    }
+}
+
+/// Formats as `YYYYMMDD`, the way iCalendar writes dates.
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}{:02}{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// The current time in UTC, the way iCalendar writes it.
+pub(crate) fn timestamp () -> String {
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map(|since| since.as_secs()).unwrap_or(0);
+    let date = Date::from_days((seconds / 86_400) as i64);
+    let time = seconds % 86_400;
+    format!("{}T{:02}{:02}{:02}Z", date, time / 3600, time / 60 % 60, time % 60)
+}
+
+/// Escapes the characters iCalendar text can't hold as they are.
+pub(crate) fn escape (text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '\\' | ';' | ',' => {
+                escaped.push('\\');
+                escaped.push(character);
+            }
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn dates () {
+        let start = Date::new(2024, 2, 27).unwrap();
+        assert_eq!(start.add_days(2), Date::new(2024, 2, 29).unwrap());
+        assert_eq!(start.add_days(3), Date::new(2024, 3, 1).unwrap());
+        assert_eq!(Date::new(2023, 12, 31).unwrap().add_days(1).to_string(), "20240101");
+        assert_eq!(Date::new(2023, 2, 29), None);
+        assert_eq!(Date::new(2024, 13, 1), None);
+    }
+}
@@ -0,0 +1,373 @@
+//! Grouping tasks into named sub-projects, which can be collapsed into one
+//! summary task and expanded again, and drawn as boxes in Graphviz.
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::hash::Hash;
+
+use uuid::Uuid;
+
+use crate::{Graph, GraphError, GraphView, Task};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClusterError<K> {
+    /// There is no cluster with the name.
+    UnknownCluster(&'static str),
+    /// The cluster is collapsed already.
+    Collapsed(&'static str),
+    /// The key for the summary task is taken.
+    KeyInUse(K),
+    /// The tasks in the cluster depend on each other in a cycle, so it has
+    /// no critical path.
+    Cycle(Vec<K>),
+}
+impl<K: fmt::Debug> fmt::Display for ClusterError<K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClusterError::UnknownCluster(name) => write!(f, "no cluster named {:?}", name),
+            ClusterError::Collapsed(name) => write!(f, "cluster {:?} is already collapsed", name),
+            ClusterError::KeyInUse(key) => write!(f, "there is already a node with key {:?}", key),
+            ClusterError::Cycle(cycle) => write!(f, "tasks in the cluster depend on each other in a cycle: {:?}", cycle),
+        }
+    }
+}
+impl<K: fmt::Debug> Error for ClusterError<K> {}
+
+/// What a collapsed cluster took out of the graph.
+#[derive(Debug, Clone)]
+struct Collapsed<K, E> {
+    summary: K,
+    members: Vec<(K, Task)>,
+    /// The edges within the cluster, and to and from it.
+    edges: Vec<((K, K), Vec<E>)>,
+}
+
+/// A task graph with some of its tasks grouped into named clusters. Each
+/// task is in one cluster at most.
+#[derive(Debug, Clone)]
+pub struct Clustered<E = (), K: Eq + Hash + Copy = Uuid> {
+    graph: Graph<Task, E, K>,
+    clusters: BTreeMap<&'static str, HashSet<K>>,
+    collapsed: HashMap<&'static str, Collapsed<K, E>>,
+}
+impl<E: Clone, K: Eq + Hash + Copy> Clustered<E, K> {
+    pub fn new (graph: Graph<Task, E, K>) -> Self {
+        Clustered { graph, clusters: BTreeMap::new(), collapsed: HashMap::new() }
+    }
+    /// The graph as it is, with collapsed clusters as their summary tasks.
+    pub fn graph (&self) -> &Graph<Task, E, K> {
+        &self.graph
+    }
+    /// The graph to change, with collapsed clusters as their summary tasks.
+    /// Edges added to a summary are moved onto the cluster's tasks when it
+    /// is expanded.
+    pub fn graph_mut (&mut self) -> &mut Graph<Task, E, K> {
+        &mut self.graph
+    }
+    /// Expands every cluster and returns the graph.
+    pub fn into_graph (mut self) -> Graph<Task, E, K> {
+        let names: Vec<&'static str> = self.collapsed.keys().copied().collect();
+        for name in names {
+            self.expand(name);
+        }
+        self.graph
+    }
+    /// Puts the task under `key` in the cluster `name`, making the cluster
+    /// if there isn't one, and taking the task out of any other.
+    pub fn add_to_cluster (&mut self, name: &'static str, key: &K) -> Result<(), GraphError<K>> {
+        if self.graph.get(key).is_none() {
+            return Err(GraphError::MissingNode(*key));
+        }
+        for members in self.clusters.values_mut() {
+            members.remove(key);
+        }
+        self.clusters.entry(name).or_default().insert(*key);
+        Ok(())
+    }
+    pub fn cluster (&self, name: &str) -> Option<&HashSet<K>> {
+        self.clusters.get(name)
+    }
+    pub fn cluster_of (&self, key: &K) -> Option<&'static str> {
+        self.clusters.iter()
+            .find(|(_, members)| members.contains(key))
+            .map(|(name, _)| *name)
+    }
+    pub fn is_collapsed (&self, name: &str) -> bool {
+        self.collapsed.contains_key(name)
+    }
+
+    /// Replaces the tasks of a cluster with one task under `summary`, named
+    /// after the cluster, that takes as long as the cluster's critical path.
+    /// Edges to and from the cluster's tasks go to and from the summary
+    /// instead, one for each task outside. Returns the summary's duration.
+    pub fn collapse (&mut self, name: &'static str, summary: K) -> Result<u32, ClusterError<K>> {
+        let members = self.clusters.get(name).ok_or(ClusterError::UnknownCluster(name))?;
+        if self.collapsed.contains_key(name) {
+            return Err(ClusterError::Collapsed(name));
+        }
+        if self.graph.get(&summary).is_some() {
+            return Err(ClusterError::KeyInUse(summary));
+        }
+
+        let mut inside: Graph<Task, (), K> = Graph::new();
+        for key in members {
+            inside.insert_node(*key, self.graph[key].clone());
+        }
+        for &(start, end) in self.graph.edges.keys() {
+            if members.contains(&start) && members.contains(&end) {
+                inside.push_edge(&start, &end, ());
+            }
+        }
+        if let Some(cycle) = inside.find_cycle() {
+            return Err(ClusterError::Cycle(cycle));
+        }
+        let duration = GraphView::new(&inside).project_end();
+
+        let pairs: Vec<(K, K)> = self.graph.edges.keys()
+            .filter(|(start, end)| members.contains(start) || members.contains(end))
+            .copied()
+            .collect();
+        let mut edges = Vec::new();
+        let mut redirected = Vec::new();
+        for (start, end) in pairs {
+            let parallel = self.graph.remove_parallel_edges(&start, &end);
+            match (members.contains(&start), members.contains(&end)) {
+                (true, false) => redirected.push((summary, end, parallel[0].clone())),
+                (false, true) => redirected.push((start, summary, parallel[0].clone())),
+                _ => {}
+            }
+            edges.push(((start, end), parallel));
+        }
+        let graph = &mut self.graph;
+        let members: Vec<(K, Task)> = members.iter()
+            .map(|key| (*key, graph.remove_node(key).unwrap()))
+            .collect();
+        self.graph.insert_node(summary, Task::new(name, duration));
+        for (start, end, data) in redirected {
+            self.graph.replace_edge(&start, &end, data);
+        }
+        self.collapsed.insert(name, Collapsed { summary, members, edges });
+        Ok(duration)
+    }
+    /// Puts back the tasks of a collapsed cluster in place of its summary.
+    /// Edges to tasks that have gone since are left out. Edges added to the
+    /// summary since it was collapsed are reattached: ones into it go to
+    /// each task in the cluster that has none before it, and ones out of it
+    /// leave each task that has none after it. A summary task can be put in
+    /// another cluster, to nest them, in which case expand the outer
+    /// cluster first. Returns whether the cluster was collapsed.
+    pub fn expand (&mut self, name: &str) -> bool {
+        let collapsed = match self.collapsed.remove(name) {
+            Some(collapsed) => collapsed,
+            None => return false,
+        };
+        let reattached = self.summary_edges(&collapsed);
+        self.graph.remove_node(&collapsed.summary);
+        for (key, task) in collapsed.members {
+            self.graph.insert_node(key, task);
+        }
+        for ((start, end), parallel) in collapsed.edges.into_iter().chain(reattached) {
+            if self.graph.check_ends(&start, &end).is_ok() {
+                for data in parallel {
+                    self.graph.push_edge(&start, &end, data);
+                }
+            }
+        }
+        true
+    }
+
+    /// Takes the edges added to a collapsed cluster's summary out of the
+    /// graph, and returns them moved onto the cluster's first and last
+    /// tasks.
+    fn summary_edges (&mut self, collapsed: &Collapsed<K, E>) -> Vec<((K, K), Vec<E>)> {
+        let summary = collapsed.summary;
+        let members: HashSet<K> = collapsed.members.iter().map(|(key, _)| *key).collect();
+        // the tasks outside the cluster that the summary's own edges went
+        // to and from, and the members with edges inside it.
+        let (mut before, mut after) = (HashSet::new(), HashSet::new());
+        let (mut has_previous, mut has_next) = (HashSet::new(), HashSet::new());
+        for &((start, end), _) in &collapsed.edges {
+            match (members.contains(&start), members.contains(&end)) {
+                (true, true) => {
+                    has_next.insert(start);
+                    has_previous.insert(end);
+                }
+                (true, false) => {
+                    after.insert(end);
+                }
+                (false, true) => {
+                    before.insert(start);
+                }
+                (false, false) => {}
+            }
+        }
+        let node = match self.graph.nodes.get(&summary) {
+            Some(node) => node,
+            None => return Vec::new(),
+        };
+        let added: Vec<(K, K)> = node.incoming.iter()
+            .filter(|start| **start != summary && !before.contains(start))
+            .map(|start| (*start, summary))
+            .chain(node.outgoing.iter()
+                .filter(|end| **end != summary && !after.contains(end))
+                .map(|end| (summary, *end)))
+            .collect();
+
+        let mut reattached = Vec::new();
+        for (start, end) in added {
+            let parallel = self.graph.remove_parallel_edges(&start, &end);
+            for member in &members {
+                if start == summary && !has_next.contains(member) {
+                    reattached.push(((*member, end), parallel.clone()));
+                } else if end == summary && !has_previous.contains(member) {
+                    reattached.push(((start, *member), parallel.clone()));
+                }
+            }
+        }
+        reattached
+    }
+
+    /// The graph in Graphviz's DOT language, each expanded cluster drawn
+    /// as a box around its tasks and each collapsed one as a single task.
+    pub fn to_dot (&self) -> String {
+        let mut keys: Vec<&K> = self.graph.node_ids().collect();
+        keys.sort_by_key(|key| self.graph[key].name);
+        let ids: HashMap<K, usize> = keys.iter().enumerate().map(|(id, key)| (**key, id)).collect();
+        let node = |key: &K| {
+            let task = &self.graph[key];
+            let shape = if self.collapsed.values().any(|collapsed| collapsed.summary == *key) { ", shape=box3d" } else { "" };
+            format!("n{} [label=\"{} ({})\"{}];", ids[key], escape(task.name), task.duration, shape)
+        };
+
+        let mut lines = vec!["digraph {".to_string()];
+        let mut clustered = HashSet::new();
+        for (index, (name, members)) in self.clusters.iter().enumerate() {
+            if self.collapsed.contains_key(name) {
+                continue;
+            }
+            lines.push(format!("    subgraph cluster_{} {{", index));
+            lines.push(format!("        label=\"{}\";", escape(name)));
+            for key in keys.iter().filter(|key| members.contains(key)) {
+                lines.push(format!("        {}", node(key)));
+                clustered.insert(**key);
+            }
+            lines.push("    }".to_string());
+        }
+        for key in keys.iter().filter(|key| !clustered.contains(key)) {
+            lines.push(format!("    {}", node(key)));
+        }
+        let mut edges: Vec<(usize, usize)> = self.graph.edges.keys()
+            .map(|(start, end)| (ids[start], ids[end]))
+            .collect();
+        edges.sort_unstable();
+        for (start, end) in edges {
+            lines.push(format!("    n{} -> n{};", start, end));
+        }
+        lines.push("}".to_string());
+        lines.join("\n") + "\n"
+    }
+}
+
+fn escape (text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn collapse_and_expand () {
+        let mut graph: Graph<Task, (), char> = Graph::new();
+        graph.insert_node('f', Task::new("Foundation", 1));
+        graph.insert_node('w', Task::new("Walls", 2));
+        graph.insert_node('r', Task::new("Roof", 4));
+        graph.insert_node('p', Task::new("Paint", 3));
+        graph.insert_node('m', Task::new("Move in", 1));
+        for &(start, end) in &[('f', 'w'), ('w', 'r'), ('w', 'p'), ('r', 'm'), ('p', 'm')] {
+            graph.add_edge(&start, &end).unwrap();
+        }
+
+        let mut clustered = Clustered::new(graph);
+        for key in &['w', 'r', 'p'] {
+            clustered.add_to_cluster("Shell", key).unwrap();
+        }
+        assert_eq!(clustered.add_to_cluster("Shell", &'x'), Err(GraphError::MissingNode('x')));
+        assert_eq!(clustered.cluster_of(&'r'), Some("Shell"));
+
+        assert_eq!(clustered.collapse("Shell", 'S'), Ok(6));
+        assert_eq!(clustered.collapse("Shell", 'T'), Err(ClusterError::Collapsed("Shell")));
+        assert_eq!(clustered.collapse("Roofing", 'T'), Err(ClusterError::UnknownCluster("Roofing")));
+        let graph = clustered.graph();
+        assert_eq!(graph.node_ids().count(), 3);
+        assert!(graph.has_edge(&'f', &'S') && graph.has_edge(&'S', &'m'));
+        assert_eq!(GraphView::new(graph).project_end(), 8);
+        assert!(clustered.to_dot().contains("[label=\"Shell (6)\", shape=box3d];"));
+
+        assert!(clustered.expand("Shell"));
+        assert!(!clustered.expand("Shell"));
+        let graph = clustered.graph();
+        assert_eq!(graph.node_ids().count(), 5);
+        assert_eq!(graph.edges().count(), 5);
+        assert!(graph.has_edge(&'w', &'p') && graph.has_edge(&'p', &'m'));
+    }
+
+    #[test]
+    fn expand_reattaches_new_edges () {
+        let mut graph: Graph<Task, u32, char> = Graph::new();
+        graph.insert_node('f', Task::new("Foundation", 1));
+        graph.insert_node('w', Task::new("Walls", 2));
+        graph.insert_node('r', Task::new("Roof", 4));
+        graph.insert_node('p', Task::new("Paint", 3));
+        graph.insert_edge(&'f', &'w', 1).unwrap();
+        graph.insert_edge(&'w', &'r', 2).unwrap();
+        graph.insert_edge(&'w', &'p', 3).unwrap();
+        let mut clustered = Clustered::new(graph);
+        for key in &['w', 'r', 'p'] {
+            clustered.add_to_cluster("Shell", key).unwrap();
+        }
+        clustered.collapse("Shell", 'S').unwrap();
+
+        let graph = clustered.graph_mut();
+        graph.insert_node('s', Task::new("Survey", 1));
+        graph.insert_node('m', Task::new("Move in", 1));
+        graph.insert_edge(&'s', &'S', 4).unwrap();
+        graph.insert_edge(&'S', &'m', 5).unwrap();
+        assert!(clustered.expand("Shell"));
+
+        let graph = clustered.graph();
+        assert_eq!(graph.edge(&'f', &'w'), Some(&1));
+        assert_eq!(graph.edge(&'s', &'w'), Some(&4));
+        assert_eq!(graph.edge(&'r', &'m'), Some(&5));
+        assert_eq!(graph.edge(&'p', &'m'), Some(&5));
+        assert_eq!(graph.edges().count(), 6);
+        assert_eq!(GraphView::new(graph).project_end(), 8);
+    }
+
+    #[test]
+    fn to_dot () {
+        let mut graph: Graph<Task, (), char> = Graph::new();
+        graph.insert_node('a', Task::new("Dig", 1));
+        graph.insert_node('b', Task::new("Pour \"slab\"", 2));
+        graph.insert_node('c', Task::new("Frame", 3));
+        graph.add_edge(&'a', &'b').unwrap();
+        graph.add_edge(&'b', &'c').unwrap();
+        let mut clustered = Clustered::new(graph);
+        clustered.add_to_cluster("Groundwork", &'a').unwrap();
+        clustered.add_to_cluster("Groundwork", &'b').unwrap();
+
+        let expected = "\
+digraph {
+    subgraph cluster_0 {
+        label=\"Groundwork\";
+        n0 [label=\"Dig (1)\"];
+        n2 [label=\"Pour \\\"slab\\\" (2)\"];
+    }
+    n1 [label=\"Frame (3)\"];
+    n0 -> n2;
+    n2 -> n1;
+}
+";
+        assert_eq!(clustered.to_dot(), expected);
+    }
+}
@@ -0,0 +1,125 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use crate::visit::GraphVisitor;
+use crate::Graph;
+
+/// Tarjan's algorithm: the depth first search order of each node, and the
+/// earliest node on the stack it can reach.
+struct Tarjan<K> {
+    order: HashMap<K, usize>,
+    lowest: HashMap<K, usize>,
+    /// The search's current path, to pass what a node can reach back to
+    /// the one before it when it finishes.
+    path: Vec<K>,
+    stack: Vec<K>,
+    on_stack: HashSet<K>,
+    components: Vec<Vec<K>>,
+}
+impl<K: Eq + Hash + Copy> Tarjan<K> {
+    fn reaches(&mut self, key: &K, order: usize) {
+        let lowest = self.lowest.get_mut(key).unwrap();
+        *lowest = (*lowest).min(order);
+    }
+}
+impl<K: Eq + Hash + Copy> GraphVisitor<K> for Tarjan<K> {
+    fn discover(&mut self, key: &K) {
+        let order = self.order.len();
+        self.order.insert(*key, order);
+        self.lowest.insert(*key, order);
+        self.path.push(*key);
+        self.stack.push(*key);
+        self.on_stack.insert(*key);
+    }
+    fn back_edge(&mut self, start: &K, end: &K) {
+        self.reaches(start, self.order[end]);
+    }
+    fn cross_edge(&mut self, start: &K, end: &K) {
+        // still on the stack, so in the component being built.
+        if self.on_stack.contains(end) {
+            self.reaches(start, self.order[end]);
+        }
+    }
+    fn finish(&mut self, key: &K) {
+        if self.lowest[key] == self.order[key] {
+            let start = self.stack.iter().position(|member| member == key).unwrap();
+            let component = self.stack.split_off(start);
+            for member in &component {
+                self.on_stack.remove(member);
+            }
+            self.components.push(component);
+        }
+        self.path.pop();
+        if let Some(before) = self.path.last().copied() {
+            self.reaches(&before, self.lowest[key]);
+        }
+    }
+}
+
+impl<T: Eq + Hash, E, K: Eq + Hash + Copy> Graph<T, E, K> {
+    /// Collapses each strongly connected component, the nodes on a cycle
+    /// together, into one node. The result has no cycles. Its nodes are
+    /// numbered from 0 and hold the keys of their component, and there is
+    /// an edge from one to another if any node in the first had an edge to
+    /// any node in the second. Also returns the number of the component
+    /// each key ended up in.
+    pub fn condense(&self) -> (Graph<Vec<K>, (), usize>, HashMap<K, usize>) {
+        let mut tarjan = Tarjan {
+            order: HashMap::new(),
+            lowest: HashMap::new(),
+            path: Vec::new(),
+            stack: Vec::new(),
+            on_stack: HashSet::new(),
+            components: Vec::new(),
+        };
+        self.depth_first(&mut tarjan);
+
+        let mut membership = HashMap::new();
+        let mut condensed = Graph::new();
+        for (index, component) in tarjan.components.into_iter().enumerate() {
+            for key in &component {
+                membership.insert(*key, index);
+            }
+            condensed.insert_node(index, component);
+        }
+        for &(start, end) in self.edges.keys() {
+            let (start, end) = (membership[&start], membership[&end]);
+            if start != end {
+                condensed.replace_edge(&start, &end, ());
+            }
+        }
+        (condensed, membership)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn condense () {
+        let mut graph = Graph::new();
+        for node in 1..=6 {
+            graph.insert_node(node, node);
+        }
+        graph.add_edge(&1, &2).unwrap();
+        graph.add_edge(&2, &3).unwrap();
+        graph.add_edge(&3, &1).unwrap();
+        graph.add_edge(&3, &4).unwrap();
+        graph.add_edge(&4, &5).unwrap();
+        graph.add_edge(&5, &4).unwrap();
+        graph.add_edge(&2, &5).unwrap();
+
+        let (condensed, membership) = graph.condense();
+        assert_eq!(condensed.node_ids().count(), 3);
+        assert_eq!(condensed.find_cycle(), None);
+        let (first, second, alone) = (membership[&1], membership[&4], membership[&6]);
+        assert!(membership[&2] == first && membership[&3] == first && membership[&5] == second);
+        let mut members = condensed[&first].clone();
+        members.sort_unstable();
+        assert_eq!(members, vec![1, 2, 3]);
+        assert_eq!(condensed[&alone], vec![6]);
+        assert_eq!(condensed.edges().count(), 1);
+        assert!(condensed.has_edge(&first, &second));
+    }
+}
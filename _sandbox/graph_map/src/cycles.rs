@@ -1,5 +1,13 @@
+//! A doubly linked list with a cursor, linked forwards by `Rc` and
+//! backwards by `Weak` so the links don't keep each other alive. The list
+//! has a head and a tail that hold no data, so the cursor can sit before
+//! the first element or after the last.
 use std::rc::{Rc, Weak};
-use std::cell::{RefCell};
+use std::cell::{Ref, RefCell, RefMut};
+use std::fmt;
+use std::iter::FromIterator;
+
+use heap_size::{rc_allocation, HeapSize, Seen};
 
 #[derive(Clone, Debug)]
 enum Elem<T> {
@@ -14,11 +22,10 @@ impl<T> ListElem<T> {
     fn new_tail() -> Rc<Self> {
         Rc::new(ListElem(RefCell::new(Elem::Tail(Weak::new()))))
     }
-    fn new_head(tail: Rc<Self>) -> Rc<Self> {
-        let head = Rc::new(ListElem(RefCell::new(Elem::Head(tail))));
-        head.set_next(tail);
+    fn new_head(next: Rc<Self>) -> Rc<Self> {
+        let head = Rc::new(ListElem(RefCell::new(Elem::Head(next.clone()))));
+        next.set_previous(head.clone());
         head
-
     }
     fn between(before: Rc<Self>, data: T, after: Rc<Self>) -> Rc<Self> {
         let node_inner = Elem::Node(Rc::downgrade(&before), data, after.clone());
@@ -27,18 +34,12 @@ impl<T> ListElem<T> {
         before.set_next(node.clone());
         node
     }
-    
+
     fn is_tail (&self) -> bool {
-        match *self.0.borrow_mut() {
-            Elem::Tail(_) => true,
-            _ => false
-        }
+        matches!(*self.0.borrow(), Elem::Tail(_))
     }
     fn is_head (&self) -> bool {
-        match *self.0.borrow_mut() {
-            Elem::Head(_) => true,
-            _ => false,
-        }
+        matches!(*self.0.borrow(), Elem::Head(_))
     }
 
     fn next (&self) -> Option<Rc<Self>> {
@@ -74,25 +75,37 @@ impl<T> ListElem<T> {
         first.set_next(other.clone());
         other.set_previous(first);
     }
+    /// Takes a node out of its list, joining the elements either side of it,
+    /// and returns its data. Nothing else may hold the node.
+    fn unlink(node: Rc<Self>) -> T {
+        ListElem::connect(node.previous().unwrap(), node.next().unwrap());
+        match Rc::try_unwrap(node).ok().map(|node| node.0.into_inner()) {
+            Some(Elem::Node(_, data, _)) => data,
+            _ => unreachable!("Tried to unlink a shared node, or not a node"),
+        }
+    }
 }
 
-#[derive(Debug)]
-struct List<T> {
+pub struct List<T> {
     head: Rc<ListElem<T>>,
     current: Rc<ListElem<T>>,
     tail: Rc<ListElem<T>>
 }
 
 impl<T> List<T> {
+    /// An empty list with the cursor at the head.
     pub fn new () -> Self {
         let tail = ListElem::new_tail();
         let head = ListElem::new_head(tail.clone());
         List {
             head: head.clone(),
             current: head,
-            tail: tail,
+            tail,
         }
     }
+    pub fn is_empty (&self) -> bool {
+        self.head.next().unwrap().is_tail()
+    }
     pub fn advance (&mut self) {
         if let Some(next) = self.current.next() {
             self.current = next;
@@ -105,86 +118,567 @@ impl<T> List<T> {
         }
         debug_assert!(self.invariant());
     }
+    /// Moves the cursor to the element at `index`, counting from zero, or
+    /// to the tail if there are too few. Returns whether there was one.
+    pub fn seek_to (&mut self, index: usize) -> bool {
+        self.current = self.head.clone();
+        for _ in 0..=index {
+            self.advance();
+            if self.current.is_tail() {
+                return false;
+            }
+        }
+        true
+    }
+    /// The element under the cursor, or `None` at the head or tail. Moving
+    /// the cursor or changing the list around it while this is held panics.
+    pub fn current (&self) -> Option<Ref<'_, T>> {
+        Ref::filter_map(self.current.0.borrow(), |elem| match elem {
+            Elem::Node(_, data, _) => Some(data),
+            _ => None,
+        }).ok()
+    }
+    /// As `current`, but to change the element.
+    pub fn current_mut (&self) -> Option<RefMut<'_, T>> {
+        RefMut::filter_map(self.current.0.borrow_mut(), |elem| match elem {
+            Elem::Node(_, data, _) => Some(data),
+            _ => None,
+        }).ok()
+    }
+    /// Does nothing at the tail.
     pub fn insert_after (&mut self, data: T) {
-        self.current.next().map(|next| {
+        if let Some(next) = self.current.next() {
             ListElem::between(self.current.clone(), data, next);
-        });
+        }
         debug_assert!(self.invariant());
     }
+    /// Does nothing at the head.
     pub fn insert_before (&mut self, data: T) {
-        self.current.previous().map(|previous| {
+        if let Some(previous) = self.current.previous() {
             ListElem::between(previous, data, self.current.clone());
-        });
+        }
         debug_assert!(self.invariant());
     }
 
-    pub fn split_after (&mut self) -> Self {
-        self.current.next().map_or_else(|| List::new(), |next| {
-            let old_tail = std::mem::replace(&mut self.tail, ListElem::new_tail());
-            ListElem::connect(self.current.clone(), self.tail.clone());
+    /// Removes the element after the cursor, if there is one.
+    pub fn remove_after (&mut self) -> Option<T> {
+        let next = self.current.next().filter(|next| !next.is_tail())?;
+        let data = ListElem::unlink(next);
+        debug_assert!(self.invariant());
+        Some(data)
+    }
+    /// Removes the element before the cursor, if there is one.
+    pub fn remove_before (&mut self) -> Option<T> {
+        let previous = self.current.previous().filter(|previous| !previous.is_head())?;
+        let data = ListElem::unlink(previous);
+        debug_assert!(self.invariant());
+        Some(data)
+    }
+    /// Removes the element under the cursor, moving the cursor on to the
+    /// element after it, or the tail. Does nothing at the head or tail.
+    pub fn remove_current (&mut self) -> Option<T> {
+        if self.current.is_head() || self.current.is_tail() {
+            return None;
+        }
+        let next = self.current.next().unwrap();
+        let current = std::mem::replace(&mut self.current, next);
+        let data = ListElem::unlink(current);
+        debug_assert!(self.invariant());
+        Some(data)
+    }
 
-            let new_head = ListElem::new_head(next.clone());
+    /// Removes the elements `keep` returns false for, in one pass from the
+    /// head. If the element under the cursor goes, the cursor moves on to
+    /// the next one left, or the tail.
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut keep: F) {
+        let mut elem = self.head.next().unwrap();
+        while !elem.is_tail() {
+            let next = elem.next().unwrap();
+            let kept = match *elem.0.borrow() {
+                Elem::Node(_, ref data, _) => keep(data),
+                _ => true,
+            };
+            if !kept {
+                if Rc::ptr_eq(&elem, &self.current) {
+                    self.current = next.clone();
+                }
+                ListElem::unlink(elem);
+            }
+            elem = next;
+        }
+        debug_assert!(self.invariant());
+    }
 
-            debug_assert!(self.invariant());
-            List {
-                head: new_head.clone(),
-                current: new_head,
-                tail: old_tail,
+    /// Takes the elements out from head to tail, leaving the list empty with
+    /// the cursor at the head, even if the iterator is dropped part way.
+    pub fn drain (&mut self) -> Drain<'_, T> {
+        self.current = self.head.clone();
+        Drain { list: self }
+    }
+
+    /// Moves the elements after the cursor into a new list, with its cursor
+    /// at its head. At the tail, or the last element, the new list is empty.
+    pub fn split_after (&mut self) -> Self {
+        match self.current.next() {
+            Some(next) if !next.is_tail() => {
+                let old_tail = std::mem::replace(&mut self.tail, ListElem::new_tail());
+                ListElem::connect(self.current.clone(), self.tail.clone());
+                let new_head = ListElem::new_head(next);
+
+                debug_assert!(self.invariant());
+                List {
+                    head: new_head.clone(),
+                    current: new_head,
+                    tail: old_tail,
+                }
             }
-        })
+            _ => List::new(),
+        }
     }
+    /// Moves the elements before the cursor into a new list, with its
+    /// cursor at its head. At the head, or the first element, the new list
+    /// is empty.
     pub fn split_before (&mut self) -> Self {
-        self.current.previous().map_or_else(|| List::new(), |previous| {
-            let new_tail = ListElem::new_tail();
-            ListElem::connect(previous, new_tail.clone());
-
-            let old_head = std::mem::replace(&mut self.head, ListElem::new_head(self.current.clone()));
+        match self.current.previous() {
+            Some(previous) if !previous.is_head() => {
+                let new_tail = ListElem::new_tail();
+                ListElem::connect(previous, new_tail.clone());
+                let old_head = std::mem::replace(&mut self.head, ListElem::new_head(self.current.clone()));
 
-            debug_assert!(self.invariant());
-            List {
-                head: old_head.clone(),
-                current: old_head,
-                tail: new_tail,
+                debug_assert!(self.invariant());
+                List {
+                    head: old_head.clone(),
+                    current: old_head,
+                    tail: new_tail,
+                }
             }
-        })
+            _ => List::new(),
+        }
+    }
+    /// Keeps the first `index` elements and returns a list of the rest, or
+    /// an empty one if there are too few. Both cursors end at the head.
+    pub fn split_at (&mut self, index: usize) -> Self {
+        self.seek_to(index);
+        let front = self.split_before();
+        let mut rest = std::mem::replace(self, front);
+        rest.current = rest.head.clone();
+        rest
     }
-    pub fn join (&mut self, other: Self) {
-        let last = self.tail.previous.unwrap();
+    /// Moves the elements of `other` onto the end of this list. A cursor at
+    /// the tail stays at the tail.
+    pub fn join (&mut self, mut other: Self) {
         let first = other.head.next().unwrap();
+        if first.is_tail() {
+            return;
+        }
+        let last = self.tail.previous().unwrap();
         ListElem::connect(last, first);
-        self.tail = other.tail;
+        let at_tail = Rc::ptr_eq(&self.current, &self.tail);
+        self.tail = other.detach();
+        if at_tail {
+            self.current = self.tail.clone();
+        }
+        debug_assert!(self.invariant());
+    }
+    /// Moves the elements of `other` in after the cursor, in order, leaving
+    /// the cursor where it is. At the tail they go on the end, before it.
+    pub fn splice_after (&mut self, mut other: Self) {
+        let first = other.head.next().unwrap();
+        if first.is_tail() {
+            return;
+        }
+        let last = other.tail.previous().unwrap();
+        other.detach();
+        let (before, after) = match self.current.next() {
+            Some(next) => (self.current.clone(), next),
+            None => (self.current.previous().unwrap(), self.current.clone()),
+        };
+        ListElem::connect(before, first);
+        ListElem::connect(last, after);
         debug_assert!(self.invariant());
     }
 
+    /// Leaves this list empty without dropping its elements, which belong
+    /// to another list now, and returns its old tail.
+    fn detach (&mut self) -> Rc<ListElem<T>> {
+        let tail = std::mem::replace(&mut self.tail, ListElem::new_tail());
+        ListElem::connect(self.head.clone(), self.tail.clone());
+        self.current = self.head.clone();
+        tail
+    }
+
+    /// Writes the elements as `[a, b, | c, d]`, with the cursor on `c`, or
+    /// `[|, a, b]` and `[a, b, |]` at the head and tail.
+    fn fmt_with (&self, f: &mut fmt::Formatter, item: FmtFn<T>) -> fmt::Result {
+        let mut entries = f.debug_list();
+        let mut elem = self.head.clone();
+        loop {
+            let cursor = Rc::ptr_eq(&elem, &self.current);
+            match *elem.0.borrow() {
+                Elem::Node(_, ref data, _) => { entries.entry(&Entry { data: Some(data), cursor, item }); }
+                _ if cursor => { entries.entry(&Entry { data: None, cursor, item }); }
+                _ => {}
+            }
+            match elem.next() {
+                Some(next) => elem = next,
+                None => break,
+            }
+        }
+        entries.finish()
+    }
+
     fn invariant (&self) -> bool {
         let head_is_head = self.head.is_head();
         let tail_is_tail = self.tail.is_tail();
-        let self_inner = &*self.current.0.borrow();
-        let next_previous_is_self = self.current.next().map_or(true, |next|{
-            let self_rc = next.previous().unwrap();
-            let self_rc_inner = &*self_rc.0.borrow();
-            same_object::<Elem<T>> (self_rc_inner, self_inner)
+        let next_previous_is_self = self.current.next().is_none_or(|next| {
+            Rc::ptr_eq(&next.previous().unwrap(), &self.current)
         });
-        let previous_next_is_self = self.current.previous().map_or(true, |previous| {
-            let self_rc = previous.next().unwrap();
-            let self_rc_inner = &*self_rc.0.borrow();
-            same_object::<Elem<T>> (self_rc_inner, self_inner)
+        let previous_next_is_self = self.current.previous().is_none_or(|previous| {
+            Rc::ptr_eq(&previous.next().unwrap(), &self.current)
         });
         head_is_head && tail_is_tail && next_previous_is_self && previous_next_is_self
     }
 }
+pub struct Drain<'a, T> {
+    list: &'a mut List<T>,
+}
+impl<'a, T> Iterator for Drain<'a, T> {
+    type Item = T;
+    fn next (&mut self) -> Option<T> {
+        self.list.remove_after()
+    }
+}
+impl<'a, T> Drop for Drain<'a, T> {
+    fn drop (&mut self) {
+        self.for_each(drop);
+    }
+}
 
-fn same_object<T>(a: *const T, b: *const T) -> bool {
-    a == b
+impl<T: Ord> List<T> {
+    /// Inserts `data` into a sorted list where it belongs, after any equal
+    /// elements, looking from the cursor, which is left on the new element.
+    pub fn insert_sorted (&mut self, data: T) {
+        if self.current.is_tail() {
+            self.retreat();
+        }
+        while self.current().is_some_and(|current| *current > data) {
+            self.retreat();
+        }
+        loop {
+            self.advance();
+            if self.current().is_none_or(|current| *current > data) {
+                self.retreat();
+                break;
+            }
+        }
+        self.insert_after(data);
+        self.advance();
+    }
 }
+type FmtFn<T> = fn(&T, &mut fmt::Formatter) -> fmt::Result;
 
-fn main() {
-    let mut list = List::new();
-    list.advance();
-    list.insert_before(5);
-    list.insert_before(6);
+/// One element, or the cursor alone, in the list's `Debug` or `Display`.
+struct Entry<'a, T> {
+    data: Option<&'a T>,
+    cursor: bool,
+    item: FmtFn<T>,
+}
+impl<'a, T> fmt::Debug for Entry<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.cursor, self.data) {
+            (true, Some(data)) => {
+                write!(f, "| ")?;
+                (self.item)(data, f)
+            }
+            (false, Some(data)) => (self.item)(data, f),
+            (_, None) => write!(f, "|"),
+        }
+    }
+}
+impl<T: fmt::Debug> fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_with(f, fmt::Debug::fmt)
+    }
+}
+impl<T: fmt::Display> fmt::Display for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_with(f, fmt::Display::fmt)
+    }
+}
+
+/// Frees the elements one at a time from the head, rather than each freeing
+/// the next in turn, which would overflow the stack on a long list.
+impl<T> Drop for List<T> {
+    fn drop (&mut self) {
+        self.current = self.head.clone();
+        let mut elem = self.head.next().unwrap();
+        ListElem::connect(self.head.clone(), self.tail.clone());
+        while !elem.is_tail() {
+            elem = match Rc::try_unwrap(elem) {
+                Ok(node) => match node.0.into_inner() {
+                    Elem::Node(_, _, next) => next,
+                    _ => unreachable!("Only the head and tail aren't nodes"),
+                },
+                // held by something else, which will free the rest.
+                Err(_) => break,
+            };
+        }
+    }
+}
+/// Builds a list with the cursor at the head.
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let list = List::new();
+        for data in iter {
+            ListElem::between(list.tail.previous().unwrap(), data, list.tail.clone());
+        }
+        list
+    }
+}
+/// Inserts the items after the cursor, in order, as `splice_after` does.
+impl<T> Extend<T> for List<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.splice_after(iter.into_iter().collect());
+    }
+}
+/// Walks the elements in a loop, so long lists don't overflow the stack.
+/// The head and tail are allocations too.
+impl<T: HeapSize> HeapSize for List<T> {
+    fn heap_size_shared(&self, seen: &mut Seen) -> usize {
+        let mut size = 0;
+        let mut elem = Some(self.head.clone());
+        while let Some(current) = elem {
+            if !seen.insert(Rc::as_ptr(&current) as *const ()) {
+                break;
+            }
+            size += rc_allocation::<ListElem<T>>();
+            if let Elem::Node(_, ref data, _) = *current.0.borrow() {
+                size += data.heap_size_shared(seen);
+            }
+            elem = current.next();
+        }
+        size
+    }
+}
+impl<T> Default for List<T> {
+    fn default () -> Self {
+        List::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// The elements from head to tail.
+    fn contents<T: Clone>(list: &List<T>) -> Vec<T> {
+        let mut contents = Vec::new();
+        let mut elem = list.head.next().unwrap();
+        while let Some(next) = elem.next() {
+            if let Elem::Node(_, ref data, _) = *elem.0.borrow() {
+                contents.push(data.clone());
+            }
+            elem = next;
+        }
+        contents
+    }
+
+    fn list_of(values: &[u32]) -> List<u32> {
+        let mut list = List::new();
+        list.advance();
+        for value in values {
+            list.insert_before(*value);
+        }
+        list
+    }
 
-    println!("{:?}", list);
+    #[test]
+    fn split () {
+        // the cursor is at the tail.
+        let mut list = list_of(&[1, 2, 3, 4]);
+        assert!(list.split_after().is_empty());
+        list.retreat();
+        list.retreat();
+        let after = list.split_after();
+        assert_eq!((contents(&list), contents(&after)), (vec![1, 2, 3], vec![4]));
 
-    println!("{:?}", vec![1, 2, 3, 4]);
-}
\ No newline at end of file
+        let before = list.split_before();
+        assert_eq!((contents(&before), contents(&list)), (vec![1, 2], vec![3]));
+        assert!(list.split_before().is_empty());
+        list.retreat();
+        assert!(list.split_before().is_empty());
+        // at the head, everything goes.
+        assert_eq!(contents(&list.split_after()), vec![3]);
+        assert!(list.is_empty());
+        assert!(List::<u32>::new().split_after().is_empty());
+    }
+
+    #[test]
+    fn join_and_splice () {
+        let mut list = list_of(&[1, 2]);
+        list.join(list_of(&[3, 4]));
+        list.join(List::new());
+        assert_eq!(contents(&list), vec![1, 2, 3, 4]);
+        list.insert_before(5);
+        assert_eq!(contents(&list), vec![1, 2, 3, 4, 5]);
+
+        let mut empty = List::new();
+        empty.join(list_of(&[1]));
+        assert_eq!(contents(&empty), vec![1]);
+
+        // at the head, the tail, and in the middle.
+        let mut list = list_of(&[1, 2]);
+        list.splice_after(list_of(&[3]));
+        assert_eq!(contents(&list), vec![1, 2, 3]);
+        list.retreat();
+        list.retreat();
+        list.splice_after(list_of(&[4, 5]));
+        assert_eq!(contents(&list), vec![1, 2, 4, 5, 3]);
+        list.retreat();
+        list.retreat();
+        list.splice_after(list_of(&[6]));
+        list.splice_after(List::new());
+        assert_eq!(contents(&list), vec![6, 1, 2, 4, 5, 3]);
+    }
+
+    #[test]
+    fn remove () {
+        let mut list = list_of(&[1, 2, 3, 4]);
+        assert_eq!(list.remove_after(), None);
+        assert_eq!(list.remove_before(), Some(4));
+        list.retreat();
+        assert_eq!(list.remove_current(), Some(3));
+        // the cursor is on the tail now, after 1 and 2.
+        assert_eq!(list.remove_current(), None);
+        list.retreat();
+        list.retreat();
+        assert_eq!(list.remove_before(), None);
+        assert_eq!(list.remove_after(), Some(2));
+        assert_eq!(contents(&list), vec![1]);
+        assert_eq!(list.remove_current(), Some(1));
+        assert!(list.is_empty());
+        assert_eq!(list.remove_before(), None);
+    }
+
+    #[test]
+    fn current () {
+        let mut list = list_of(&[1, 2]);
+        assert!(list.current().is_none());
+        list.retreat();
+        assert_eq!(*list.current().unwrap(), 2);
+        *list.current_mut().unwrap() += 10;
+        list.retreat();
+        list.retreat();
+        assert!(list.current_mut().is_none());
+        assert_eq!(contents(&list), vec![1, 12]);
+    }
+
+    #[test]
+    fn drop () {
+        let counted = Rc::new(());
+        let mut list = List::new();
+        for _ in 0..3 {
+            list.insert_after(counted.clone());
+        }
+        list.advance();
+        let mut other = list.split_after();
+        other.join(List::new());
+        list.join(other);
+        std::mem::drop(list);
+        assert_eq!(Rc::strong_count(&counted), 1);
+
+        let mut long = List::new();
+        for value in 0..1_000_000 {
+            long.insert_after(value);
+        }
+    }
+
+    #[test]
+    fn insert_sorted () {
+        let mut list = List::new();
+        for value in &[5, 1, 4, 1, 9, 2, 6] {
+            list.insert_sorted(*value);
+            assert_eq!(*list.current().unwrap(), *value);
+        }
+        assert_eq!(contents(&list), vec![1, 1, 2, 4, 5, 6, 9]);
+        list.advance();
+        list.advance();
+        list.insert_sorted(0);
+        list.insert_sorted(10);
+        assert_eq!(contents(&list), vec![0, 1, 1, 2, 4, 5, 6, 9, 10]);
+    }
+
+    #[test]
+    fn seek_and_split_at () {
+        let mut list = list_of(&[1, 2, 3, 4]);
+        assert!(list.seek_to(2));
+        assert_eq!(*list.current().unwrap(), 3);
+        assert!(!list.seek_to(4));
+        assert!(list.current().is_none());
+
+        let rest = list.split_at(1);
+        assert_eq!((contents(&list), contents(&rest)), (vec![1], vec![2, 3, 4]));
+        assert!(list.split_at(5).is_empty());
+        assert!(!list.seek_to(usize::MAX));
+        assert!(list.split_at(usize::MAX).is_empty());
+        assert_eq!(contents(&list.split_at(0)), vec![1]);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn retain () {
+        let mut list = list_of(&[1, 2, 3, 4, 5, 6]);
+        list.seek_to(2);
+        list.retain(|value| value % 3 != 0);
+        assert_eq!(contents(&list), vec![1, 2, 4, 5]);
+        assert_eq!(*list.current().unwrap(), 4);
+        list.retain(|_| false);
+        assert!(list.is_empty() && list.current().is_none());
+    }
+
+    #[test]
+    fn drain () {
+        let mut list = list_of(&[1, 2, 3]);
+        assert_eq!(list.drain().collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert!(list.is_empty() && list.invariant());
+
+        let mut list = list_of(&[1, 2, 3]);
+        assert_eq!(list.drain().next(), Some(1));
+        assert!(list.is_empty());
+        list.insert_after(4);
+        assert_eq!(contents(&list), vec![4]);
+    }
+
+    #[test]
+    fn collect_and_extend () {
+        let mut list: List<u32> = (1..=3).collect();
+        assert_eq!(contents(&list), vec![1, 2, 3]);
+        list.advance();
+        list.extend(vec![4, 5]);
+        assert_eq!(contents(&list), vec![1, 4, 5, 2, 3]);
+        assert_eq!(*list.current().unwrap(), 1);
+        list.seek_to(5);
+        list.extend(6..8);
+        assert_eq!(contents(&list), vec![1, 4, 5, 2, 3, 6, 7]);
+    }
+
+    #[test]
+    fn debug () {
+        let mut list: List<&str> = vec!["a", "b", "c"].into_iter().collect();
+        assert_eq!(format!("{:?}", list), r#"[|, "a", "b", "c"]"#);
+        list.seek_to(2);
+        assert_eq!(format!("{:?}", list), r#"["a", "b", | "c"]"#);
+        assert_eq!(list.to_string(), "[a, b, | c]");
+        list.advance();
+        assert_eq!(list.to_string(), "[a, b, c, |]");
+        assert_eq!(format!("{:?}", List::<u32>::new()), "[|]");
+    }
+
+    #[test]
+    fn heap_size () {
+        let elem = rc_allocation::<ListElem<String>>();
+        assert_eq!(List::<String>::new().heap_size(), 2 * elem);
+        let list: List<String> = vec![String::with_capacity(10), String::new()].into_iter().collect();
+        assert_eq!(list.heap_size(), 4 * elem + 10);
+    }
+}
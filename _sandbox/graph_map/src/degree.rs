@@ -0,0 +1,69 @@
+use std::collections::BTreeMap;
+use std::hash::Hash;
+
+use crate::Graph;
+
+/// How many nodes have each in and out degree.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DegreeDistribution {
+    pub in_degrees: BTreeMap<usize, usize>,
+    pub out_degrees: BTreeMap<usize, usize>,
+}
+impl DegreeDistribution {
+    /// The number of nodes no edge goes to.
+    pub fn sources(&self) -> usize {
+        self.in_degrees.get(&0).copied().unwrap_or(0)
+    }
+    /// The number of nodes no edge leaves.
+    pub fn sinks(&self) -> usize {
+        self.out_degrees.get(&0).copied().unwrap_or(0)
+    }
+}
+
+impl<T: Eq + Hash, E, K: Eq + Hash + Copy> Graph<T, E, K> {
+    /// The number of edges to `key`, counting each parallel edge, or `None`
+    /// if there is no node under it.
+    pub fn in_degree(&self, key: &K) -> Option<usize> {
+        let node = self.nodes.get(key)?;
+        Some(node.incoming.iter().map(|start| self.edges[&(*start, *key)].len()).sum())
+    }
+    /// The number of edges from `key`, counting each parallel edge.
+    pub fn out_degree(&self, key: &K) -> Option<usize> {
+        let node = self.nodes.get(key)?;
+        Some(node.outgoing.iter().map(|end| self.edges[&(*key, *end)].len()).sum())
+    }
+    pub fn degree_distribution(&self) -> DegreeDistribution {
+        let mut distribution = DegreeDistribution::default();
+        for key in self.nodes.keys() {
+            *distribution.in_degrees.entry(self.in_degree(key).unwrap()).or_insert(0) += 1;
+            *distribution.out_degrees.entry(self.out_degree(key).unwrap()).or_insert(0) += 1;
+        }
+        distribution
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn degrees () {
+        let mut graph = Graph::new();
+        for node in 1..=4 {
+            graph.insert_node(node, node);
+        }
+        graph.add_edge(&1, &2).unwrap();
+        graph.add_edge(&1, &3).unwrap();
+        graph.add_parallel_edge(&2, &3, ()).unwrap();
+        graph.add_parallel_edge(&2, &3, ()).unwrap();
+
+        assert_eq!(graph.out_degree(&1), Some(2));
+        assert_eq!(graph.in_degree(&3), Some(3));
+        assert_eq!(graph.in_degree(&9), None);
+
+        let distribution = graph.degree_distribution();
+        assert_eq!(distribution.in_degrees, vec![(0, 2), (1, 1), (3, 1)].into_iter().collect());
+        assert_eq!(distribution.out_degrees, vec![(0, 2), (2, 2)].into_iter().collect());
+        assert_eq!((distribution.sources(), distribution.sinks()), (2, 2));
+    }
+}
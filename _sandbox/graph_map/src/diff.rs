@@ -0,0 +1,136 @@
+use std::collections::HashSet;
+use std::hash::Hash;
+
+use crate::{same_edges, Graph};
+
+/// What changed between two versions of a graph, by key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphDiff<K: Eq + Hash> {
+    pub added_nodes: HashSet<K>,
+    pub removed_nodes: HashSet<K>,
+    /// Nodes in both versions whose data differs.
+    pub changed_nodes: HashSet<K>,
+    pub added_edges: HashSet<(K, K)>,
+    pub removed_edges: HashSet<(K, K)>,
+    /// Pairs of nodes joined in both versions whose edge data, or number
+    /// of parallel edges, differs.
+    pub changed_edges: HashSet<(K, K)>,
+}
+impl<K: Eq + Hash> GraphDiff<K> {
+    /// Whether the two versions have the same nodes and edges.
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.changed_nodes.is_empty()
+            && self.added_edges.is_empty()
+            && self.removed_edges.is_empty()
+            && self.changed_edges.is_empty()
+    }
+}
+
+impl<T: Eq + Hash, E: PartialEq, K: Eq + Hash + Copy> Graph<T, E, K> {
+    /// The changes that turn this graph into `other`. Nodes are matched by
+    /// key, and `same` decides whether a node's data has changed, so
+    /// fields that don't matter to the comparison can be ignored. Parallel
+    /// edges are compared regardless of the order they were added in.
+    pub fn diff<F: Fn(&T, &T) -> bool>(&self, other: &Graph<T, E, K>, same: F) -> GraphDiff<K> {
+        let mut diff = GraphDiff {
+            added_nodes: HashSet::new(),
+            removed_nodes: HashSet::new(),
+            changed_nodes: HashSet::new(),
+            added_edges: HashSet::new(),
+            removed_edges: HashSet::new(),
+            changed_edges: HashSet::new(),
+        };
+        for (key, node) in &self.nodes {
+            match other.nodes.get(key) {
+                Some(other_node) if !same(&node.data, &other_node.data) => {
+                    diff.changed_nodes.insert(*key);
+                }
+                Some(_) => {}
+                None => {
+                    diff.removed_nodes.insert(*key);
+                }
+            }
+        }
+        diff.added_nodes = other.nodes.keys()
+            .filter(|key| !self.nodes.contains_key(key))
+            .copied()
+            .collect();
+        for (edge, parallel) in &self.edges {
+            match other.edges.get(edge) {
+                Some(other_parallel) if !same_edges(parallel, other_parallel) => {
+                    diff.changed_edges.insert(*edge);
+                }
+                Some(_) => {}
+                None => {
+                    diff.removed_edges.insert(*edge);
+                }
+            }
+        }
+        diff.added_edges = other.edges.keys()
+            .filter(|edge| !self.edges.contains_key(edge))
+            .copied()
+            .collect();
+        diff
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Task;
+
+    #[test]
+    fn diff () {
+        let mut before = Graph::new();
+        before.insert_node(1, Task::new("Lay foundation", 1));
+        before.insert_node(2, Task::new("Build walls", 2));
+        before.insert_node(3, Task::new("Build roof", 4));
+        before.add_edge(&1, &2).unwrap();
+        before.add_edge(&2, &3).unwrap();
+        assert!(before.diff(&before, |a, b| a == b).is_empty());
+
+        let mut after = before.clone();
+        after.get_mut(&2).unwrap().duration = 3;
+        after.get_mut(&3).unwrap().name = "Build a roof";
+        after.remove_node(&1);
+        after.insert_node(4, Task::new("Paint walls", 8));
+        after.add_edge(&2, &4).unwrap();
+
+        let diff = before.diff(&after, |a, b| a.duration == b.duration);
+        assert_eq!(diff.added_nodes, vec![4].into_iter().collect());
+        assert_eq!(diff.removed_nodes, vec![1].into_iter().collect());
+        assert_eq!(diff.changed_nodes, vec![2].into_iter().collect());
+        assert_eq!(diff.added_edges, vec![(2, 4)].into_iter().collect());
+        assert_eq!(diff.removed_edges, vec![(1, 2)].into_iter().collect());
+        assert!(diff.changed_edges.is_empty());
+    }
+
+    #[test]
+    fn edge_data () {
+        let mut before: Graph<&str, u32, char> = Graph::new();
+        before.insert_node('a', "a");
+        before.insert_node('b', "b");
+        before.insert_node('c', "c");
+        before.insert_edge(&'a', &'b', 1).unwrap();
+        before.add_parallel_edge(&'b', &'c', 2).unwrap();
+        before.add_parallel_edge(&'b', &'c', 3).unwrap();
+
+        let mut after = before.clone();
+        *after.edge_mut(&'a', &'b').unwrap() = 5;
+        let diff = before.diff(&after, |a, b| a == b);
+        assert_eq!(diff.changed_edges, vec![('a', 'b')].into_iter().collect());
+        assert!(diff.added_edges.is_empty() && diff.removed_edges.is_empty());
+
+        // the same parallel edges in another order are no change, but one
+        // more of them is.
+        let mut after = before.clone();
+        after.remove_edge(&'b', &'c');
+        after.add_parallel_edge(&'b', &'c', 2).unwrap();
+        assert!(before.diff(&after, |a, b| a == b).is_empty());
+        after.add_parallel_edge(&'b', &'c', 2).unwrap();
+        let diff = before.diff(&after, |a, b| a == b);
+        assert_eq!(diff.changed_edges, vec![('b', 'c')].into_iter().collect());
+    }
+}
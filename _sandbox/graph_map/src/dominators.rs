@@ -0,0 +1,132 @@
+//! Which nodes every path from a start node to another must go through,
+//! like the tasks every route to a milestone waits on. Worked out with the
+//! iterative algorithm of Cooper, Harvey and Kennedy, "A Simple, Fast
+//! Dominance Algorithm".
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::visit::GraphVisitor;
+use crate::Graph;
+
+/// The dominator tree of the nodes reachable from a root.
+#[derive(Debug, Clone)]
+pub struct Dominators<K: Eq + Hash> {
+    root: K,
+    /// The closest strict dominator of each node but the root.
+    immediate: HashMap<K, K>,
+}
+impl<K: Eq + Hash + Copy> Dominators<K> {
+    pub fn root (&self) -> K {
+        self.root
+    }
+    /// The last node before `key` that every path from the root to it goes
+    /// through. `None` for the root, and nodes the root can't reach.
+    pub fn immediate_dominator (&self, key: &K) -> Option<K> {
+        self.immediate.get(key).copied()
+    }
+    /// The nodes every path from the root to `key` goes through, from `key`
+    /// itself back to the root. `None` if the root can't reach `key`.
+    pub fn dominators (&self, key: &K) -> Option<Vec<K>> {
+        if *key != self.root && !self.immediate.contains_key(key) {
+            return None;
+        }
+        let mut chain = vec![*key];
+        let mut current = *key;
+        while let Some(&next) = self.immediate.get(&current) {
+            chain.push(next);
+            current = next;
+        }
+        Some(chain)
+    }
+    /// Whether every path from the root to `key` goes through `dominator`.
+    /// Every node the root can reach dominates itself.
+    pub fn dominates (&self, dominator: &K, key: &K) -> bool {
+        self.dominators(key).is_some_and(|chain| chain.contains(dominator))
+    }
+}
+
+/// Numbers the nodes in the order they finish.
+struct Postorder<K> {
+    order: HashMap<K, usize>,
+}
+impl<K: Eq + Hash + Copy> GraphVisitor<K> for Postorder<K> {
+    fn finish(&mut self, key: &K) {
+        let number = self.order.len();
+        self.order.insert(*key, number);
+    }
+}
+
+impl<T: Eq + Hash, E, K: Eq + Hash + Copy> Graph<T, E, K> {
+    /// The dominator tree of the nodes reachable from `root`, or `None` if
+    /// there is no node under `root`.
+    pub fn dominators(&self, root: &K) -> Option<Dominators<K>> {
+        self.get(root)?;
+        let mut postorder = Postorder { order: HashMap::new() };
+        self.depth_first_from(root, &mut postorder);
+        let order = postorder.order;
+        let mut reverse_postorder: Vec<K> = order.keys().copied().collect();
+        reverse_postorder.sort_by_key(|key| std::cmp::Reverse(order[key]));
+
+        // the root is its own dominator while working, so every chain ends.
+        let mut immediate: HashMap<K, K> = HashMap::new();
+        immediate.insert(*root, *root);
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for key in &reverse_postorder[1..] {
+                let mut processed = self.nodes[key].incoming.iter()
+                    .filter(|before| immediate.contains_key(before));
+                let first = *processed.next().unwrap();
+                let dominator = processed.fold(first, |dominator, before| {
+                    intersect(&immediate, &order, *before, dominator)
+                });
+                if immediate.get(key) != Some(&dominator) {
+                    immediate.insert(*key, dominator);
+                    changed = true;
+                }
+            }
+        }
+        immediate.remove(root);
+        Some(Dominators { root: *root, immediate })
+    }
+}
+
+/// The closest node dominating both `first` and `second`.
+fn intersect<K: Eq + Hash + Copy>(immediate: &HashMap<K, K>, order: &HashMap<K, usize>, mut first: K, mut second: K) -> K {
+    while first != second {
+        while order[&first] < order[&second] {
+            first = immediate[&first];
+        }
+        while order[&second] < order[&first] {
+            second = immediate[&second];
+        }
+    }
+    first
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn dominators () {
+        // 1 -> 2 -> {3, 4} -> 5 -> 6, with 6 looping back to 2.
+        let mut graph = Graph::new();
+        for node in 1..=7 {
+            graph.insert_node(node, node);
+        }
+        for &(start, end) in &[(1, 2), (2, 3), (2, 4), (3, 5), (4, 5), (5, 6), (6, 2), (7, 5)] {
+            graph.add_edge(&start, &end).unwrap();
+        }
+
+        let dominators = graph.dominators(&1).unwrap();
+        assert_eq!(dominators.immediate_dominator(&5), Some(2));
+        assert_eq!(dominators.immediate_dominator(&3), Some(2));
+        assert_eq!(dominators.immediate_dominator(&1), None);
+        assert_eq!(dominators.dominators(&6), Some(vec![6, 5, 2, 1]));
+        assert!(dominators.dominates(&2, &4));
+        assert!(!dominators.dominates(&3, &5));
+        assert_eq!(dominators.dominators(&7), None);
+        assert!(graph.dominators(&9).is_none());
+    }
+}
@@ -0,0 +1,98 @@
+//! Reading graphs from edge lists, one edge per line:
+//!
+//! ```text
+//! from,to,weight
+//! Lay foundation,Build walls,1
+//! Build walls,Build roof
+//! ```
+//!
+//! Fields are separated by commas or tabs. The weight is optional, and
+//! edges without one get the default weight. Blank lines, lines starting
+//! with `#`, and a `from,to` header line are skipped.
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead};
+use std::str::FromStr;
+
+use uuid::Uuid;
+
+use crate::Graph;
+
+#[derive(Debug)]
+pub enum EdgeListError {
+    Io(io::Error),
+    /// A line that isn't an edge, numbered from 1.
+    Parse { line: usize, message: String },
+}
+impl fmt::Display for EdgeListError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EdgeListError::Io(error) => write!(f, "{}", error),
+            EdgeListError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+impl Error for EdgeListError {}
+impl From<io::Error> for EdgeListError {
+    fn from(error: io::Error) -> Self {
+        EdgeListError::Io(error)
+    }
+}
+
+impl<E: FromStr + Default> Graph<String, E> {
+    /// Builds a graph from an edge list. Each node's data is its name, and
+    /// it is added the first time an edge mentions it.
+    pub fn from_edge_list<R: BufRead>(reader: R) -> Result<Self, EdgeListError> {
+        let mut graph = Graph::new();
+        let mut ids: HashMap<String, Uuid> = HashMap::new();
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            let parse_error = |message: &str| EdgeListError::Parse { line: index + 1, message: message.to_string() };
+            let fields: Vec<&str> = line.split([',', '\t']).map(str::trim).collect();
+            if fields == [""] || fields[0].starts_with('#') || (index == 0 && fields[0] == "from") {
+                continue;
+            }
+            let (from, to, weight) = match fields[..] {
+                [from, to] => (from, to, E::default()),
+                [from, to, weight] => {
+                    let weight = weight.parse().map_err(|_| parse_error("invalid weight"))?;
+                    (from, to, weight)
+                }
+                _ => return Err(parse_error("expected from,to[,weight]")),
+            };
+            if from.is_empty() || to.is_empty() {
+                return Err(parse_error("empty node name"));
+            }
+            let mut id = |name: &str| *ids.entry(name.to_string())
+                .or_insert_with(|| graph.add_node(name.to_string()));
+            let (from, to) = (id(from), id(to));
+            graph.replace_edge(&from, &to, weight);
+        }
+        Ok(graph)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn id (graph: &Graph<String, u32>, name: &str) -> Uuid {
+        *graph.nodes().find(|(_, data)| *data == name).unwrap().0
+    }
+
+    #[test]
+    fn from_edge_list () {
+        let input = "from,to,weight\n# a comment\nfoundation,walls,1\n\nwalls\troof\n";
+        let graph: Graph<String, u32> = Graph::from_edge_list(input.as_bytes()).unwrap();
+        let walls = id(&graph, "walls");
+        assert_eq!(graph.edge(&id(&graph, "foundation"), &walls), Some(&1));
+        assert_eq!(graph.edge(&walls, &id(&graph, "roof")), Some(&0));
+        assert_eq!(graph.predecessors(&walls).count(), 1);
+        assert_eq!(graph.node_ids().count(), 3);
+
+        let error = Graph::<String, u32>::from_edge_list("a,b\na,b,heavy\n".as_bytes()).unwrap_err();
+        assert_eq!(error.to_string(), "line 2: invalid weight");
+        assert!(Graph::<String, u32>::from_edge_list("a\n".as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,259 @@
+//! Computing a value for each node from the values of the nodes with edges
+//! to it, like the task times in `GraphView`, but for any values. `DagEval`
+//! works on a graph that doesn't change, and `Incremental` on one whose node
+//! data does, recomputing only what a change could affect.
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::hash::Hash;
+
+use uuid::Uuid;
+
+use crate::Graph;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvalError<K> {
+    /// The nodes of a cycle, in the order of its edges, that a value
+    /// depends on, so no value on it can be worked out.
+    Cycle(Vec<K>),
+}
+impl<K: fmt::Debug> fmt::Display for EvalError<K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EvalError::Cycle(cycle) => write!(f, "values depend on each other in a cycle: {:?}", cycle),
+        }
+    }
+}
+impl<K: fmt::Debug> Error for EvalError<K> {}
+
+/// Works out each node's value with `compute`, from its key, its data and
+/// the values of the nodes with edges to it, each worked out first. Values
+/// are remembered, so each is computed once however often it is asked for.
+pub struct DagEval<'a, T: Eq + Hash, V, F, E = (), K: Eq + Hash + Copy = Uuid> {
+    graph: &'a Graph<T, E, K>,
+    compute: F,
+    values: HashMap<K, V>,
+}
+impl<'a, T, V, F, E, K> DagEval<'a, T, V, F, E, K>
+where
+    T: Eq + Hash,
+    K: Eq + Hash + Copy,
+    F: FnMut(&K, &T, &[(K, &V)]) -> V,
+{
+    /// `compute` is given the values before each node in no particular
+    /// order.
+    pub fn new (graph: &'a Graph<T, E, K>, compute: F) -> Self {
+        DagEval { graph, compute, values: HashMap::new() }
+    }
+    pub fn graph (&self) -> &'a Graph<T, E, K> {
+        self.graph
+    }
+    /// The value of the node under `key`. Panics if there is none.
+    pub fn value (&mut self, key: &K) -> Result<&V, EvalError<K>> {
+        // each node on the stack waits on the one after it, so the stack is
+        // a path against the edges.
+        let mut stack = vec![*key];
+        while let Some(&current) = stack.last() {
+            if self.values.contains_key(&current) {
+                stack.pop();
+                continue;
+            }
+            let incoming = &self.graph.nodes[&current].incoming;
+            match incoming.iter().find(|before| !self.values.contains_key(before)) {
+                Some(before) => {
+                    if let Some(position) = stack.iter().position(|waiting| waiting == before) {
+                        let cycle = stack[position..].iter().rev().copied().collect();
+                        return Err(EvalError::Cycle(cycle));
+                    }
+                    stack.push(*before);
+                }
+                None => {
+                    let values = &self.values;
+                    let before: Vec<(K, &V)> = incoming.iter()
+                        .map(|before| (*before, &values[before]))
+                        .collect();
+                    let value = (self.compute)(&current, &self.graph[&current], &before);
+                    self.values.insert(current, value);
+                    stack.pop();
+                }
+            }
+        }
+        Ok(&self.values[key])
+    }
+    /// The values worked out so far.
+    pub fn values (&self) -> &HashMap<K, V> {
+        &self.values
+    }
+}
+
+/// A `DagEval` that owns its graph, so node data can be changed. A change
+/// marks the node and everything after it as dirty, and the next query
+/// brings what it needs up to date. A dirty node is only recomputed if its
+/// own data or the value of a node before it changed, so a change that
+/// doesn't alter a value stops there.
+pub struct Incremental<T: Eq + Hash, V, F, E = (), K: Eq + Hash + Copy = Uuid> {
+    graph: Graph<T, E, K>,
+    compute: F,
+    values: HashMap<K, V>,
+    /// The nodes whose values may be out of date.
+    dirty: HashSet<K>,
+    /// The dirty nodes whose own data changed.
+    touched: HashSet<K>,
+    /// The nodes whose values changed since nothing was dirty.
+    changed: HashSet<K>,
+}
+impl<T, V, F, E, K> Incremental<T, V, F, E, K>
+where
+    T: Eq + Hash,
+    V: PartialEq,
+    K: Eq + Hash + Copy,
+    F: FnMut(&K, &T, &[(K, &V)]) -> V,
+{
+    pub fn new (graph: Graph<T, E, K>, compute: F) -> Self {
+        Incremental {
+            graph,
+            compute,
+            values: HashMap::new(),
+            dirty: HashSet::new(),
+            touched: HashSet::new(),
+            changed: HashSet::new(),
+        }
+    }
+    pub fn graph (&self) -> &Graph<T, E, K> {
+        &self.graph
+    }
+    pub fn into_graph (self) -> Graph<T, E, K> {
+        self.graph
+    }
+    /// Changes the data of a node, returning the old data, or `None` if
+    /// there is no node under `key`.
+    pub fn set (&mut self, key: &K, data: T) -> Option<T> {
+        let old = std::mem::replace(self.graph.get_mut(key)?, data);
+        if old != self.graph[key] {
+            self.touched.insert(*key);
+            self.dirty.insert(*key);
+            self.dirty.extend(self.graph.descendants(key));
+        }
+        Some(old)
+    }
+    /// The value of the node under `key`, recomputing only the values it
+    /// depends on that are out of date. Panics if there is no node.
+    pub fn value (&mut self, key: &K) -> Result<&V, EvalError<K>> {
+        let mut stack = vec![*key];
+        while let Some(&current) = stack.last() {
+            if self.is_current(&current) {
+                stack.pop();
+                continue;
+            }
+            let incoming = &self.graph.nodes[&current].incoming;
+            if let Some(before) = incoming.iter().find(|before| !self.is_current(before)) {
+                if let Some(position) = stack.iter().position(|waiting| waiting == before) {
+                    let cycle = stack[position..].iter().rev().copied().collect();
+                    return Err(EvalError::Cycle(cycle));
+                }
+                stack.push(*before);
+                continue;
+            }
+
+            let unaffected = self.values.contains_key(&current)
+                && !self.touched.contains(&current)
+                && incoming.iter().all(|before| !self.changed.contains(before));
+            if !unaffected {
+                let values = &self.values;
+                let before: Vec<(K, &V)> = incoming.iter()
+                    .map(|before| (*before, &values[before]))
+                    .collect();
+                let value = (self.compute)(&current, &self.graph[&current], &before);
+                if self.values.get(&current) != Some(&value) {
+                    self.changed.insert(current);
+                    self.values.insert(current, value);
+                }
+            }
+            self.dirty.remove(&current);
+            self.touched.remove(&current);
+            stack.pop();
+        }
+        if self.dirty.is_empty() {
+            self.changed.clear();
+        }
+        Ok(&self.values[key])
+    }
+
+    fn is_current (&self, key: &K) -> bool {
+        self.values.contains_key(key) && !self.dirty.contains(key)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn dag_eval () {
+        // the number of paths to each node from a node with no edges in.
+        let mut graph: Graph<&str, (), char> = Graph::new();
+        for key in "abcd".chars() {
+            graph.insert_node(key, "node");
+        }
+        graph.add_edge(&'a', &'b').unwrap();
+        graph.add_edge(&'a', &'c').unwrap();
+        graph.add_edge(&'b', &'d').unwrap();
+        graph.add_edge(&'c', &'d').unwrap();
+
+        let mut computed = 0;
+        let mut paths = DagEval::new(&graph, |_, _, before: &[(char, &u32)]| {
+            computed += 1;
+            before.iter().map(|(_, paths)| **paths).sum::<u32>().max(1)
+        });
+        assert_eq!(paths.value(&'d'), Ok(&2));
+        assert_eq!(paths.value(&'b'), Ok(&1));
+        assert_eq!(paths.values().len(), 4);
+        drop(paths);
+        assert_eq!(computed, 4);
+
+        graph.add_edge(&'d', &'a').unwrap();
+        let mut paths = DagEval::new(&graph, |_, _, _: &[(char, &u32)]| 1);
+        match paths.value(&'b') {
+            Err(EvalError::Cycle(cycle)) => {
+                assert_eq!(cycle.len(), 3);
+                assert!(graph.has_edge(cycle.last().unwrap(), &cycle[0]));
+            }
+            result => panic!("expected a cycle, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn incremental () {
+        use std::cell::Cell;
+
+        let mut graph: Graph<u32, (), char> = Graph::new();
+        for (key, data) in [('a', 20), ('b', 0), ('c', 1), ('d', 5)] {
+            graph.insert_node(key, data);
+        }
+        graph.add_edge(&'a', &'b').unwrap();
+        graph.add_edge(&'b', &'c').unwrap();
+
+        // `b` caps what it passes on at 10, so changes to a big `a` stop there.
+        let computed = Cell::new(0);
+        let mut sums = Incremental::new(graph, |key, data: &u32, before: &[(char, &u32)]| {
+            computed.set(computed.get() + 1);
+            let sum = data + before.iter().map(|(_, sum)| **sum).sum::<u32>();
+            if *key == 'b' { sum.min(10) } else { sum }
+        });
+        assert_eq!(sums.value(&'c'), Ok(&11));
+        assert_eq!(computed.get(), 3);
+
+        assert_eq!(sums.set(&'a', 30), Some(20));
+        assert_eq!(sums.value(&'c'), Ok(&11));
+        assert_eq!(computed.get(), 5);
+        assert_eq!(sums.value(&'a'), Ok(&30));
+
+        sums.set(&'a', 3);
+        sums.set(&'c', 2);
+        assert_eq!(sums.value(&'c'), Ok(&5));
+        assert_eq!(computed.get(), 8);
+        assert_eq!(sums.set(&'e', 1), None);
+        assert_eq!(sums.value(&'d'), Ok(&5));
+        assert_eq!(computed.get(), 9);
+    }
+}
@@ -0,0 +1,54 @@
+//! `HeapSize` for graphs and lists, from the `heap_size` crate the other
+//! collection crates share. Nodes behind an `Rc` are counted the first time
+//! they are reached and skipped after that.
+use std::hash::Hash;
+
+pub use heap_size::{HeapSize, Seen};
+use heap_size::table_allocation;
+
+use crate::{Graph, GraphNode, Task};
+
+impl HeapSize for Task {
+    fn heap_size_shared(&self, _: &mut Seen) -> usize {
+        0
+    }
+}
+impl<K: Eq + Hash, T: HeapSize> HeapSize for GraphNode<K, T> {
+    fn heap_size_shared(&self, seen: &mut Seen) -> usize {
+        self.data.heap_size_shared(seen)
+            + table_allocation::<K>(self.incoming.capacity())
+            + table_allocation::<K>(self.outgoing.capacity())
+    }
+}
+/// Keys are `Copy`, so they hold nothing on the heap themselves.
+impl<T: Eq + Hash + HeapSize, E: HeapSize, K: Eq + Hash + Copy> HeapSize for Graph<T, E, K> {
+    fn heap_size_shared(&self, seen: &mut Seen) -> usize {
+        table_allocation::<(K, GraphNode<K, T>)>(self.nodes.capacity())
+            + self.nodes.values().map(|node| node.heap_size_shared(seen)).sum::<usize>()
+            + table_allocation::<((K, K), Vec<E>)>(self.edges.capacity())
+            + self.edges.values().map(|parallel| parallel.heap_size_shared(seen)).sum::<usize>()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::mem::size_of;
+
+    #[test]
+    fn graph () {
+        let mut graph: Graph<String, Vec<u32>, char> = Graph::new();
+        let empty = graph.heap_size();
+        graph.insert_node('a', String::with_capacity(100));
+        graph.insert_node('b', String::new());
+        let nodes = graph.heap_size();
+        assert!(nodes >= empty + 100 + 2 * size_of::<(char, GraphNode<char, String>)>());
+
+        graph.insert_edge(&'a', &'b', Vec::with_capacity(10)).unwrap();
+        assert!(graph.heap_size() >= nodes + 10 * size_of::<u32>() + size_of::<((char, char), Vec<Vec<u32>>)>());
+        graph.remove_node(&'a');
+        graph.nodes.shrink_to_fit();
+        graph.edges.shrink_to_fit();
+        assert!(graph.heap_size() < nodes);
+    }
+}
@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::{same_edges, Graph};
+
+/// Graphs with up to this many nodes are compared exactly.
+const EXACT_LIMIT: usize = 12;
+
+/// What a node looks like without its key: its data, in degree and out
+/// degree. Nodes that could stand for each other have the same signature.
+type Signature<'a, T> = (&'a T, usize, usize);
+
+impl<T: Eq + Hash, E: PartialEq, K: Eq + Hash + Copy> Graph<T, E, K> {
+    /// Whether the two graphs are the same but for their keys. Graphs of up
+    /// to a dozen nodes are checked exactly, by searching for a renaming
+    /// of keys that turns one into the other. Bigger ones are only checked
+    /// for having the same node data and degrees, and edges between nodes
+    /// of the same data and degrees, which is enough to catch all but
+    /// deliberate lookalikes. Parallel edges match whatever order they were
+    /// added in.
+    pub fn structurally_eq<L: Eq + Hash + Copy>(&self, other: &Graph<T, E, L>) -> bool {
+        if self.nodes.len() != other.nodes.len() || self.edges().count() != other.edges().count() {
+            return false;
+        }
+        let ours = self.signatures();
+        let theirs = other.signatures();
+        if counts(ours.values()) != counts(theirs.values()) {
+            return false;
+        }
+        if self.edge_signatures(&ours) != other.edge_signatures(&theirs) {
+            return false;
+        }
+        if self.nodes.len() > EXACT_LIMIT {
+            return true;
+        }
+
+        let keys: Vec<K> = self.nodes.keys().copied().collect();
+        let mut renaming = HashMap::new();
+        let mut used = HashMap::new();
+        self.rename(other, &keys, &ours, &theirs, &mut renaming, &mut used)
+    }
+
+    fn signatures(&self) -> HashMap<K, Signature<'_, T>> {
+        self.nodes.iter()
+            .map(|(key, node)| (*key, (&node.data, self.in_degree(key).unwrap(), self.out_degree(key).unwrap())))
+            .collect()
+    }
+
+    /// How many edges there are between nodes of each pair of signatures.
+    fn edge_signatures<'a>(&self, signatures: &HashMap<K, Signature<'a, T>>) -> HashMap<(Signature<'a, T>, Signature<'a, T>), usize> {
+        counts(self.edges().map(|(start, end, _)| (signatures[start], signatures[end])))
+    }
+
+    /// Tries each node of `other` with the right signature as the match for
+    /// the next of `keys`, backtracking when the edges to the nodes matched
+    /// so far don't agree.
+    fn rename<L: Eq + Hash + Copy>(
+        &self,
+        other: &Graph<T, E, L>,
+        keys: &[K],
+        ours: &HashMap<K, Signature<'_, T>>,
+        theirs: &HashMap<L, Signature<'_, T>>,
+        renaming: &mut HashMap<K, L>,
+        used: &mut HashMap<L, K>,
+    ) -> bool {
+        let (key, rest) = match keys.split_first() {
+            Some(split) => split,
+            None => return true,
+        };
+        for (candidate, signature) in theirs {
+            if used.contains_key(candidate) || *signature != ours[key] {
+                continue;
+            }
+            renaming.insert(*key, *candidate);
+            let agrees = renaming.iter().all(|(matched, their_matched)| {
+                same_edges(self.parallel_edges(key, matched), other.parallel_edges(candidate, their_matched))
+                    && same_edges(self.parallel_edges(matched, key), other.parallel_edges(their_matched, candidate))
+            });
+            if agrees {
+                used.insert(*candidate, *key);
+                if self.rename(other, rest, ours, theirs, renaming, used) {
+                    return true;
+                }
+                used.remove(candidate);
+            }
+            renaming.remove(key);
+        }
+        false
+    }
+}
+
+fn counts<A: Eq + Hash, I: IntoIterator<Item = A>>(values: I) -> HashMap<A, usize> {
+    let mut counts = HashMap::new();
+    for value in values {
+        *counts.entry(value).or_insert(0) += 1;
+    }
+    counts
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn structurally_eq () {
+        let mut graph: Graph<&str, u32, char> = Graph::new();
+        graph.insert_node('a', "task");
+        graph.insert_node('b', "task");
+        graph.insert_node('c', "task");
+        graph.insert_edge(&'a', &'b', 1).unwrap();
+        graph.insert_edge(&'b', &'c', 2).unwrap();
+
+        let mut renamed: Graph<&str, u32, u32> = Graph::new();
+        renamed.insert_node(3, "task");
+        renamed.insert_node(1, "task");
+        renamed.insert_node(2, "task");
+        renamed.insert_edge(&1, &2, 1).unwrap();
+        renamed.insert_edge(&2, &3, 2).unwrap();
+        assert!(graph.structurally_eq(&renamed));
+
+        // the same shape and degrees, but the weights are the other way.
+        *renamed.edge_mut(&1, &2).unwrap() = 2;
+        *renamed.edge_mut(&2, &3).unwrap() = 1;
+        assert!(!graph.structurally_eq(&renamed));
+
+        renamed.remove_edge(&2, &3);
+        renamed.insert_edge(&3, &2, 1).unwrap();
+        assert!(!graph.structurally_eq(&renamed));
+    }
+
+    #[test]
+    fn parallel_edges_in_any_order () {
+        let mut graph: Graph<&str, u32, char> = Graph::new();
+        graph.insert_node('a', "start");
+        graph.insert_node('b', "end");
+        for weight in &[1, 2, 2] {
+            graph.add_parallel_edge(&'a', &'b', *weight).unwrap();
+        }
+
+        let mut renamed: Graph<&str, u32, u32> = Graph::new();
+        renamed.insert_node(1, "start");
+        renamed.insert_node(2, "end");
+        for weight in &[2, 1, 2] {
+            renamed.add_parallel_edge(&1, &2, *weight).unwrap();
+        }
+        assert!(graph.structurally_eq(&renamed));
+
+        *renamed.edge_mut(&1, &2).unwrap() = 1;
+        assert!(!graph.structurally_eq(&renamed));
+    }
+}
@@ -0,0 +1,210 @@
+//! A history of the changes made to a graph, to undo them, or to make them
+//! again to another graph.
+use std::hash::Hash;
+
+use uuid::Uuid;
+
+use crate::{Graph, GraphError};
+
+/// A change made through a `Journaled` graph, with what it replaced or
+/// removed so it can be undone.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mutation<K, T, E> {
+    InsertNode { key: K, data: T, old: Option<T> },
+    /// The edges are the lists of parallel edges to and from the node that
+    /// went with it.
+    RemoveNode { key: K, data: T, edges: Vec<((K, K), Vec<E>)> },
+    InsertEdge { start: K, end: K, data: E, old: Option<E> },
+    AddParallelEdge { start: K, end: K, data: E },
+    /// The first of the edges from `start` to `end` was removed.
+    RemoveEdge { start: K, end: K, data: E },
+}
+
+/// A graph that keeps a log of the changes made through it. Changes made
+/// to the graph some other way, after `into_graph`, aren't logged.
+#[derive(Debug, Clone)]
+pub struct Journaled<T: Eq + Hash, E = (), K: Eq + Hash + Copy = Uuid> {
+    graph: Graph<T, E, K>,
+    log: Vec<Mutation<K, T, E>>,
+}
+impl<T: Eq + Hash + Clone, E: Clone, K: Eq + Hash + Copy> Journaled<T, E, K> {
+    /// Starts an empty log. The changes that made `graph` aren't in it.
+    pub fn new (graph: Graph<T, E, K>) -> Self {
+        Journaled { graph, log: Vec::new() }
+    }
+    pub fn graph (&self) -> &Graph<T, E, K> {
+        &self.graph
+    }
+    pub fn into_graph (self) -> Graph<T, E, K> {
+        self.graph
+    }
+    /// The changes made so far, oldest first.
+    pub fn log (&self) -> &[Mutation<K, T, E>] {
+        &self.log
+    }
+
+    pub fn insert_node (&mut self, key: K, data: T) -> Option<T> {
+        let old = self.graph.insert_node(key, data.clone());
+        self.log.push(Mutation::InsertNode { key, data, old: old.clone() });
+        old
+    }
+    pub fn remove_node (&mut self, key: &K) -> Option<T> {
+        let node = self.graph.nodes.get(key)?;
+        let pairs: Vec<(K, K)> = node.incoming.iter().map(|start| (*start, *key))
+            .chain(node.outgoing.iter().map(|end| (*key, *end)))
+            .collect();
+        // a loop is both to and from the node, so take each list once.
+        let edges = pairs.into_iter()
+            .filter_map(|pair| Some((pair, self.graph.edges.remove(&pair)?)))
+            .collect();
+        let data = self.graph.remove_node(key)?;
+        self.log.push(Mutation::RemoveNode { key: *key, data: data.clone(), edges });
+        Some(data)
+    }
+    pub fn insert_edge (&mut self, start: &K, end: &K, data: E) -> Result<Option<E>, GraphError<K>> {
+        let old = self.graph.insert_edge(start, end, data.clone())?;
+        self.log.push(Mutation::InsertEdge { start: *start, end: *end, data, old: old.clone() });
+        Ok(old)
+    }
+    pub fn add_parallel_edge (&mut self, start: &K, end: &K, data: E) -> Result<usize, GraphError<K>> {
+        let index = self.graph.add_parallel_edge(start, end, data.clone())?;
+        self.log.push(Mutation::AddParallelEdge { start: *start, end: *end, data });
+        Ok(index)
+    }
+    pub fn remove_edge (&mut self, start: &K, end: &K) -> Option<E> {
+        let data = self.graph.remove_edge(start, end)?;
+        self.log.push(Mutation::RemoveEdge { start: *start, end: *end, data: data.clone() });
+        Some(data)
+    }
+
+    /// Undoes the last `count` changes, newest first, and forgets them.
+    /// Returns how many there were to undo.
+    pub fn undo (&mut self, count: usize) -> usize {
+        let keep = self.log.len().saturating_sub(count);
+        let undone = self.log.split_off(keep);
+        let count = undone.len();
+        for mutation in undone.into_iter().rev() {
+            self.revert(mutation);
+        }
+        count
+    }
+    /// Makes the changes in `log` again, logging them here too. Stops at
+    /// the first that fails, when an edge joins a node that isn't here.
+    pub fn replay (&mut self, log: &[Mutation<K, T, E>]) -> Result<(), GraphError<K>> {
+        for mutation in log {
+            match mutation {
+                Mutation::InsertNode { key, data, .. } => {
+                    self.insert_node(*key, data.clone());
+                }
+                Mutation::RemoveNode { key, .. } => {
+                    self.remove_node(key);
+                }
+                Mutation::InsertEdge { start, end, data, .. } => {
+                    self.insert_edge(start, end, data.clone())?;
+                }
+                Mutation::AddParallelEdge { start, end, data } => {
+                    self.add_parallel_edge(start, end, data.clone())?;
+                }
+                Mutation::RemoveEdge { start, end, .. } => {
+                    self.remove_edge(start, end);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn revert (&mut self, mutation: Mutation<K, T, E>) {
+        let graph = &mut self.graph;
+        match mutation {
+            Mutation::InsertNode { key, old: Some(old), .. } => {
+                graph.insert_node(key, old);
+            }
+            Mutation::InsertNode { key, old: None, .. } => {
+                graph.remove_node(&key);
+            }
+            Mutation::RemoveNode { key, data, edges } => {
+                graph.insert_node(key, data);
+                for ((start, end), parallel) in edges {
+                    graph.nodes.get_mut(&start).unwrap().outgoing.insert(end);
+                    graph.nodes.get_mut(&end).unwrap().incoming.insert(start);
+                    graph.edges.insert((start, end), parallel);
+                }
+            }
+            Mutation::InsertEdge { start, end, old: Some(old), .. } => {
+                *graph.edge_mut(&start, &end).unwrap() = old;
+            }
+            Mutation::InsertEdge { start, end, old: None, .. } => {
+                graph.remove_edge(&start, &end);
+            }
+            Mutation::AddParallelEdge { start, end, .. } => {
+                let parallel = graph.edges.get_mut(&(start, end)).unwrap();
+                parallel.pop();
+                if parallel.is_empty() {
+                    graph.remove_parallel_edges(&start, &end);
+                }
+            }
+            Mutation::RemoveEdge { start, end, data } => {
+                graph.push_edge(&start, &end, data);
+                let parallel = graph.edges.get_mut(&(start, end)).unwrap();
+                parallel.rotate_right(1);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    type Plan = Graph<&'static str, u32, char>;
+
+    fn same (first: &Plan, second: &Plan) -> bool {
+        let nodes = |graph: &Plan| {
+            let mut nodes: Vec<(char, &str)> = graph.nodes().map(|(key, data)| (*key, *data)).collect();
+            nodes.sort_unstable();
+            nodes
+        };
+        let edges = |graph: &Plan| {
+            let mut edges: Vec<(char, char, u32)> = graph.edges().map(|(start, end, data)| (*start, *end, *data)).collect();
+            edges.sort_unstable();
+            edges
+        };
+        nodes(first) == nodes(second) && edges(first) == edges(second)
+            && first.node_ids().all(|key| first.neighbors(key).count() == second.neighbors(key).count())
+    }
+
+    #[test]
+    fn undo_and_replay () {
+        let mut start: Plan = Graph::new();
+        start.insert_node('a', "foundation");
+        start.insert_node('b', "walls");
+        start.insert_edge(&'a', &'b', 1).unwrap();
+        start.add_parallel_edge(&'a', &'b', 2).unwrap();
+
+        let mut journaled = Journaled::new(start.clone());
+        journaled.insert_node('c', "roof");
+        journaled.insert_edge(&'b', &'c', 3).unwrap();
+        journaled.insert_edge(&'b', &'c', 4).unwrap();
+        journaled.add_parallel_edge(&'c', &'c', 5).unwrap();
+        journaled.remove_edge(&'a', &'b');
+        journaled.insert_node('a', "deep foundation");
+        journaled.remove_node(&'b');
+        assert!(journaled.insert_edge(&'b', &'a', 6).is_err());
+        assert_eq!(journaled.log().len(), 7);
+        let finish = journaled.graph().clone();
+        let log = journaled.log().to_vec();
+
+        assert_eq!(journaled.undo(2), 2);
+        assert_eq!(journaled.graph()[&'a'], "foundation");
+        assert_eq!(journaled.graph().parallel_edges(&'a', &'b'), &[2]);
+        assert_eq!(journaled.graph().edge(&'b', &'c'), Some(&4));
+        assert_eq!(journaled.undo(10), 5);
+        assert!(same(journaled.graph(), &start));
+        assert_eq!(journaled.graph().parallel_edges(&'a', &'b'), &[1, 2]);
+
+        let mut again = Journaled::new(start);
+        again.replay(&log).unwrap();
+        assert!(same(again.graph(), &finish));
+        assert_eq!(again.log(), &log[..]);
+    }
+}
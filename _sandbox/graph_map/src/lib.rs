@@ -0,0 +1,855 @@
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::hash::Hash;
+use std::ops::{Index, IndexMut};
+
+use uuid::Uuid;
+
+use eval::{DagEval, EvalError};
+use visit::GraphVisitor;
+
+mod bipartite;
+#[cfg(feature = "calendar")]
+pub mod calendar;
+pub mod clusters;
+mod components;
+pub mod cycles;
+pub mod degree;
+pub mod diff;
+pub mod dominators;
+pub mod edge_list;
+pub mod eval;
+pub mod heap_size;
+mod isomorphism;
+pub mod journal;
+mod merge;
+pub mod metrics;
+mod order;
+mod paths;
+pub mod pert;
+pub mod raw_list;
+pub mod reach;
+pub mod resources;
+pub mod schedule;
+pub mod slab_list;
+pub mod visit;
+#[cfg(feature = "parallel")]
+mod parallel;
+#[cfg(feature = "petgraph")]
+mod petgraph_interop;
+#[cfg(feature = "serde")]
+mod serialize;
+#[cfg(feature = "testing")]
+pub mod testing;
+
+#[derive(Clone, Eq, PartialEq, Hash, Debug, PartialOrd, Ord)]
+pub struct Task {
+    pub name: &'static str,
+    pub duration: u32,
+    /// The earliest time the task can start, whatever it waits on.
+    pub not_before: Option<u32>,
+    /// The time the task should be finished by. Schedules report tasks
+    /// that end after their deadline rather than moving anything for it.
+    pub deadline: Option<u32>,
+    /// Which of the tasks ready at once gets a worker first, when there
+    /// aren't enough to go round: the higher, the sooner.
+    pub priority: u32,
+}
+impl Task {
+    pub fn new (name: &'static str, duration: u32) -> Self {
+        Task {
+            name,
+            duration,
+            not_before: None,
+            deadline: None,
+            priority: 0,
+        }
+    }
+    pub fn with_not_before (self, time: u32) -> Self {
+        Task { not_before: Some(time), ..self }
+    }
+    pub fn with_deadline (self, time: u32) -> Self {
+        Task { deadline: Some(time), ..self }
+    }
+    pub fn with_priority (self, priority: u32) -> Self {
+        Task { priority, ..self }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct GraphNode<K: Eq + Hash, T> {
+    data: T,
+    incoming: HashSet<K>,
+    outgoing: HashSet<K>,
+}
+impl<K: Eq + Hash, T> GraphNode<K, T> {
+    fn new (data: T) -> Self {
+        GraphNode {
+            data,
+            incoming: HashSet::new(),
+            outgoing: HashSet::new(),
+        }
+    }
+}
+
+
+/// Why a change to a graph could not be made.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphError<K> {
+    /// There is no node under the key.
+    MissingNode(K),
+}
+impl<K: fmt::Debug> fmt::Display for GraphError<K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GraphError::MissingNode(key) => write!(f, "no node with key {:?}", key),
+        }
+    }
+}
+impl<K: fmt::Debug> Error for GraphError<K> {}
+
+/// A directed graph of `T`s, where each edge carries an `E`: a weight, a
+/// lag time, or `()` if edges are just edges. Nodes are looked up by a key
+/// of type `K`, which is a random `Uuid` from `add_node` unless the caller
+/// gives their own to `insert_node`.
+///
+/// There can be more than one edge from a node to another, such as for
+/// two kinds of dependency between the same tasks. The methods that take
+/// the start and end of an edge work on the first of them, and the
+/// `parallel` ones on all of them.
+#[derive(Debug, Clone)]
+pub struct Graph<T: Eq + Hash, E = (), K: Eq + Hash + Copy = Uuid> {
+    nodes: HashMap<K, GraphNode<K, T>>,
+    /// The edges from one node to another, in the order they were added.
+    /// Pairs with no edges are removed, so the lists are never empty.
+    edges: HashMap<(K, K), Vec<E>>,
+}
+impl<T: Eq + Hash, E, K: Eq + Hash + Copy> Default for Graph<T, E, K> {
+    fn default() -> Self {
+        Graph::new()
+    }
+}
+impl<T: Eq + Hash, E> Graph<T, E> {
+    pub fn add_node(&mut self, node: T) -> Uuid {
+        let key = Uuid::new_v4();
+        self.nodes.insert(key, GraphNode::new(node));
+        key
+    }
+}
+impl<T: Eq + Hash, K: Eq + Hash + Copy> Graph<T, (), K> {
+    /// Adds an edge to a graph whose edges carry no data.
+    pub fn add_edge(&mut self, start: &K, end: &K) -> Result<(), GraphError<K>> {
+        self.insert_edge(start, end, ()).map(|_| ())
+    }
+}
+impl<T: Eq + Hash, E, K: Eq + Hash + Copy> Graph<T, E, K> {
+    pub fn new() -> Self {
+        Graph {
+            nodes: HashMap::new(),
+            edges: HashMap::new(),
+        }
+    }
+    /// Adds a node under `key`. If there was already a node there, its
+    /// data is replaced and returned, and its edges are kept.
+    pub fn insert_node(&mut self, key: K, node: T) -> Option<T> {
+        match self.nodes.get_mut(&key) {
+            Some(existing) => Some(std::mem::replace(&mut existing.data, node)),
+            None => {
+                self.nodes.insert(key, GraphNode::new(node));
+                None
+            }
+        }
+    }
+    /// Adds an edge carrying `data`. If there was already an edge from
+    /// `start` to `end`, its data is replaced and returned. Fails, leaving
+    /// the graph as it was, if either end isn't in the graph.
+    pub fn insert_edge(&mut self, start: &K, end: &K, data: E) -> Result<Option<E>, GraphError<K>> {
+        self.check_ends(start, end)?;
+        Ok(self.replace_edge(start, end, data))
+    }
+    /// As `insert_edge`, but first adds either end that isn't in the graph
+    /// with the data `make` gives for its key.
+    pub fn add_edge_with_nodes<F: FnMut(&K) -> T>(&mut self, start: &K, end: &K, data: E, mut make: F) -> Option<E> {
+        for key in &[*start, *end] {
+            if !self.nodes.contains_key(key) {
+                self.insert_node(*key, make(key));
+            }
+        }
+        self.replace_edge(start, end, data)
+    }
+    /// Adds an edge carrying `data`, alongside any there already are from
+    /// `start` to `end`. Returns its position among them.
+    pub fn add_parallel_edge(&mut self, start: &K, end: &K, data: E) -> Result<usize, GraphError<K>> {
+        self.check_ends(start, end)?;
+        Ok(self.push_edge(start, end, data))
+    }
+    fn check_ends(&self, start: &K, end: &K) -> Result<(), GraphError<K>> {
+        for key in &[start, end] {
+            if !self.nodes.contains_key(key) {
+                return Err(GraphError::MissingNode(**key));
+            }
+        }
+        Ok(())
+    }
+    /// `insert_edge` between two nodes known to be in the graph.
+    fn replace_edge(&mut self, start: &K, end: &K, data: E) -> Option<E> {
+        match self.edges.get_mut(&(*start, *end)) {
+            Some(parallel) => Some(std::mem::replace(&mut parallel[0], data)),
+            None => {
+                self.push_edge(start, end, data);
+                None
+            }
+        }
+    }
+    /// `add_parallel_edge` between two nodes known to be in the graph.
+    fn push_edge(&mut self, start: &K, end: &K, data: E) -> usize {
+        self.nodes.get_mut(start).unwrap().outgoing.insert(*end);
+        self.nodes.get_mut(end).unwrap().incoming.insert(*start);
+        let parallel = self.edges.entry((*start, *end)).or_default();
+        parallel.push(data);
+        parallel.len() - 1
+    }
+    /// Removes the first edge from `start` to `end`, returning its data.
+    pub fn remove_edge(&mut self, start: &K, end: &K) -> Option<E> {
+        let parallel = self.edges.get_mut(&(*start, *end))?;
+        let data = parallel.remove(0);
+        if parallel.is_empty() {
+            self.remove_parallel_edges(start, end);
+        }
+        Some(data)
+    }
+    /// Removes every edge from `start` to `end`, returning their data.
+    pub fn remove_parallel_edges(&mut self, start: &K, end: &K) -> Vec<E> {
+        if let Some(node) = self.nodes.get_mut(start) {
+            node.outgoing.remove(end);
+        }
+        if let Some(node) = self.nodes.get_mut(end) {
+            node.incoming.remove(start);
+        }
+        self.edges.remove(&(*start, *end)).unwrap_or_default()
+    }
+    pub fn edge(&self, start: &K, end: &K) -> Option<&E> {
+        self.edges.get(&(*start, *end)).map(|parallel| &parallel[0])
+    }
+    pub fn edge_mut(&mut self, start: &K, end: &K) -> Option<&mut E> {
+        self.edges.get_mut(&(*start, *end)).map(|parallel| &mut parallel[0])
+    }
+    /// The data of every edge from `start` to `end`, in the order they were
+    /// added.
+    pub fn parallel_edges(&self, start: &K, end: &K) -> &[E] {
+        self.edges.get(&(*start, *end)).map_or(&[], Vec::as_slice)
+    }
+    /// Removes a node and its edges, returning its data, or `None` if
+    /// there was no node under `node_id`.
+    pub fn remove_node(&mut self, node_id: &K) -> Option<T> {
+        let node = self.nodes.remove(node_id)?;
+        for start in node.incoming.iter() {
+            if let Some(start_node) = self.nodes.get_mut(start) {
+                start_node.outgoing.remove(node_id);
+            }
+            self.edges.remove(&(*start, *node_id));
+        }
+        for end in node.outgoing.iter() {
+            if let Some(end_node) = self.nodes.get_mut(end) {
+                end_node.incoming.remove(node_id);
+            }
+            self.edges.remove(&(*node_id, *end));
+        }
+        Some(node.data)
+    }
+    /// Removes every node for which `keep` returns false, along with its
+    /// edges.
+    pub fn retain_nodes<F: FnMut(&K, &T) -> bool>(&mut self, mut keep: F) {
+        self.nodes.retain(|key, node| keep(key, &node.data));
+        let nodes = &mut self.nodes;
+        self.edges.retain(|(start, end), _| nodes.contains_key(start) && nodes.contains_key(end));
+        let remaining: HashSet<K> = nodes.keys().copied().collect();
+        for node in nodes.values_mut() {
+            node.incoming.retain(|start| remaining.contains(start));
+            node.outgoing.retain(|end| remaining.contains(end));
+        }
+    }
+    pub fn get(&self, key: &K) -> Option<&T> {
+        self.nodes.get(key).map(|node| &node.data)
+    }
+    /// The data of the node under `key`, to change in place without
+    /// touching its edges.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut T> {
+        self.nodes.get_mut(key).map(|node| &mut node.data)
+    }
+    pub fn has_edge(&self, start: &K, end: &K) -> bool {
+        self.edges.contains_key(&(*start, *end))
+    }
+    /// The nodes `key` has edges to, once each however many edges there
+    /// are. There are none if there is no node under `key`.
+    pub fn neighbors(&self, key: &K) -> impl Iterator<Item = &K> {
+        self.nodes.get(key).into_iter().flat_map(|node| node.outgoing.iter())
+    }
+    /// The nodes with edges to `key`, once each.
+    pub fn predecessors(&self, key: &K) -> impl Iterator<Item = &K> {
+        self.nodes.get(key).into_iter().flat_map(|node| node.incoming.iter())
+    }
+    /// Each node's key and data, in no particular order.
+    pub fn nodes(&self) -> impl Iterator<Item = (&K, &T)> {
+        self.nodes.iter().map(|(key, node)| (key, &node.data))
+    }
+    pub fn node_ids(&self) -> impl Iterator<Item = &K> {
+        self.nodes.keys()
+    }
+    /// Each edge's start, end and data, in no particular order, except
+    /// that parallel edges come in the order they were added.
+    pub fn edges(&self) -> impl Iterator<Item = (&K, &K, &E)> {
+        self.edges.iter()
+            .flat_map(|((start, end), parallel)| parallel.iter().map(move |data| (start, end, data)))
+    }
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&K, &mut T)> {
+        self.nodes.iter_mut().map(|(key, node)| (key, &mut node.data))
+    }
+    /// The nodes of a cycle, if the graph has one, each with an edge to the
+    /// next and the last with an edge back to the first.
+    pub fn find_cycle(&self) -> Option<Vec<K>> {
+        let mut finder = CycleFinder { path: Vec::new(), cycle: None };
+        self.depth_first(&mut finder);
+        finder.cycle
+    }
+}
+
+/// Keeps the path the search took to the current node, so an edge back to
+/// a node on it closes a cycle.
+struct CycleFinder<K> {
+    path: Vec<K>,
+    cycle: Option<Vec<K>>,
+}
+impl<K: Eq + Copy> GraphVisitor<K> for CycleFinder<K> {
+    fn discover(&mut self, key: &K) {
+        self.path.push(*key);
+    }
+    fn finish(&mut self, _key: &K) {
+        self.path.pop();
+    }
+    fn back_edge(&mut self, _start: &K, end: &K) {
+        let start = self.path.iter().position(|node| node == end).unwrap();
+        self.cycle = Some(self.path[start..].to_vec());
+    }
+    fn done(&self) -> bool {
+        self.cycle.is_some()
+    }
+}
+
+/// `graph[&key]` is the data of the node under `key`, and panics if there
+/// is none. `get` and `get_mut` are the versions that do not.
+impl<T: Eq + Hash, E, K: Eq + Hash + Copy> Index<&K> for Graph<T, E, K> {
+    type Output = T;
+    fn index(&self, key: &K) -> &T {
+        self.get(key).expect("Graph: invalid key.")
+    }
+}
+impl<T: Eq + Hash, E, K: Eq + Hash + Copy> IndexMut<&K> for Graph<T, E, K> {
+    fn index_mut(&mut self, key: &K) -> &mut T {
+        self.get_mut(key).expect("Graph: invalid key.")
+    }
+}
+
+/// The start and end times worked out so far for a graph of tasks, shared
+/// by the views that borrow and own their graph.
+#[derive(Debug, Clone)]
+struct Times<K> {
+    start_times: HashMap<K, u32>,
+    end_times: HashMap<K, u32>,
+    latest_ends: HashMap<K, u32>,
+    project_end: Option<u32>,
+}
+impl<K: Eq + Hash + Copy> Times<K> {
+    fn new () -> Self {
+        Times {
+            start_times: HashMap::new(),
+            end_times: HashMap::new(),
+            latest_ends: HashMap::new(),
+            project_end: None,
+        }
+    }
+    fn end_time<E>(&mut self, graph: &Graph<Task, E, K>, key: &K) -> u32 {
+        if let Some(result) = self.end_times.get(key) {
+            return *result;
+        }
+        
+        let result = graph[key].duration + self.start_time(graph, key);
+
+        self.end_times.insert(*key, result);
+        result
+    }
+    fn start_time<E>(&mut self, graph: &Graph<Task, E, K>, key: &K) -> u32 {
+        if let Some(result) = self.start_times.get(key) {
+            return *result;
+        }
+
+        let result = graph.nodes[key].incoming
+            .iter()
+            .map(|key_out| self.end_time(graph, key_out))
+            .max()
+            .unwrap_or(0)
+            .max(graph[key].not_before.unwrap_or(0));
+
+        self.start_times.insert(*key, result);
+        result
+    }
+    fn project_end<E>(&mut self, graph: &Graph<Task, E, K>) -> u32 {
+        if let Some(result) = self.project_end {
+            return result;
+        }
+
+        let result = graph.nodes.keys()
+            .map(|key| self.end_time(graph, key))
+            .max()
+            .unwrap_or(0);
+
+        self.project_end = Some(result);
+        result
+    }
+    fn latest_end<E>(&mut self, graph: &Graph<Task, E, K>, key: &K) -> u32 {
+        if let Some(result) = self.latest_ends.get(key) {
+            return *result;
+        }
+
+        let project_end = self.project_end(graph);
+        let result = graph.nodes[key].outgoing
+            .iter()
+            .map(|key_in| self.latest_end(graph, key_in) - graph[key_in].duration)
+            .min()
+            .unwrap_or(project_end);
+
+        self.latest_ends.insert(*key, result);
+        result
+    }
+    /// Forgets the times that depend on the duration of the task under
+    /// `key`: the ends of it and everything after it, and the latest ends
+    /// of everything before it, or of every task if the project end moves.
+    fn invalidate<E>(&mut self, graph: &Graph<Task, E, K>, key: &K) {
+        self.end_times.remove(key);
+        for after in graph.descendants(key) {
+            self.start_times.remove(&after);
+            self.end_times.remove(&after);
+        }
+        if let Some(old_end) = self.project_end.take() {
+            if self.project_end(graph) == old_end {
+                for before in graph.ancestors(key) {
+                    self.latest_ends.remove(&before);
+                }
+            }
+            else {
+                self.latest_ends.clear();
+            }
+        }
+    }
+}
+
+pub struct GraphView<'a, E = (), K: Eq + Hash + Copy = Uuid> {
+    graph: &'a Graph<Task, E, K>,
+    times: Times<K>,
+}
+impl<'a, E, K: Eq + Hash + Copy> GraphView<'a, E, K> {
+    pub fn new (graph: &'a Graph<Task, E, K>) -> Self {
+        GraphView {
+            graph,
+            times: Times::new(),
+        }
+    }
+    pub fn end_time(&mut self, key: &K) -> u32 {
+        self.times.end_time(self.graph, key)
+    }
+    pub fn start_time(&mut self, key: &K) -> u32 {
+        self.times.start_time(self.graph, key)
+    }
+    /// The time the last task finishes.
+    pub fn project_end(&mut self) -> u32 {
+        self.times.project_end(self.graph)
+    }
+    /// The latest a task can end without delaying the project: the latest
+    /// start of the earliest of the tasks that wait for it, or the end of
+    /// the project if none do.
+    pub fn latest_end(&mut self, key: &K) -> u32 {
+        self.times.latest_end(self.graph, key)
+    }
+    pub fn latest_start(&mut self, key: &K) -> u32 {
+        self.latest_end(key) - self.graph[key].duration
+    }
+    /// How long a task can slip without delaying the project.
+    pub fn slack(&mut self, key: &K) -> u32 {
+        self.latest_start(key) - self.start_time(key)
+    }
+}
+
+/// A `GraphView` that owns its graph, so task durations can be changed
+/// through it. A change only forgets the times that depend on it, and the
+/// rest are kept.
+#[derive(Debug, Clone)]
+pub struct OwnedGraphView<E = (), K: Eq + Hash + Copy = Uuid> {
+    graph: Graph<Task, E, K>,
+    times: Times<K>,
+}
+impl<E, K: Eq + Hash + Copy> OwnedGraphView<E, K> {
+    pub fn new (graph: Graph<Task, E, K>) -> Self {
+        OwnedGraphView {
+            graph,
+            times: Times::new(),
+        }
+    }
+    pub fn graph(&self) -> &Graph<Task, E, K> {
+        &self.graph
+    }
+    pub fn into_graph(self) -> Graph<Task, E, K> {
+        self.graph
+    }
+    /// Changes the duration of a task, returning the old one, or `None` if
+    /// there is no task under `key`.
+    pub fn set_duration(&mut self, key: &K, duration: u32) -> Option<u32> {
+        let task = self.graph.get_mut(key)?;
+        let old = std::mem::replace(&mut task.duration, duration);
+        if old != duration {
+            self.times.invalidate(&self.graph, key);
+        }
+        Some(old)
+    }
+    pub fn end_time(&mut self, key: &K) -> u32 {
+        self.times.end_time(&self.graph, key)
+    }
+    pub fn start_time(&mut self, key: &K) -> u32 {
+        self.times.start_time(&self.graph, key)
+    }
+    pub fn project_end(&mut self) -> u32 {
+        self.times.project_end(&self.graph)
+    }
+    pub fn latest_end(&mut self, key: &K) -> u32 {
+        self.times.latest_end(&self.graph, key)
+    }
+    pub fn latest_start(&mut self, key: &K) -> u32 {
+        self.latest_end(key) - self.graph[key].duration
+    }
+    pub fn slack(&mut self, key: &K) -> u32 {
+        self.latest_start(key) - self.start_time(key)
+    }
+}
+
+/// Why tasks could not be scheduled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScheduleError<K> {
+    /// The tasks depend on each other in a loop: each on the one before,
+    /// and the first on the last.
+    Cycle(Vec<K>),
+}
+impl<K: fmt::Debug> fmt::Display for ScheduleError<K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScheduleError::Cycle(cycle) => write!(f, "tasks depend on each other in a cycle: {:?}", cycle),
+        }
+    }
+}
+impl<K: fmt::Debug> Error for ScheduleError<K> {}
+
+impl<K> From<EvalError<K>> for ScheduleError<K> {
+    fn from(error: EvalError<K>) -> Self {
+        match error {
+            EvalError::Cycle(cycle) => ScheduleError::Cycle(cycle),
+        }
+    }
+}
+
+/// The start and end time of a task, from the end times of the tasks before
+/// it.
+type TaskTimesFn<K> = fn(&K, &Task, &[(K, &(u32, u32))]) -> (u32, u32);
+
+fn task_times<K>(_key: &K, task: &Task, before: &[(K, &(u32, u32))]) -> (u32, u32) {
+    let start = before.iter()
+        .map(|(_, (_, end))| *end)
+        .fold(task.not_before.unwrap_or(0), u32::max);
+    (start, start + task.duration)
+}
+
+/// Whether two runs of parallel edges hold the same data the same number
+/// of times, whatever order they were added in.
+fn same_edges<E: PartialEq>(ours: &[E], theirs: &[E]) -> bool {
+    if ours.len() != theirs.len() {
+        return false;
+    }
+    let mut matched = vec![false; theirs.len()];
+    ours.iter().all(|edge| {
+        let found = theirs.iter().enumerate().position(|(index, other)| !matched[index] && edge == other);
+        found.map(|index| matched[index] = true).is_some()
+    })
+}
+
+/// A `GraphView` that reports a cycle instead of overflowing the stack.
+pub struct GraphView2<'a, E = (), K: Eq + Hash + Copy = Uuid> {
+    times: DagEval<'a, Task, (u32, u32), TaskTimesFn<K>, E, K>,
+}
+impl<'a, E, K: Eq + Hash + Copy> GraphView2<'a, E, K> {
+    pub fn new (graph: &'a Graph<Task, E, K>) -> Self {
+        GraphView2 {
+            times: DagEval::new(graph, task_times),
+        }
+    }
+    pub fn end_time(&mut self, key: &K) -> Result<u32, ScheduleError<K>> {
+        Ok(self.times.value(key)?.1)
+    }
+    pub fn start_time(&mut self, key: &K) -> Result<u32, ScheduleError<K>> {
+        Ok(self.times.value(key)?.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn graph_view () {
+        let mut graph = Graph::new();
+        let n1 = graph.add_node(Task::new("Lay foundation", 1));
+        let n2 = graph.add_node(Task::new("Build walls", 2));
+        graph.add_edge(&n1, &n2).unwrap();
+        let n3 = graph.add_node(Task::new("Build roof", 4));
+        graph.add_edge(&n2, &n3).unwrap();
+        let n4 = graph.add_node(Task::new("Paint walls", 8));
+        graph.add_edge(&n2, &n4).unwrap();
+        let n5 = graph.add_node(Task::new("Furnish house", 16));
+        graph.add_edge(&n4, &n5).unwrap();
+
+        let mut view = GraphView::new(&graph);
+        assert_eq!(view.start_time(&n5), 11);
+        assert_eq!(view.end_time(&n5), 27);
+        assert_eq!(view.latest_start(&n3), 23);
+        assert_eq!(view.latest_end(&n2), 3);
+        assert_eq!(view.slack(&n3), 20);
+        assert_eq!(view.slack(&n4), 0);
+
+        graph.get_mut(&n4).unwrap().duration = 4;
+        let mut view = GraphView::new(&graph);
+        assert_eq!(view.end_time(&n5), 23);
+        assert!(graph.get_mut(&Uuid::nil()).is_none());
+    }
+
+    #[test]
+    fn find_cycle () {
+        let mut graph = Graph::new();
+        let n1 = graph.add_node(Task::new("Lay foundation", 1));
+        let n2 = graph.add_node(Task::new("Build walls", 2));
+        let n3 = graph.add_node(Task::new("Paint walls", 8));
+        let n4 = graph.add_node(Task::new("Furnish house", 16));
+        graph.add_edge(&n1, &n2).unwrap();
+        graph.add_edge(&n2, &n3).unwrap();
+        graph.add_edge(&n3, &n4).unwrap();
+        assert_eq!(graph.find_cycle(), None);
+
+        graph.add_edge(&n4, &n2).unwrap();
+        let cycle = graph.find_cycle().unwrap();
+        // the cycle may start at any of its nodes.
+        let start = cycle.iter().position(|&key| key == n2).unwrap();
+        let rotated: Vec<Uuid> = cycle[start..].iter().chain(&cycle[..start]).cloned().collect();
+        assert_eq!(rotated, vec![n2, n3, n4]);
+
+        graph.add_edge(&n1, &n1).unwrap();
+        graph.remove_edge(&n4, &n2);
+        assert_eq!(graph.find_cycle(), Some(vec![n1]));
+    }
+
+    #[test]
+    fn own_keys () {
+        let mut graph = Graph::new();
+        assert_eq!(graph.insert_node("walls", Task::new("Build walls", 2)), None);
+        graph.insert_node("roof", Task::new("Build roof", 4));
+        graph.add_edge(&"walls", &"roof").unwrap();
+        let old = graph.insert_node("walls", Task::new("Build walls", 3));
+        assert_eq!(old, Some(Task::new("Build walls", 2)));
+
+        let mut view = GraphView::new(&graph);
+        assert_eq!(view.end_time(&"roof"), 7);
+    }
+
+    #[test]
+    fn index () {
+        let mut graph: Graph<Task> = Graph::new();
+        let walls = graph.add_node(Task::new("Build walls", 2));
+        graph[&walls].duration = 3;
+        assert_eq!(graph[&walls], Task::new("Build walls", 3));
+        assert_eq!(graph.get(&Uuid::new_v4()), None);
+        assert!(graph.get_mut(&Uuid::new_v4()).is_none());
+    }
+
+    #[test]
+    fn edge_data () {
+        let mut graph: Graph<&str, u32, &str> = Graph::new();
+        graph.insert_node("a", "a");
+        graph.insert_node("b", "b");
+        graph.insert_node("c", "c");
+        assert_eq!(graph.insert_edge(&"a", &"b", 3), Ok(None));
+        assert_eq!(graph.insert_edge(&"a", &"b", 5), Ok(Some(3)));
+        graph.insert_edge(&"b", &"c", 1).unwrap();
+        *graph.edge_mut(&"b", &"c").unwrap() += 1;
+
+        assert_eq!(graph.edge(&"a", &"b"), Some(&5));
+        assert_eq!(graph.edge(&"b", &"a"), None);
+        assert_eq!(graph.remove_edge(&"b", &"c"), Some(2));
+        graph.insert_edge(&"b", &"c", 1).unwrap();
+        graph.remove_node(&"b");
+        assert_eq!(graph.edge(&"a", &"b"), None);
+        assert_eq!(graph.edge(&"b", &"c"), None);
+        assert_eq!(graph.neighbors(&"a").count(), 0);
+        assert_eq!(graph.remove_node(&"b"), None);
+        assert_eq!(graph.get(&"b"), None);
+        assert_eq!(graph[&"a"], "a");
+    }
+
+    #[test]
+    fn iteration () {
+        let mut graph = Graph::new();
+        graph.insert_node(1, 10);
+        graph.insert_node(2, 20);
+        graph.add_edge(&1, &2).unwrap();
+        for (_, data) in graph.iter_mut() {
+            *data += 1;
+        }
+
+        let mut nodes: Vec<(u32, u32)> = graph.nodes().map(|(&key, &data)| (key, data)).collect();
+        nodes.sort_unstable();
+        assert_eq!(nodes, vec![(1, 11), (2, 21)]);
+        assert_eq!(graph.node_ids().count(), 2);
+        assert_eq!(graph.edges().collect::<Vec<_>>(), vec![(&1, &2, &())]);
+    }
+
+    #[test]
+    fn owned_graph_view () {
+        let mut graph = Graph::new();
+        graph.insert_node(1, Task::new("Lay foundation", 1));
+        graph.insert_node(2, Task::new("Build walls", 2));
+        graph.insert_node(3, Task::new("Build roof", 4));
+        graph.insert_node(4, Task::new("Paint walls", 8));
+        graph.add_edge(&1, &2).unwrap();
+        graph.add_edge(&2, &3).unwrap();
+        graph.add_edge(&2, &4).unwrap();
+
+        let mut view = OwnedGraphView::new(graph);
+        assert_eq!(view.end_time(&4), 11);
+        assert_eq!(view.slack(&3), 4);
+
+        assert_eq!(view.set_duration(&3, 6), Some(4));
+        // the project end stays the same, so only the latest ends before
+        // the roof are forgotten.
+        assert_eq!(view.times.end_times[&3], 9);
+        assert!(!view.times.latest_ends.contains_key(&2));
+        assert!(view.times.latest_ends.contains_key(&3));
+        assert_eq!(view.slack(&3), 2);
+        assert_eq!(view.latest_end(&1), 1);
+
+        view.set_duration(&1, 3);
+        assert!(view.times.latest_ends.is_empty());
+        assert_eq!(view.end_time(&4), 13);
+        assert_eq!(view.slack(&3), 2);
+        assert_eq!(view.set_duration(&9, 1), None);
+    }
+
+    #[test]
+    fn parallel_edges () {
+        let mut graph = Graph::new();
+        graph.insert_node('a', "a");
+        graph.insert_node('b', "b");
+        assert_eq!(graph.add_parallel_edge(&'a', &'b', "finish to start"), Ok(0));
+        assert_eq!(graph.add_parallel_edge(&'a', &'b', "start to start"), Ok(1));
+        assert_eq!(graph.insert_edge(&'a', &'b', "finish to finish"), Ok(Some("finish to start")));
+        assert_eq!(graph.parallel_edges(&'a', &'b'), &["finish to finish", "start to start"]);
+        assert_eq!(graph.edges().count(), 2);
+
+        assert_eq!(graph.remove_edge(&'a', &'b'), Some("finish to finish"));
+        assert_eq!(graph.edge(&'a', &'b'), Some(&"start to start"));
+        assert!(graph.has_edge(&'a', &'b'));
+        graph.add_parallel_edge(&'a', &'b', "finish to start").unwrap();
+        assert_eq!(graph.remove_parallel_edges(&'a', &'b').len(), 2);
+        assert!(!graph.has_edge(&'a', &'b'));
+        assert_eq!(graph.predecessors(&'b').count(), 0);
+        assert_eq!(graph.parallel_edges(&'a', &'b'), &[] as &[&str]);
+    }
+
+    #[test]
+    fn neighbors () {
+        let mut graph = Graph::new();
+        for node in 1..=4 {
+            graph.insert_node(node, node);
+        }
+        graph.add_edge(&1, &2).unwrap();
+        graph.add_edge(&1, &3).unwrap();
+        graph.add_edge(&4, &3).unwrap();
+
+        let mut neighbors: Vec<u32> = graph.neighbors(&1).copied().collect();
+        neighbors.sort_unstable();
+        assert_eq!(neighbors, vec![2, 3]);
+        let mut predecessors: Vec<u32> = graph.predecessors(&3).copied().collect();
+        predecessors.sort_unstable();
+        assert_eq!(predecessors, vec![1, 4]);
+        assert!(graph.has_edge(&4, &3) && !graph.has_edge(&3, &4));
+        assert_eq!(graph.neighbors(&9).count(), 0);
+    }
+
+    #[test]
+    fn graph_view2_cycle () {
+        let mut graph = Graph::new();
+        for node in 1..=4 {
+            graph.insert_node(node, Task::new("Task", 1));
+        }
+        graph.add_edge(&1, &2).unwrap();
+        graph.add_edge(&2, &3).unwrap();
+        graph.add_edge(&3, &4).unwrap();
+
+        let mut view = GraphView2::new(&graph);
+        assert_eq!(view.end_time(&4), Ok(4));
+        graph.add_edge(&4, &2).unwrap();
+        let mut view = GraphView2::new(&graph);
+        let cycle = match view.end_time(&4) {
+            Err(ScheduleError::Cycle(cycle)) => cycle,
+            result => panic!("expected a cycle, got {:?}", result),
+        };
+        // the cycle may start at any of its nodes.
+        let start = cycle.iter().position(|&key| key == 2).unwrap();
+        let rotated: Vec<u32> = cycle[start..].iter().chain(&cycle[..start]).copied().collect();
+        assert_eq!(rotated, vec![2, 3, 4]);
+        assert!(view.start_time(&1).is_ok());
+    }
+
+    #[test]
+    fn retain_nodes () {
+        let mut graph = Graph::new();
+        for node in 1..=6 {
+            graph.insert_node(node, node);
+        }
+        for node in 1..6 {
+            graph.add_edge(&node, &(node + 1)).unwrap();
+        }
+        graph.retain_nodes(|_, data| data % 3 != 0);
+
+        let mut remaining: Vec<u32> = graph.node_ids().copied().collect();
+        remaining.sort_unstable();
+        assert_eq!(remaining, vec![1, 2, 4, 5]);
+        assert_eq!(graph.edges().count(), 2);
+        assert!(graph.has_edge(&1, &2) && graph.has_edge(&4, &5));
+        assert_eq!(graph.neighbors(&2).count(), 0);
+        assert_eq!(graph.predecessors(&4).count(), 0);
+    }
+
+    #[test]
+    fn missing_endpoints () {
+        let mut graph: Graph<&str, (), char> = Graph::new();
+        graph.insert_node('a', "Lay foundation");
+        assert_eq!(graph.add_edge(&'a', &'b'), Err(GraphError::MissingNode('b')));
+        assert_eq!(graph.add_edge(&'c', &'a'), Err(GraphError::MissingNode('c')));
+        assert_eq!(graph.edges().count(), 0);
+        assert_eq!(graph.neighbors(&'a').count(), 0);
+        assert_eq!(graph.predecessors(&'a').count(), 0);
+
+        assert_eq!(graph.add_edge_with_nodes(&'a', &'b', (), |_| "Build walls"), None);
+        assert!(graph.has_edge(&'a', &'b'));
+        assert_eq!((graph[&'a'], graph[&'b']), ("Lay foundation", "Build walls"));
+
+        let mut weighted: Graph<&str, u32, char> = Graph::new();
+        weighted.add_edge_with_nodes(&'a', &'b', 1, |_| "Unnamed");
+        assert_eq!(weighted.add_edge_with_nodes(&'a', &'b', 2, |_| "Unnamed"), Some(1));
+        assert_eq!(weighted.parallel_edges(&'a', &'b'), &[2]);
+    }
+}
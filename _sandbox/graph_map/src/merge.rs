@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::Graph;
+
+impl<T: Eq + Hash, E, K: Eq + Hash + Copy> Graph<T, E, K> {
+    /// Adds all of `other`'s nodes and edges under the keys they already
+    /// have. A node whose key is in both graphs takes `other`'s data, and
+    /// keeps the edges from both. An edge in both takes `other`'s data, and
+    /// `other`'s parallel edges are added after this graph's.
+    pub fn merge(&mut self, other: Graph<T, E, K>) {
+        self.merge_with(other, |key| *key);
+    }
+    /// Adds all of `other`'s nodes and edges, each node under the key
+    /// `rekey` gives for its old one. Returns the new key of each old one.
+    /// To keep two graphs of random `Uuid`s apart, give every node a new
+    /// one with `|_| Uuid::new_v4()`.
+    pub fn merge_with<F: FnMut(&K) -> K>(&mut self, other: Graph<T, E, K>, mut rekey: F) -> HashMap<K, K> {
+        let keys: HashMap<K, K> = other.nodes.keys()
+            .map(|key| (*key, rekey(key)))
+            .collect();
+        for (key, node) in other.nodes {
+            self.insert_node(keys[&key], node.data);
+        }
+        for ((start, end), parallel) in other.edges {
+            let (start, end) = (keys[&start], keys[&end]);
+            for (index, data) in parallel.into_iter().enumerate() {
+                if index == 0 {
+                    self.replace_edge(&start, &end, data);
+                }
+                else {
+                    self.push_edge(&start, &end, data);
+                }
+            }
+        }
+        keys
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn merge () {
+        let mut graph = Graph::new();
+        graph.insert_node("walls", 2);
+        graph.insert_node("roof", 4);
+        graph.add_edge(&"walls", &"roof").unwrap();
+        let mut other = Graph::new();
+        other.insert_node("roof", 5);
+        other.insert_node("gutters", 1);
+        other.add_edge(&"roof", &"gutters").unwrap();
+
+        graph.merge(other);
+        assert_eq!(graph[&"roof"], 5);
+        assert!(graph.is_reachable(&"walls", &"gutters"));
+    }
+
+    #[test]
+    fn merge_with_new_keys () {
+        let mut graph: Graph<&str> = Graph::new();
+        let walls = graph.add_node("walls");
+        let other = graph.clone();
+
+        let keys = graph.merge_with(other, |_| Uuid::new_v4());
+        assert_eq!(graph.node_ids().count(), 2);
+        assert_ne!(keys[&walls], walls);
+        assert_eq!(graph[&keys[&walls]], "walls");
+    }
+}
@@ -0,0 +1,75 @@
+use std::hash::Hash;
+
+use crate::Graph;
+
+/// The shape of a graph at a glance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Metrics {
+    pub nodes: usize,
+    /// Counting each parallel edge.
+    pub edges: usize,
+    /// The most edges on any path, or `None` if the graph has a cycle.
+    pub longest_path: Option<usize>,
+    /// The most nodes in one of `Graph::layers`: how many tasks can run at
+    /// once at most, going by layers. `None` if the graph has a cycle.
+    pub width: Option<usize>,
+    /// The fraction of the pairs of different nodes with an edge from the
+    /// first to the second.
+    pub density: f64,
+    /// The nodes no edge goes to.
+    pub sources: usize,
+    /// The nodes no edge leaves.
+    pub sinks: usize,
+}
+
+impl<T: Eq + Hash, E, K: Eq + Hash + Copy> Graph<T, E, K> {
+    pub fn metrics(&self) -> Metrics {
+        let nodes = self.nodes.len();
+        let layers = self.layers();
+        let pairs = nodes * nodes.saturating_sub(1);
+        let joined = self.edges.keys().filter(|(start, end)| start != end).count();
+        let distribution = self.degree_distribution();
+        Metrics {
+            nodes,
+            edges: self.edges().count(),
+            longest_path: layers.as_ref().map(|layers| layers.len().saturating_sub(1)),
+            width: layers.as_ref().map(|layers| layers.iter().map(Vec::len).max().unwrap_or(0)),
+            density: if pairs == 0 { 0.0 } else { joined as f64 / pairs as f64 },
+            sources: distribution.sources(),
+            sinks: distribution.sinks(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn metrics () {
+        let mut graph = Graph::new();
+        for node in 1..=5 {
+            graph.insert_node(node, node);
+        }
+        graph.add_edge(&1, &2).unwrap();
+        graph.add_edge(&1, &3).unwrap();
+        graph.add_edge(&2, &4).unwrap();
+        graph.add_edge(&3, &4).unwrap();
+        graph.add_parallel_edge(&3, &4, ()).unwrap();
+
+        assert_eq!(graph.metrics(), Metrics {
+            nodes: 5,
+            edges: 5,
+            longest_path: Some(2),
+            width: Some(2),
+            density: 0.2,
+            sources: 2,
+            sinks: 2,
+        });
+
+        graph.add_edge(&4, &1).unwrap();
+        let metrics = graph.metrics();
+        assert_eq!((metrics.longest_path, metrics.width), (None, None));
+        assert_eq!(Graph::<u32, (), u32>::new().metrics().density, 0.0);
+    }
+}
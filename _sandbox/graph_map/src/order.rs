@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::visit::GraphVisitor;
+use crate::Graph;
+
+/// Collects the nodes as they finish, which is after everything they have
+/// edges to, so the reverse is a topological order.
+struct Finished<K> {
+    order: Vec<K>,
+    cyclic: bool,
+}
+impl<K: Copy> GraphVisitor<K> for Finished<K> {
+    fn finish(&mut self, key: &K) {
+        self.order.push(*key);
+    }
+    fn back_edge(&mut self, _start: &K, _end: &K) {
+        self.cyclic = true;
+    }
+    fn done(&self) -> bool {
+        self.cyclic
+    }
+}
+
+impl<T: Eq + Hash, E, K: Eq + Hash + Copy> Graph<T, E, K> {
+    /// Every node, each before all the nodes it has edges to. Returns
+    /// `None` if the graph has a cycle.
+    pub fn topological_order(&self) -> Option<Vec<K>> {
+        let mut finished = Finished { order: Vec::new(), cyclic: false };
+        self.depth_first(&mut finished);
+        if finished.cyclic {
+            return None;
+        }
+        finished.order.reverse();
+        Some(finished.order)
+    }
+    /// The nodes grouped by the length of the longest path to them from a
+    /// node with no edges in. Edges only go from a layer to a later one,
+    /// so the nodes in a layer can all be done at once, when the layers
+    /// before are done. Returns `None` if the graph has a cycle.
+    pub fn layers(&self) -> Option<Vec<Vec<K>>> {
+        let mut waiting_on: HashMap<K, usize> = self.nodes.iter()
+            .map(|(key, node)| (*key, node.incoming.len()))
+            .collect();
+        let mut layer: Vec<K> = waiting_on.iter()
+            .filter(|(_, &count)| count == 0)
+            .map(|(key, _)| *key)
+            .collect();
+        let mut layers = Vec::new();
+        let mut placed = 0;
+        while !layer.is_empty() {
+            let mut next_layer = Vec::new();
+            for key in &layer {
+                for next in &self.nodes[key].outgoing {
+                    let count = waiting_on.get_mut(next).unwrap();
+                    *count -= 1;
+                    if *count == 0 {
+                        next_layer.push(*next);
+                    }
+                }
+            }
+            placed += layer.len();
+            layers.push(layer);
+            layer = next_layer;
+        }
+        if placed == self.nodes.len() {
+            Some(layers)
+        }
+        else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn layers () {
+        let mut graph = Graph::new();
+        for node in 1..=5 {
+            graph.insert_node(node, node);
+        }
+        graph.add_edge(&1, &2).unwrap();
+        graph.add_edge(&2, &3).unwrap();
+        graph.add_edge(&1, &3).unwrap();
+        graph.add_edge(&4, &3).unwrap();
+
+        let mut layers = graph.layers().unwrap();
+        for layer in &mut layers {
+            layer.sort_unstable();
+        }
+        assert_eq!(layers, vec![vec![1, 4, 5], vec![2], vec![3]]);
+
+        graph.add_edge(&3, &1).unwrap();
+        assert_eq!(graph.layers(), None);
+    }
+
+    #[test]
+    fn topological_order () {
+        let mut graph = Graph::new();
+        for node in 1..=5 {
+            graph.insert_node(node, node);
+        }
+        graph.add_edge(&1, &2).unwrap();
+        graph.add_edge(&2, &3).unwrap();
+        graph.add_edge(&1, &3).unwrap();
+        graph.add_edge(&4, &3).unwrap();
+
+        let order = graph.topological_order().unwrap();
+        assert_eq!(order.len(), 5);
+        let position = |key| order.iter().position(|node| *node == key).unwrap();
+        assert!(graph.edges().all(|(start, end, _)| position(*start) < position(*end)));
+
+        graph.add_edge(&3, &1).unwrap();
+        assert_eq!(graph.topological_order(), None);
+    }
+}
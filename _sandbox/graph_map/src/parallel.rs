@@ -0,0 +1,74 @@
+//! Evaluating a graph's nodes on several threads. The nodes are taken a
+//! layer at a time, as from `Graph::layers`: nothing in a layer depends on
+//! anything else in it, so the whole layer can be computed at once.
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use rayon::prelude::*;
+
+use crate::eval::EvalError;
+use crate::Graph;
+
+impl<T, E, K> Graph<T, E, K>
+where
+    T: Eq + Hash + Sync,
+    E: Sync,
+    K: Eq + Hash + Copy + Send + Sync,
+{
+    /// Works out the value of every node like `DagEval`, computing the
+    /// nodes of each layer in parallel. Worth it when `compute` is slow
+    /// and the layers are wide.
+    pub fn par_eval<V, F>(&self, compute: F) -> Result<HashMap<K, V>, EvalError<K>>
+    where
+        V: Send + Sync,
+        F: Fn(&K, &T, &[(K, &V)]) -> V + Sync,
+    {
+        let layers = match self.layers() {
+            Some(layers) => layers,
+            None => return Err(EvalError::Cycle(self.find_cycle().unwrap())),
+        };
+        let mut values: HashMap<K, V> = HashMap::with_capacity(self.nodes.len());
+        for layer in layers {
+            let computed: Vec<(K, V)> = layer.par_iter()
+                .map(|key| {
+                    let before: Vec<(K, &V)> = self.nodes[key].incoming.iter()
+                        .map(|before| (*before, &values[before]))
+                        .collect();
+                    (*key, compute(key, &self.nodes[key].data, &before))
+                })
+                .collect();
+            values.extend(computed);
+        }
+        Ok(values)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::eval::DagEval;
+
+    #[test]
+    fn par_eval () {
+        let mut graph: Graph<u64, (), u32> = Graph::new();
+        for node in 0..40 {
+            graph.insert_node(node, u64::from(node));
+        }
+        for node in 10..40 {
+            graph.add_edge(&(node - 10), &node).unwrap();
+            graph.add_edge(&(node / 2), &node).unwrap();
+        }
+        let compute = |_: &u32, data: &u64, before: &[(u32, &u64)]| {
+            data + before.iter().map(|(_, value)| **value).sum::<u64>()
+        };
+
+        let values = graph.par_eval(compute).unwrap();
+        let mut serial = DagEval::new(&graph, compute);
+        for node in 0..40 {
+            assert_eq!(serial.value(&node), Ok(&values[&node]));
+        }
+
+        graph.add_edge(&30, &0).unwrap();
+        assert!(matches!(graph.par_eval(compute), Err(EvalError::Cycle(_))));
+    }
+}
@@ -0,0 +1,98 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::Hash;
+use std::ops::Add;
+
+use crate::Graph;
+
+/// A node waiting in the frontier, ordered by cost alone so keys don't
+/// need to be `Ord`.
+struct Frontier<C, K> {
+    cost: C,
+    key: K,
+}
+impl<C: Ord, K> PartialEq for Frontier<C, K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cost == other.cost
+    }
+}
+impl<C: Ord, K> Eq for Frontier<C, K> {}
+impl<C: Ord, K> PartialOrd for Frontier<C, K> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl<C: Ord, K> Ord for Frontier<C, K> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.cost.cmp(&other.cost)
+    }
+}
+
+impl<T: Eq + Hash, E, K: Eq + Hash + Copy> Graph<T, E, K> {
+    /// The cheapest path from `start` to `end` following edges, with its
+    /// total cost, or `None` if `end` can't be reached. `cost` gives the
+    /// cost of each edge from its data, and must not be negative:
+    /// `C::default()` is the cost of the empty path. Of parallel edges, the
+    /// cheapest is taken.
+    pub fn shortest_path<C, F>(&self, start: &K, end: &K, cost: F) -> Option<(Vec<K>, C)>
+    where
+        C: Copy + Ord + Default + Add<Output = C>,
+        F: Fn(&E) -> C,
+    {
+        self.get(start)?;
+        let mut best = HashMap::new();
+        let mut previous = HashMap::new();
+        let mut frontier = BinaryHeap::new();
+        best.insert(*start, C::default());
+        frontier.push(Reverse(Frontier { cost: C::default(), key: *start }));
+
+        while let Some(Reverse(Frontier { cost: so_far, key })) = frontier.pop() {
+            if key == *end {
+                let mut path = vec![key];
+                while let Some(before) = previous.get(path.last().unwrap()) {
+                    path.push(*before);
+                }
+                path.reverse();
+                return Some((path, so_far));
+            }
+            // a stale entry, the node was reached more cheaply since.
+            if best.get(&key).is_some_and(|&cheapest| so_far > cheapest) {
+                continue;
+            }
+            for next in &self.nodes[&key].outgoing {
+                let cheapest_edge = self.edges[&(key, *next)].iter().map(&cost).min().unwrap();
+                let total = so_far + cheapest_edge;
+                if best.get(next).is_none_or(|&cheapest| total < cheapest) {
+                    best.insert(*next, total);
+                    previous.insert(*next, key);
+                    frontier.push(Reverse(Frontier { cost: total, key: *next }));
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn shortest_path () {
+        let mut graph: Graph<char, u32, char> = Graph::new();
+        for node in "abcde".chars() {
+            graph.insert_node(node, node);
+        }
+        graph.insert_edge(&'a', &'b', 4).unwrap();
+        graph.insert_edge(&'a', &'c', 1).unwrap();
+        graph.insert_edge(&'c', &'b', 2).unwrap();
+        graph.insert_edge(&'b', &'d', 5).unwrap();
+        graph.insert_edge(&'c', &'d', 8).unwrap();
+
+        assert_eq!(graph.shortest_path(&'a', &'d', |&weight| weight), Some((vec!['a', 'c', 'b', 'd'], 8)));
+        assert_eq!(graph.shortest_path(&'a', &'d', |_| 1).map(|(_, cost)| cost), Some(2));
+        assert_eq!(graph.shortest_path(&'a', &'a', |&weight| weight), Some((vec!['a'], 0)));
+        assert_eq!(graph.shortest_path(&'a', &'e', |&weight| weight), None);
+        assert_eq!(graph.shortest_path(&'x', &'a', |&weight| weight), None);
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use uuid::Uuid;
+
+use crate::Graph;
+
+/// Float durations this close together count as the same.
+const EPSILON: f64 = 1e-9;
+
+/// A PERT task: how long it takes at best, most likely, and at worst.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Estimate {
+    pub name: &'static str,
+    pub optimistic: u32,
+    pub most_likely: u32,
+    pub pessimistic: u32,
+}
+impl Estimate {
+    /// Returns `None` unless `optimistic <= most_likely <= pessimistic`.
+    pub fn new(name: &'static str, optimistic: u32, most_likely: u32, pessimistic: u32) -> Option<Self> {
+        if optimistic <= most_likely && most_likely <= pessimistic {
+            Some(Estimate { name, optimistic, most_likely, pessimistic })
+        } else {
+            None
+        }
+    }
+    /// The mean of the beta distribution PERT assumes, weighting the most
+    /// likely duration four times the others.
+    pub fn expected (&self) -> f64 {
+        (f64::from(self.optimistic) + 4.0 * f64::from(self.most_likely) + f64::from(self.pessimistic)) / 6.0
+    }
+    /// A sixth of the range, squared.
+    pub fn variance (&self) -> f64 {
+        let deviation = (f64::from(self.pessimistic) - f64::from(self.optimistic)) / 6.0;
+        deviation * deviation
+    }
+}
+
+/// A PERT analysis of a task graph: the critical path method run on each
+/// task's expected duration, with the uncertainty of the project's length
+/// taken as the sum of the variances along the critical path. Where the
+/// project has more than one critical path, the one with the most variance
+/// is used.
+#[derive(Debug, Clone)]
+pub struct Pert<K: Eq + Hash + Copy = Uuid> {
+    expected_ends: HashMap<K, f64>,
+    critical_path: Vec<K>,
+    duration: f64,
+    variance: f64,
+}
+impl<K: Eq + Hash + Copy> Pert<K> {
+    /// Returns `None` if the tasks depend on each other in a cycle.
+    pub fn new<E> (graph: &Graph<Estimate, E, K>) -> Option<Self> {
+        // for each task, its earliest expected end, the variance of the
+        // path to it, and the task before it on that path.
+        let mut ends: HashMap<K, (f64, f64, Option<K>)> = HashMap::new();
+        for key in graph.layers()?.into_iter().flatten() {
+            let mut longest: (f64, f64, Option<K>) = (0.0, 0.0, None);
+            for start in &graph.nodes[&key].incoming {
+                let (end, variance, _) = ends[start];
+                if end > longest.0 + EPSILON || (end > longest.0 - EPSILON && variance > longest.1) {
+                    longest = (end, variance, Some(*start));
+                }
+            }
+            let estimate = &graph[&key];
+            ends.insert(key, (longest.0 + estimate.expected(), longest.1 + estimate.variance(), longest.2));
+        }
+
+        let mut last: Option<(K, f64, f64)> = None;
+        for (key, &(end, variance, _)) in &ends {
+            if last.is_none_or(|(_, last_end, last_variance)| {
+                end > last_end + EPSILON || (end > last_end - EPSILON && variance > last_variance)
+            }) {
+                last = Some((*key, end, variance));
+            }
+        }
+        let (duration, variance) = last.map_or((0.0, 0.0), |(_, end, variance)| (end, variance));
+        let mut critical_path = Vec::new();
+        let mut current = last.map(|(key, _, _)| key);
+        while let Some(key) = current {
+            critical_path.push(key);
+            current = ends[&key].2;
+        }
+        critical_path.reverse();
+
+        let expected_ends = ends.into_iter().map(|(key, (end, _, _))| (key, end)).collect();
+        Some(Pert { expected_ends, critical_path, duration, variance })
+    }
+    /// The expected time the last task finishes.
+    pub fn expected_duration (&self) -> f64 {
+        self.duration
+    }
+    /// The variance of the project's duration.
+    pub fn variance (&self) -> f64 {
+        self.variance
+    }
+    pub fn standard_deviation (&self) -> f64 {
+        self.variance.sqrt()
+    }
+    /// The earliest time the task is expected to finish.
+    pub fn expected_end (&self, key: &K) -> Option<f64> {
+        self.expected_ends.get(key).copied()
+    }
+    /// The tasks the expected duration and variance were worked out along,
+    /// in the order they run.
+    pub fn critical_path (&self) -> &[K] {
+        &self.critical_path
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn house () {
+        let mut graph = Graph::new();
+        let foundation = graph.add_node(Estimate::new("Lay foundation", 1, 1, 1).unwrap());
+        let walls = graph.add_node(Estimate::new("Build walls", 1, 2, 9).unwrap());
+        let roof = graph.add_node(Estimate::new("Build roof", 2, 4, 6).unwrap());
+        let paint = graph.add_node(Estimate::new("Paint walls", 2, 3, 10).unwrap());
+        graph.add_edge(&foundation, &walls).unwrap();
+        graph.add_edge(&walls, &roof).unwrap();
+        graph.add_edge(&walls, &paint).unwrap();
+
+        // walls are expected to take 3 days, the roof 4 and the paint 4.
+        // both paths are as long, so the paint's wider range wins.
+        let pert = Pert::new(&graph).unwrap();
+        assert!((pert.expected_duration() - 8.0).abs() < EPSILON);
+        assert_eq!(pert.critical_path(), &[foundation, walls, paint][..]);
+        let variance = (8.0f64 / 6.0).powi(2) * 2.0;
+        assert!((pert.variance() - variance).abs() < EPSILON);
+        assert!((pert.expected_end(&roof).unwrap() - 8.0).abs() < EPSILON);
+
+        graph.add_edge(&paint, &foundation).unwrap();
+        assert!(Pert::new(&graph).is_none());
+    }
+
+    #[test]
+    fn estimates () {
+        let fixed = Estimate::new("Fixed", 5, 5, 5).unwrap();
+        assert!((fixed.expected() - 5.0).abs() < EPSILON);
+        assert_eq!(fixed.variance(), 0.0);
+
+        assert!(Estimate::new("Backwards", 9, 2, 1).is_none());
+        assert!(Estimate::new("Likely too soon", 2, 1, 9).is_none());
+        assert!(Estimate::new("Likely too late", 1, 10, 9).is_none());
+
+        // neither sum fits in a u32.
+        let long = Estimate::new("Long", 0, u32::MAX, u32::MAX).unwrap();
+        assert!((long.expected() - f64::from(u32::MAX) * 5.0 / 6.0).abs() < 1.0);
+        assert!((long.variance() - (f64::from(u32::MAX) / 6.0).powi(2)).abs() < 1.0);
+    }
+}
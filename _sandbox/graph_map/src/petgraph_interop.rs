@@ -0,0 +1,104 @@
+//! Conversions to and from petgraph's graphs, to use its algorithms on a
+//! `Graph` and bring the results back.
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::graphmap::DiGraphMap;
+
+use crate::Graph;
+
+/// The keys are dropped, as petgraph numbers its nodes itself.
+impl<T: Eq + Hash, E, K: Eq + Hash + Copy> From<Graph<T, E, K>> for DiGraph<T, E> {
+    fn from(graph: Graph<T, E, K>) -> Self {
+        let mut result = DiGraph::with_capacity(graph.nodes.len(), graph.edges.len());
+        let indices: HashMap<K, NodeIndex> = graph.nodes.into_iter()
+            .map(|(key, node)| (key, result.add_node(node.data)))
+            .collect();
+        for ((start, end), parallel) in graph.edges {
+            for data in parallel {
+                result.add_edge(indices[&start], indices[&end], data);
+            }
+        }
+        result
+    }
+}
+
+/// Each node is keyed by its petgraph index.
+impl<T: Eq + Hash, E> From<DiGraph<T, E>> for Graph<T, E, NodeIndex> {
+    fn from(graph: DiGraph<T, E>) -> Self {
+        let mut result = Graph::new();
+        let (nodes, edges) = graph.into_nodes_edges();
+        for (index, node) in nodes.into_iter().enumerate() {
+            result.insert_node(NodeIndex::new(index), node.weight);
+        }
+        for edge in edges {
+            result.push_edge(&edge.source(), &edge.target(), edge.weight);
+        }
+        result
+    }
+}
+
+/// A graph map's nodes are its keys, so the node data is dropped. It can
+/// only hold one edge from a node to another, so of parallel edges only
+/// the last is kept.
+impl<T: Eq + Hash, E, K: Eq + Hash + Copy + Ord> From<Graph<T, E, K>> for DiGraphMap<K, E> {
+    fn from(graph: Graph<T, E, K>) -> Self {
+        let mut result = DiGraphMap::with_capacity(graph.nodes.len(), graph.edges.len());
+        for key in graph.nodes.keys() {
+            result.add_node(*key);
+        }
+        for ((start, end), parallel) in graph.edges {
+            for data in parallel {
+                result.add_edge(start, end, data);
+            }
+        }
+        result
+    }
+}
+
+/// Each node's data is its key.
+impl<E, K: Eq + Hash + Copy + Ord> From<DiGraphMap<K, E>> for Graph<K, E, K> {
+    fn from(graph: DiGraphMap<K, E>) -> Self {
+        let mut result = Graph::new();
+        let (nodes, edges) = graph.into_graph::<u32>().into_nodes_edges();
+        let keys: Vec<K> = nodes.into_iter().map(|node| node.weight).collect();
+        for key in &keys {
+            result.insert_node(*key, *key);
+        }
+        for edge in edges {
+            result.push_edge(&keys[edge.source().index()], &keys[edge.target().index()], edge.weight);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trips () {
+        let mut graph: Graph<&str, u32, char> = Graph::new();
+        graph.insert_node('a', "a");
+        graph.insert_node('b', "b");
+        graph.insert_node('c', "c");
+        graph.insert_edge(&'a', &'b', 1).unwrap();
+        graph.insert_edge(&'b', &'c', 2).unwrap();
+
+        let map: DiGraphMap<char, u32> = graph.clone().into();
+        assert_eq!(map.edge_weight('b', 'c'), Some(&2));
+        let back: Graph<char, u32, char> = map.into();
+        assert_eq!(back.edge(&'a', &'b'), Some(&1));
+        assert_eq!(back[&'c'], 'c');
+
+        let numbered: DiGraph<&str, u32> = graph.into();
+        assert_eq!(numbered.node_count(), 3);
+        let order = petgraph::algo::toposort(&numbered, None).unwrap();
+        let names: Vec<&str> = order.iter().map(|index| numbered[*index]).collect();
+        assert_eq!(names, vec!["a", "b", "c"]);
+        let back: Graph<&str, u32, NodeIndex> = numbered.into();
+        assert_eq!(back.edge(&order[1], &order[2]), Some(&2));
+        assert_eq!(back[&order[0]], "a");
+    }
+}
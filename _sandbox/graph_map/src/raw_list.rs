@@ -0,0 +1,334 @@
+//! A cursor list linked with raw pointers instead of `Rc<RefCell<..>>`, so
+//! moving and changing it doesn't count references or check borrows. It has
+//! the basic cursor operations of `cycles::List`: moving, inserting,
+//! removing, splitting and joining, but none of the rest.
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+
+type Link<T> = Option<NonNull<Node<T>>>;
+/// The first and last nodes of a run of them.
+type Run<T> = (NonNull<Node<T>>, NonNull<Node<T>>);
+
+struct Node<T> {
+    previous: Link<T>,
+    next: Link<T>,
+    data: T,
+}
+
+/// Where the cursor is: before the first element, on one, or after the
+/// last.
+enum Cursor<T> {
+    Head,
+    Node(NonNull<Node<T>>),
+    Tail,
+}
+impl<T> Clone for Cursor<T> {
+    fn clone (&self) -> Self {
+        *self
+    }
+}
+impl<T> Copy for Cursor<T> {}
+
+pub struct List<T> {
+    first: Link<T>,
+    last: Link<T>,
+    current: Cursor<T>,
+    /// The list owns its nodes, as if they were boxed.
+    marker: PhantomData<Box<Node<T>>>,
+}
+
+// the nodes are only reached through the list, like `std::collections::LinkedList`.
+unsafe impl<T: Send> Send for List<T> {}
+unsafe impl<T: Sync> Sync for List<T> {}
+
+impl<T> List<T> {
+    /// An empty list with the cursor at the head.
+    pub fn new () -> Self {
+        List { first: None, last: None, current: Cursor::Head, marker: PhantomData }
+    }
+    pub fn is_empty (&self) -> bool {
+        self.first.is_none()
+    }
+    pub fn advance (&mut self) {
+        self.current = match self.current {
+            Cursor::Head => self.first.map_or(Cursor::Tail, Cursor::Node),
+            Cursor::Node(node) => unsafe { node.as_ref() }.next.map_or(Cursor::Tail, Cursor::Node),
+            Cursor::Tail => Cursor::Tail,
+        };
+    }
+    pub fn retreat (&mut self) {
+        self.current = match self.current {
+            Cursor::Head => Cursor::Head,
+            Cursor::Node(node) => unsafe { node.as_ref() }.previous.map_or(Cursor::Head, Cursor::Node),
+            Cursor::Tail => self.last.map_or(Cursor::Head, Cursor::Node),
+        };
+    }
+    /// The element under the cursor, or `None` at the head or tail.
+    pub fn current (&self) -> Option<&T> {
+        match self.current {
+            Cursor::Node(node) => Some(unsafe { &(*node.as_ptr()).data }),
+            _ => None,
+        }
+    }
+    pub fn current_mut (&mut self) -> Option<&mut T> {
+        match self.current {
+            Cursor::Node(node) => Some(unsafe { &mut (*node.as_ptr()).data }),
+            _ => None,
+        }
+    }
+    /// Does nothing at the tail.
+    pub fn insert_after (&mut self, data: T) {
+        if !matches!(self.current, Cursor::Tail) {
+            let (previous, next) = self.around_cursor();
+            unsafe { self.link(previous, data, next) };
+        }
+    }
+    /// Does nothing at the head.
+    pub fn insert_before (&mut self, data: T) {
+        if !matches!(self.current, Cursor::Head) {
+            let (previous, next) = (self.before_cursor(), self.cursor_or_after());
+            unsafe { self.link(previous, data, next) };
+        }
+    }
+
+    /// Removes the element after the cursor, if there is one.
+    pub fn remove_after (&mut self) -> Option<T> {
+        let next = self.after_cursor()?;
+        Some(unsafe { self.unlink(next) })
+    }
+    /// Removes the element before the cursor, if there is one.
+    pub fn remove_before (&mut self) -> Option<T> {
+        let previous = self.before_cursor()?;
+        Some(unsafe { self.unlink(previous) })
+    }
+    /// Removes the element under the cursor, moving the cursor on to the
+    /// element after it, or the tail. Does nothing at the head or tail.
+    pub fn remove_current (&mut self) -> Option<T> {
+        match self.current {
+            Cursor::Node(node) => {
+                self.advance();
+                Some(unsafe { self.unlink(node) })
+            }
+            _ => None,
+        }
+    }
+
+    /// Moves the elements after the cursor into a new list, with its cursor
+    /// at its head. At the tail, or the last element, the new list is empty.
+    pub fn split_after (&mut self) -> Self {
+        let next = match self.after_cursor() {
+            Some(next) => next,
+            None => return List::new(),
+        };
+        let current = self.cursor_node();
+        unsafe {
+            (*next.as_ptr()).previous = None;
+            self.set_next(current, None);
+        }
+        let last = std::mem::replace(&mut self.last, current);
+        List { first: Some(next), last, current: Cursor::Head, marker: PhantomData }
+    }
+    /// Moves the elements before the cursor into a new list, with its
+    /// cursor at its head. At the head, or the first element, the new list
+    /// is empty.
+    pub fn split_before (&mut self) -> Self {
+        let previous = match self.before_cursor() {
+            Some(previous) => previous,
+            None => return List::new(),
+        };
+        let current = self.cursor_node();
+        unsafe {
+            (*previous.as_ptr()).next = None;
+            self.set_previous(current, None);
+        }
+        let first = std::mem::replace(&mut self.first, current);
+        List { first, last: Some(previous), current: Cursor::Head, marker: PhantomData }
+    }
+    /// Moves the elements of `other` onto the end of this list. A cursor at
+    /// the tail stays at the tail.
+    pub fn join (&mut self, mut other: Self) {
+        if let Some((first, last)) = other.detach() {
+            let previous = self.last;
+            unsafe { self.connect(previous, first, last, None) };
+        }
+    }
+    /// Moves the elements of `other` in after the cursor, in order, leaving
+    /// the cursor where it is. At the tail they go on the end, before it.
+    pub fn splice_after (&mut self, mut other: Self) {
+        if let Some((first, last)) = other.detach() {
+            let (previous, next) = match self.current {
+                Cursor::Tail => (self.last, None),
+                _ => self.around_cursor(),
+            };
+            unsafe { self.connect(previous, first, last, next) };
+        }
+    }
+
+    /// The node under the cursor, if it is on one.
+    fn cursor_node (&self) -> Link<T> {
+        match self.current {
+            Cursor::Node(node) => Some(node),
+            _ => None,
+        }
+    }
+    fn after_cursor (&self) -> Link<T> {
+        match self.current {
+            Cursor::Head => self.first,
+            Cursor::Node(node) => unsafe { node.as_ref() }.next,
+            Cursor::Tail => None,
+        }
+    }
+    fn before_cursor (&self) -> Link<T> {
+        match self.current {
+            Cursor::Head => None,
+            Cursor::Node(node) => unsafe { node.as_ref() }.previous,
+            Cursor::Tail => self.last,
+        }
+    }
+    /// The node under the cursor, or the first one after it.
+    fn cursor_or_after (&self) -> Link<T> {
+        self.cursor_node().or_else(|| self.after_cursor())
+    }
+    /// The nodes either side of the gap just after the cursor.
+    fn around_cursor (&self) -> (Link<T>, Link<T>) {
+        (self.cursor_node(), self.after_cursor())
+    }
+
+    /// Points `node`'s next link at `next`, or the list's first link if
+    /// there's no node.
+    unsafe fn set_next (&mut self, node: Link<T>, next: Link<T>) {
+        match node {
+            Some(node) => (*node.as_ptr()).next = next,
+            None => self.first = next,
+        }
+    }
+    unsafe fn set_previous (&mut self, node: Link<T>, previous: Link<T>) {
+        match node {
+            Some(node) => (*node.as_ptr()).previous = previous,
+            None => self.last = previous,
+        }
+    }
+    /// Puts the run of nodes from `first` to `last` between `previous` and
+    /// `next`, which must be next to each other in this list.
+    unsafe fn connect (&mut self, previous: Link<T>, first: NonNull<Node<T>>, last: NonNull<Node<T>>, next: Link<T>) {
+        (*first.as_ptr()).previous = previous;
+        (*last.as_ptr()).next = next;
+        self.set_next(previous, Some(first));
+        self.set_previous(next, Some(last));
+    }
+    unsafe fn link (&mut self, previous: Link<T>, data: T, next: Link<T>) {
+        let node = Box::new(Node { previous, next, data });
+        let node = NonNull::from(Box::leak(node));
+        self.connect(previous, node, node, next);
+    }
+    /// Takes a node of this list out of it, joining the nodes either side,
+    /// and frees it. The cursor mustn't be on it.
+    unsafe fn unlink (&mut self, node: NonNull<Node<T>>) -> T {
+        let node = Box::from_raw(node.as_ptr());
+        self.set_next(node.previous, node.next);
+        self.set_previous(node.next, node.previous);
+        node.data
+    }
+    /// Leaves this list empty, without freeing its nodes, which belong to
+    /// another list now, and returns the first and last of them.
+    fn detach (&mut self) -> Option<Run<T>> {
+        self.current = Cursor::Head;
+        Some((self.first.take()?, self.last.take()?))
+    }
+}
+impl<T> Drop for List<T> {
+    fn drop (&mut self) {
+        self.current = Cursor::Head;
+        while let Some(first) = self.first {
+            unsafe { self.unlink(first) };
+        }
+    }
+}
+impl<T> Default for List<T> {
+    fn default () -> Self {
+        List::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// The elements from head to tail, leaving the cursor where it was.
+    fn contents<T: Clone>(list: &List<T>) -> Vec<T> {
+        let mut contents = Vec::new();
+        let mut node = list.first;
+        while let Some(current) = node {
+            let current = unsafe { current.as_ref() };
+            contents.push(current.data.clone());
+            node = current.next;
+        }
+        contents
+    }
+
+    fn list_of(values: &[u32]) -> List<u32> {
+        let mut list = List::new();
+        list.advance();
+        for value in values {
+            list.insert_before(*value);
+        }
+        list
+    }
+
+    #[test]
+    fn split_and_join () {
+        let mut list = list_of(&[1, 2, 3, 4]);
+        assert!(list.split_after().is_empty());
+        list.retreat();
+        list.retreat();
+        let after = list.split_after();
+        assert_eq!((contents(&list), contents(&after)), (vec![1, 2, 3], vec![4]));
+        let before = list.split_before();
+        assert_eq!((contents(&before), contents(&list)), (vec![1, 2], vec![3]));
+        assert_eq!(list.current(), Some(&3));
+
+        list.join(after);
+        list.splice_after(before);
+        assert_eq!(contents(&list), vec![3, 1, 2, 4]);
+        list.retreat();
+        list.splice_after(list_of(&[5]));
+        list.join(List::new());
+        assert_eq!(contents(&list), vec![5, 3, 1, 2, 4]);
+        list.retreat();
+        assert_eq!(contents(&list.split_after()), vec![5, 3, 1, 2, 4]);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn remove_and_change () {
+        let mut list = list_of(&[1, 2, 3, 4]);
+        assert_eq!(list.remove_after(), None);
+        assert_eq!(list.remove_before(), Some(4));
+        list.retreat();
+        *list.current_mut().unwrap() += 10;
+        assert_eq!(list.remove_current(), Some(13));
+        assert_eq!(list.remove_current(), None);
+        list.retreat();
+        list.retreat();
+        assert_eq!(list.remove_before(), None);
+        assert_eq!(list.remove_after(), Some(2));
+        assert_eq!(list.remove_current(), Some(1));
+        assert!(list.is_empty());
+        list.insert_before(5);
+        assert_eq!(contents(&list), vec![5]);
+    }
+
+    #[test]
+    fn drop () {
+        let counted = std::rc::Rc::new(());
+        let mut list = List::new();
+        for _ in 0..3 {
+            list.insert_after(counted.clone());
+        }
+        list.advance();
+        let other = list.split_after();
+        list.splice_after(other);
+        std::mem::drop(list);
+        assert_eq!(std::rc::Rc::strong_count(&counted), 1);
+    }
+}
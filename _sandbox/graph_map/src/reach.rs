@@ -0,0 +1,104 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use crate::{Graph, GraphNode};
+
+impl<T: Eq + Hash, E, K: Eq + Hash + Copy> Graph<T, E, K> {
+    /// Whether there is a path from `from` to `to`. Every node can reach
+    /// itself.
+    pub fn is_reachable(&self, from: &K, to: &K) -> bool {
+        if self.get(from).is_none() {
+            return false;
+        }
+        from == to || self.walk(from, |node| &node.outgoing, Some(to)).contains(to)
+    }
+    /// The nodes that can be reached from `key` by following one or more
+    /// edges. `key` is only among them if it is on a cycle.
+    pub fn descendants(&self, key: &K) -> HashSet<K> {
+        self.walk(key, |node| &node.outgoing, None)
+    }
+    /// The nodes that can reach `key` by following one or more edges.
+    pub fn ancestors(&self, key: &K) -> HashSet<K> {
+        self.walk(key, |node| &node.incoming, None)
+    }
+
+    /// Every node reachable from `key` going along `next`, stopping early
+    /// once `target` is found.
+    fn walk<F>(&self, key: &K, next: F, target: Option<&K>) -> HashSet<K>
+    where
+        F: Fn(&GraphNode<K, T>) -> &HashSet<K>,
+    {
+        let mut seen = HashSet::new();
+        let mut stack = vec![*key];
+        while let Some(current) = stack.pop() {
+            let node = match self.nodes.get(&current) {
+                Some(node) => node,
+                None => continue,
+            };
+            for following in next(node) {
+                if seen.insert(*following) {
+                    if Some(following) == target {
+                        return seen;
+                    }
+                    stack.push(*following);
+                }
+            }
+        }
+        seen
+    }
+}
+
+/// The transitive closure of a graph, for answering many reachability
+/// queries on a graph that isn't changing. It is a snapshot: changes to the
+/// graph after it is built aren't seen.
+#[derive(Debug, Clone)]
+pub struct Reachability<K: Eq + Hash> {
+    descendants: HashMap<K, HashSet<K>>,
+}
+impl<K: Eq + Hash + Copy> Reachability<K> {
+    pub fn new<T: Eq + Hash, E> (graph: &Graph<T, E, K>) -> Self {
+        let descendants = graph.nodes.keys()
+            .map(|key| (*key, graph.descendants(key)))
+            .collect();
+        Reachability { descendants }
+    }
+    pub fn is_reachable(&self, from: &K, to: &K) -> bool {
+        match self.descendants.get(from) {
+            Some(descendants) => from == to || descendants.contains(to),
+            None => false,
+        }
+    }
+    pub fn descendants(&self, key: &K) -> Option<&HashSet<K>> {
+        self.descendants.get(key)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reachability () {
+        let mut graph = Graph::new();
+        for node in "abcde".chars() {
+            graph.insert_node(node, node);
+        }
+        graph.add_edge(&'a', &'b').unwrap();
+        graph.add_edge(&'b', &'c').unwrap();
+        graph.add_edge(&'d', &'c').unwrap();
+
+        assert!(graph.is_reachable(&'a', &'c'));
+        assert!(graph.is_reachable(&'e', &'e'));
+        assert!(!graph.is_reachable(&'c', &'a'));
+        assert!(!graph.is_reachable(&'x', &'x'));
+        assert_eq!(graph.descendants(&'a'), vec!['b', 'c'].into_iter().collect());
+        assert_eq!(graph.ancestors(&'c'), vec!['a', 'b', 'd'].into_iter().collect());
+
+        graph.add_edge(&'c', &'a').unwrap();
+        assert!(graph.descendants(&'a').contains(&'a'));
+        let closure = Reachability::new(&graph);
+        assert!(closure.is_reachable(&'c', &'b'));
+        assert!(!closure.is_reachable(&'a', &'d'));
+        assert_eq!(closure.descendants(&'e'), Some(&HashSet::new()));
+    }
+}
@@ -0,0 +1,197 @@
+//! Scheduling with a limited number of workers. The critical path method
+//! in `schedule` assumes every task can start as soon as the tasks before
+//! it finish, however many tasks that leaves running at once. Here each task
+//! needs a worker from a pool for as long as it runs, and waits while its
+//! pool has none free.
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use uuid::Uuid;
+
+use crate::schedule::{self, MissedDeadline, Schedule};
+use crate::{Graph, Task};
+
+/// When a task runs, and which of its pool's workers runs it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Assignment {
+    pub start: u32,
+    pub end: u32,
+    /// Numbered from 0 within the task's pool.
+    pub worker: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct ResourceSchedule<K: Eq + Hash + Copy = Uuid> {
+    assignments: HashMap<K, Assignment>,
+    deadlines: HashMap<K, u32>,
+    duration: u32,
+}
+impl<K: Eq + Hash + Copy> ResourceSchedule<K> {
+    /// Schedules the tasks with `workers` workers who can each do any
+    /// task. Returns `None` if the tasks depend on each other in a cycle,
+    /// or there are no workers and something to do.
+    pub fn with_workers<E> (graph: &Graph<Task, E, K>, workers: usize) -> Option<Self> {
+        let mut capacities = HashMap::new();
+        capacities.insert((), workers);
+        ResourceSchedule::with_pools(graph, &capacities, |_| ())
+    }
+    /// Schedules the tasks with named pools of workers: `pool` gives the
+    /// pool a task draws from, and `capacities` the number of workers in
+    /// each. Returns `None` if the tasks depend on each other in a cycle,
+    /// or a task needs a pool with no workers.
+    ///
+    /// Whenever a worker is free, the ready task with the highest priority
+    /// goes first, then the one with the earliest latest start, so the
+    /// critical path is delayed as little as it can be. No task starts
+    /// before its `not_before` time.
+    pub fn with_pools<E, R, F> (graph: &Graph<Task, E, K>, capacities: &HashMap<R, usize>, pool: F) -> Option<Self>
+    where
+        R: Eq + Hash + Clone,
+        F: Fn(&K) -> R,
+    {
+        let critical = Schedule::new(graph)?;
+        let priority = |key: &K| {
+            let times = critical.times(key).unwrap();
+            (Reverse(graph[key].priority), times.latest_start, times.earliest_start)
+        };
+        let mut free: HashMap<R, Vec<usize>> = capacities.iter()
+            .map(|(pool, &capacity)| (pool.clone(), (0..capacity).rev().collect()))
+            .collect();
+        let mut waiting_on: HashMap<K, usize> = graph.nodes.iter()
+            .map(|(key, node)| (*key, node.incoming.len()))
+            .collect();
+        let mut ready: Vec<K> = waiting_on.iter()
+            .filter(|(_, &count)| count == 0)
+            .map(|(key, _)| *key)
+            .collect();
+        let mut running: Vec<(K, R)> = Vec::new();
+        let mut assignments = HashMap::new();
+        let mut now = 0;
+
+        while assignments.len() < graph.nodes.len() {
+            ready.sort_by_key(|key| priority(key));
+            let mut still_ready = Vec::new();
+            for key in ready {
+                if graph[&key].not_before.is_some_and(|time| time > now) {
+                    still_ready.push(key);
+                    continue;
+                }
+                let pool = pool(&key);
+                match free.get_mut(&pool).and_then(Vec::pop) {
+                    Some(worker) => {
+                        let end = now + graph[&key].duration;
+                        assignments.insert(key, Assignment { start: now, end, worker });
+                        running.push((key, pool));
+                    }
+                    None => still_ready.push(key),
+                }
+            }
+            ready = still_ready;
+
+            // nothing running and nothing waiting to start: a pool is empty.
+            let released = ready.iter().filter_map(|key| graph[key].not_before).filter(|&time| time > now);
+            now = running.iter().map(|(key, _)| assignments[key].end).chain(released).min()?;
+            let (finished, unfinished) = running.into_iter()
+                .partition(|(key, _)| assignments[key].end == now);
+            running = unfinished;
+            for (key, pool) in finished {
+                free.get_mut(&pool).unwrap().push(assignments[&key].worker);
+                for next in &graph.nodes[&key].outgoing {
+                    let count = waiting_on.get_mut(next).unwrap();
+                    *count -= 1;
+                    if *count == 0 {
+                        ready.push(*next);
+                    }
+                }
+            }
+        }
+        let duration = assignments.values().map(|assignment| assignment.end).max().unwrap_or(0);
+        let deadlines = schedule::deadlines(graph);
+        Some(ResourceSchedule { assignments, deadlines, duration })
+    }
+    /// The time the last task finishes.
+    pub fn duration (&self) -> u32 {
+        self.duration
+    }
+    pub fn assignment (&self, key: &K) -> Option<&Assignment> {
+        self.assignments.get(key)
+    }
+    /// The tasks that end after their deadline.
+    pub fn missed_deadlines (&self) -> Vec<MissedDeadline<K>> {
+        schedule::missed_deadlines(self.assignments.iter().map(|(key, assignment)| (*key, assignment.end)), &self.deadlines)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn house () -> Graph<Task, (), &'static str> {
+        let mut graph = Graph::new();
+        graph.insert_node("foundation", Task::new("Lay foundation", 1));
+        graph.insert_node("walls", Task::new("Build walls", 2));
+        graph.insert_node("roof", Task::new("Build roof", 4));
+        graph.insert_node("paint", Task::new("Paint walls", 8));
+        graph.insert_node("furnish", Task::new("Furnish house", 16));
+        graph.add_edge(&"foundation", &"walls").unwrap();
+        graph.add_edge(&"walls", &"roof").unwrap();
+        graph.add_edge(&"walls", &"paint").unwrap();
+        graph.add_edge(&"paint", &"furnish").unwrap();
+        graph
+    }
+
+    #[test]
+    fn workers () {
+        let graph = house();
+        let alone = ResourceSchedule::with_workers(&graph, 1).unwrap();
+        assert_eq!(alone.duration(), 31);
+        // the painting and furnishing are critical, so they go before the roof.
+        assert_eq!(alone.assignment(&"paint").unwrap().start, 3);
+        assert_eq!(alone.assignment(&"roof").unwrap().start, 27);
+
+        let pair = ResourceSchedule::with_workers(&graph, 2).unwrap();
+        assert_eq!(pair.duration(), 27);
+        let (roof, paint) = (pair.assignment(&"roof").unwrap(), pair.assignment(&"paint").unwrap());
+        assert_eq!((roof.start, paint.start), (3, 3));
+        assert_ne!(roof.worker, paint.worker);
+
+        assert!(ResourceSchedule::with_workers(&graph, 0).is_none());
+    }
+
+    #[test]
+    fn pools () {
+        let graph = house();
+        let mut capacities = HashMap::new();
+        capacities.insert("builder", 1);
+        capacities.insert("decorator", 1);
+        let pool = |key: &&str| if ["paint", "furnish"].contains(key) { "decorator" } else { "builder" };
+
+        let schedule = ResourceSchedule::with_pools(&graph, &capacities, pool).unwrap();
+        assert_eq!(schedule.duration(), 27);
+        capacities.remove("decorator");
+        assert!(ResourceSchedule::with_pools(&graph, &capacities, pool).is_none());
+    }
+
+    #[test]
+    fn constraints () {
+        let mut graph = house();
+        graph.insert_node("roof", Task::new("Build roof", 4).with_priority(1).with_deadline(8));
+        graph.insert_node("paint", Task::new("Paint walls", 8).with_not_before(4));
+
+        // the roof goes first for its priority, though the painting is critical.
+        let alone = ResourceSchedule::with_workers(&graph, 1).unwrap();
+        assert_eq!(alone.assignment(&"roof").unwrap().start, 3);
+        assert_eq!(alone.assignment(&"paint").unwrap().start, 7);
+        assert!(alone.missed_deadlines().is_empty());
+
+        let pair = ResourceSchedule::with_workers(&graph, 2).unwrap();
+        assert_eq!(pair.assignment(&"paint").unwrap().start, 4);
+        assert_eq!(pair.duration(), 28);
+
+        let mut graph = house();
+        graph.insert_node("roof", Task::new("Build roof", 4).with_deadline(8));
+        let missed = ResourceSchedule::with_workers(&graph, 1).unwrap().missed_deadlines();
+        assert_eq!(missed, vec![MissedDeadline { task: "roof", end: 31, deadline: 8 }]);
+    }
+}
@@ -0,0 +1,281 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+#[cfg(feature = "calendar")]
+use std::fmt;
+
+use uuid::Uuid;
+
+#[cfg(feature = "calendar")]
+use crate::calendar::{self, Date};
+use crate::{Graph, GraphView, Task};
+
+/// When a task can run. The earliest times come from `GraphView`'s forward
+/// pass, and the latest, from its backward pass, are the latest the task
+/// can start and end without delaying the whole project.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TaskTimes {
+    pub earliest_start: u32,
+    pub earliest_end: u32,
+    pub latest_start: u32,
+    pub latest_end: u32,
+}
+impl TaskTimes {
+    /// How long the task can slip without delaying the project.
+    pub fn total_float(&self) -> u32 {
+        self.latest_start - self.earliest_start
+    }
+    /// Whether any delay to the task delays the project.
+    pub fn is_critical(&self) -> bool {
+        self.total_float() == 0
+    }
+}
+
+/// A task that ends after its deadline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MissedDeadline<K> {
+    pub task: K,
+    pub end: u32,
+    pub deadline: u32,
+}
+
+/// The tasks in `ends` that end after their deadline, in the order they end.
+pub(crate) fn missed_deadlines<K, I>(ends: I, deadlines: &HashMap<K, u32>) -> Vec<MissedDeadline<K>>
+where
+    K: Eq + Hash + Copy,
+    I: Iterator<Item = (K, u32)>,
+{
+    let mut missed: Vec<MissedDeadline<K>> = ends
+        .filter_map(|(task, end)| {
+            let deadline = *deadlines.get(&task)?;
+            if end > deadline { Some(MissedDeadline { task, end, deadline }) } else { None }
+        })
+        .collect();
+    missed.sort_by_key(|missed| (missed.end, missed.deadline));
+    missed
+}
+
+pub(crate) fn deadlines<E, K: Eq + Hash + Copy>(graph: &Graph<Task, E, K>) -> HashMap<K, u32> {
+    graph.nodes()
+        .filter_map(|(key, task)| Some((*key, task.deadline?)))
+        .collect()
+}
+
+/// A critical path analysis of a task graph, where an edge from one task to
+/// another means the second can not start until the first has finished.
+#[derive(Debug, Clone)]
+pub struct Schedule<K: Eq + Hash + Copy = Uuid> {
+    times: HashMap<K, TaskTimes>,
+    names: HashMap<K, &'static str>,
+    deadlines: HashMap<K, u32>,
+    duration: u32,
+}
+impl<K: Eq + Hash + Copy> Schedule<K> {
+    /// Schedules every task in the graph. Returns `None` if the tasks
+    /// depend on each other in a cycle, which `Graph::find_cycle` can
+    /// report.
+    pub fn new<E> (graph: &Graph<Task, E, K>) -> Option<Self> {
+        if graph.find_cycle().is_some() {
+            return None;
+        }
+        let mut view = GraphView::new(graph);
+        let duration = view.project_end();
+        let times = graph.nodes.keys()
+            .map(|key| {
+                let times = TaskTimes {
+                    earliest_start: view.start_time(key),
+                    earliest_end: view.end_time(key),
+                    latest_start: view.latest_start(key),
+                    latest_end: view.latest_end(key),
+                };
+                (*key, times)
+            })
+            .collect();
+        let names = graph.nodes().map(|(key, task)| (*key, task.name)).collect();
+        let deadlines = deadlines(graph);
+        Some(Schedule { times, names, deadlines, duration })
+    }
+    /// The time the last task finishes.
+    pub fn duration (&self) -> u32 {
+        self.duration
+    }
+    pub fn times (&self, key: &K) -> Option<&TaskTimes> {
+        self.times.get(key)
+    }
+    /// The tasks with no float. Delaying any of them delays the project.
+    pub fn critical_tasks (&self) -> HashSet<K> {
+        self.times.iter()
+            .filter(|(_, times)| times.is_critical())
+            .map(|(key, _)| *key)
+            .collect()
+    }
+    /// A text Gantt chart with a row for each task, in the order they
+    /// start, and a column for each unit of time. A task runs over the
+    /// `#`s if it is critical, or the `=`s otherwise, and can slip into
+    /// the `.`s after them. Critical tasks are marked with a `*`.
+    pub fn render_gantt (&self) -> String {
+        let mut rows: Vec<(&K, &TaskTimes)> = self.times.iter().collect();
+        rows.sort_by_key(|(key, times)| (times.earliest_start, times.earliest_end, self.names[key]));
+        let width = self.names.values().map(|name| name.chars().count()).max().unwrap_or(0);
+
+        let mut chart = String::new();
+        for (key, times) in rows {
+            let bar = if times.is_critical() { "#" } else { "=" };
+            let length = times.earliest_end - times.earliest_start;
+            let after = self.duration - times.earliest_end - times.total_float();
+            chart.push_str(&format!(
+                "{:width$} |{}{}{}{}| {}-{}{}\n",
+                self.names[key],
+                " ".repeat(times.earliest_start as usize),
+                bar.repeat(length as usize),
+                ".".repeat(times.total_float() as usize),
+                " ".repeat(after as usize),
+                times.earliest_start,
+                times.earliest_end,
+                if times.is_critical() { " *" } else { "" },
+                width = width,
+            ));
+        }
+        chart
+    }
+    /// The tasks that end after their deadline even at their earliest.
+    pub fn missed_deadlines (&self) -> Vec<MissedDeadline<K>> {
+        missed_deadlines(self.times.iter().map(|(key, times)| (*key, times.earliest_end)), &self.deadlines)
+    }
+    /// The critical tasks in the order they run.
+    pub fn critical_path (&self) -> Vec<K> {
+        let mut path: Vec<K> = self.critical_tasks().into_iter().collect();
+        path.sort_by_key(|key| (self.times[key].earliest_start, self.times[key].earliest_end));
+        path
+    }
+}
+#[cfg(feature = "calendar")]
+impl<K: Eq + Hash + Copy + fmt::Display> Schedule<K> {
+    /// An iCalendar file with an all day event for each task, at its
+    /// earliest times, when the project starts on `start` and each unit of
+    /// time is a day. Critical tasks are marked as such in the description,
+    /// and the others say how many days they can slip.
+    pub fn to_ics (&self, start: Date) -> String {
+        let mut rows: Vec<(&K, &TaskTimes)> = self.times.iter().collect();
+        rows.sort_by_key(|(key, times)| (times.earliest_start, times.earliest_end, self.names[key]));
+        let stamp = calendar::timestamp();
+
+        let mut lines = vec![
+            "BEGIN:VCALENDAR".to_string(),
+            "VERSION:2.0".to_string(),
+            "PRODID:-//graph_map//Schedule//EN".to_string(),
+        ];
+        for (key, times) in rows {
+            let description = if times.is_critical() {
+                "Critical".to_string()
+            }
+            else {
+                format!("{} days of float", times.total_float())
+            };
+            lines.push("BEGIN:VEVENT".to_string());
+            lines.push(format!("UID:{}@graph_map", key));
+            lines.push(format!("DTSTAMP:{}", stamp));
+            lines.push(format!("DTSTART;VALUE=DATE:{}", start.add_days(times.earliest_start)));
+            // the end of an all day event is the day after it.
+            lines.push(format!("DTEND;VALUE=DATE:{}", start.add_days(times.earliest_end)));
+            lines.push(format!("SUMMARY:{}", calendar::escape(self.names[key])));
+            lines.push(format!("DESCRIPTION:{}", description));
+            lines.push("END:VEVENT".to_string());
+        }
+        lines.push("END:VCALENDAR".to_string());
+
+        let mut ics = lines.join("\r\n");
+        ics.push_str("\r\n");
+        ics
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn house () {
+        let mut graph = Graph::new();
+        let foundation = graph.add_node(Task::new("Lay foundation", 1));
+        let walls = graph.add_node(Task::new("Build walls", 2));
+        let roof = graph.add_node(Task::new("Build roof", 4));
+        let paint = graph.add_node(Task::new("Paint walls", 8));
+        let furnish = graph.add_node(Task::new("Furnish house", 16));
+        graph.add_edge(&foundation, &walls).unwrap();
+        graph.add_edge(&walls, &roof).unwrap();
+        graph.add_edge(&walls, &paint).unwrap();
+        graph.add_edge(&paint, &furnish).unwrap();
+
+        let schedule = Schedule::new(&graph).unwrap();
+        assert_eq!(schedule.duration(), 27);
+        assert_eq!(schedule.critical_path(), vec![foundation, walls, paint, furnish]);
+        let roof_times = schedule.times(&roof).unwrap();
+        assert_eq!(*roof_times, TaskTimes { earliest_start: 3, earliest_end: 7, latest_start: 23, latest_end: 27 });
+        assert_eq!(roof_times.total_float(), 20);
+        assert!(!schedule.critical_tasks().contains(&roof));
+
+        graph.add_edge(&furnish, &walls).unwrap();
+        assert!(Schedule::new(&graph).is_none());
+    }
+
+    #[test]
+    fn render_gantt () {
+        let mut graph = Graph::new();
+        let foundation = graph.add_node(Task::new("Foundation", 1));
+        let walls = graph.add_node(Task::new("Walls", 2));
+        let roof = graph.add_node(Task::new("Roof", 1));
+        let paint = graph.add_node(Task::new("Paint", 3));
+        graph.add_edge(&foundation, &walls).unwrap();
+        graph.add_edge(&walls, &roof).unwrap();
+        graph.add_edge(&walls, &paint).unwrap();
+
+        let chart = Schedule::new(&graph).unwrap().render_gantt();
+        let expected = "\
+Foundation |#     | 0-1 *
+Walls      | ##   | 1-3 *
+Roof       |   =..| 3-4
+Paint      |   ###| 3-6 *
+";
+        assert_eq!(chart, expected);
+    }
+
+    #[cfg(feature = "calendar")]
+    #[test]
+    fn to_ics () {
+        let mut graph: Graph<Task, (), char> = Graph::new();
+        graph.insert_node('f', Task::new("Foundation", 1));
+        graph.insert_node('w', Task::new("Walls, then roof", 2));
+        graph.add_edge(&'f', &'w').unwrap();
+
+        let ics = Schedule::new(&graph).unwrap().to_ics(Date::new(2024, 2, 28).unwrap());
+        let lines: Vec<&str> = ics.split("\r\n").collect();
+        assert_eq!(lines[..3], ["BEGIN:VCALENDAR", "VERSION:2.0", "PRODID:-//graph_map//Schedule//EN"]);
+        let walls = lines.iter().position(|line| *line == "UID:w@graph_map").unwrap();
+        assert_eq!(lines[walls + 2..walls + 6], [
+            "DTSTART;VALUE=DATE:20240229",
+            "DTEND;VALUE=DATE:20240302",
+            "SUMMARY:Walls\\, then roof",
+            "DESCRIPTION:Critical",
+        ]);
+        assert_eq!(lines.iter().filter(|line| **line == "BEGIN:VEVENT").count(), 2);
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    }
+
+    #[test]
+    fn constraints () {
+        let mut graph = Graph::new();
+        let foundation = graph.add_node(Task::new("Lay foundation", 1));
+        let walls = graph.add_node(Task::new("Build walls", 2).with_deadline(4));
+        let delivery = graph.add_node(Task::new("Deliver roof", 1).with_not_before(5));
+        let roof = graph.add_node(Task::new("Build roof", 4).with_deadline(9));
+        graph.add_edge(&foundation, &walls).unwrap();
+        graph.add_edge(&walls, &roof).unwrap();
+        graph.add_edge(&delivery, &roof).unwrap();
+
+        let schedule = Schedule::new(&graph).unwrap();
+        assert_eq!(schedule.times(&delivery).unwrap().earliest_start, 5);
+        assert_eq!(schedule.times(&roof).unwrap().earliest_end, 10);
+        assert_eq!(schedule.critical_path(), vec![delivery, roof]);
+        assert_eq!(schedule.missed_deadlines(), vec![MissedDeadline { task: roof, end: 10, deadline: 9 }]);
+    }
+}
@@ -0,0 +1,102 @@
+//! Graphs serialize as a list of nodes and a list of edges:
+//!
+//! ```json
+//! {
+//!   "nodes": [{ "id": "a", "data": "Lay foundation" }, { "id": "b", "data": "Build walls" }],
+//!   "edges": [{ "from": "a", "to": "b", "data": null }]
+//! }
+//! ```
+//!
+//! Parallel edges are listed in the order they were added. Deserializing
+//! fails if two nodes have the same id, or an edge starts or ends at a
+//! node that isn't in the list.
+use std::hash::Hash;
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::Graph;
+
+#[derive(Serialize, Deserialize)]
+struct Node<K, T> {
+    id: K,
+    data: T,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Edge<K, E> {
+    from: K,
+    to: K,
+    data: E,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Contents<K, T, E> {
+    nodes: Vec<Node<K, T>>,
+    edges: Vec<Edge<K, E>>,
+}
+
+impl<T, E, K> Serialize for Graph<T, E, K>
+where
+    T: Eq + Hash + Serialize,
+    E: Serialize,
+    K: Eq + Hash + Copy + Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let nodes = self.nodes.iter()
+            .map(|(key, node)| Node { id: *key, data: &node.data })
+            .collect();
+        let edges = self.edges()
+            .map(|(&from, &to, data)| Edge { from, to, data })
+            .collect();
+        Contents { nodes, edges }.serialize(serializer)
+    }
+}
+
+impl<'de, T, E, K> Deserialize<'de> for Graph<T, E, K>
+where
+    T: Eq + Hash + Deserialize<'de>,
+    E: Deserialize<'de>,
+    K: Eq + Hash + Copy + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let contents = Contents::<K, T, E>::deserialize(deserializer)?;
+        let mut graph = Graph::new();
+        for node in contents.nodes {
+            if graph.get(&node.id).is_some() {
+                return Err(D::Error::custom("two nodes have the same id"));
+            }
+            graph.insert_node(node.id, node.data);
+        }
+        for edge in contents.edges {
+            graph.add_parallel_edge(&edge.from, &edge.to, edge.data)
+                .map_err(|_| D::Error::custom("an edge joins a node that doesn't exist"))?;
+        }
+        Ok(graph)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Graph;
+
+    #[test]
+    fn round_trip () {
+        let mut graph: Graph<String, u32, u32> = Graph::new();
+        graph.insert_node(1, "Lay foundation".to_string());
+        graph.insert_node(2, "Build walls".to_string());
+        graph.insert_edge(&1, &2, 5).unwrap();
+
+        let json = serde_json::to_string(&graph).unwrap();
+        let read: Graph<String, u32, u32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(read[&2], "Build walls");
+        assert_eq!(read.edge(&1, &2), Some(&5));
+        assert!(read.has_edge(&1, &2));
+        assert!(read.structurally_eq(&graph));
+
+        let dangling = r#"{"nodes": [{"id": 1, "data": "a"}], "edges": [{"from": 1, "to": 2, "data": 0}]}"#;
+        assert!(serde_json::from_str::<Graph<String, u32, u32>>(dangling).is_err());
+        let duplicate = r#"{"nodes": [{"id": 1, "data": "a"}, {"id": 1, "data": "b"}], "edges": []}"#;
+        assert!(serde_json::from_str::<Graph<String, u32, u32>>(duplicate).is_err());
+    }
+}
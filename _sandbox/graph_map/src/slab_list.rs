@@ -0,0 +1,252 @@
+//! A doubly linked list kept in a `Vec`, with links that are indices into
+//! it rather than pointers. Elements are reached by the handles given out
+//! when they're inserted, so any of them can be removed or moved in constant
+//! time, as in a least recently used cache.
+
+/// Refers to an element of a `SlabList`. A handle to an element that has
+/// been removed refers to nothing, even once its slot is used again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Handle {
+    index: usize,
+    generation: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Entry<T> {
+    previous: Option<usize>,
+    next: Option<usize>,
+    data: T,
+}
+
+#[derive(Debug, Clone)]
+struct Slot<T> {
+    /// How many times the slot has been emptied.
+    generation: usize,
+    entry: Option<Entry<T>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SlabList<T> {
+    slots: Vec<Slot<T>>,
+    /// The empty slots, to use before growing.
+    free: Vec<usize>,
+    first: Option<usize>,
+    last: Option<usize>,
+    len: usize,
+}
+
+impl<T> SlabList<T> {
+    pub fn new () -> Self {
+        SlabList::with_capacity(0)
+    }
+    pub fn with_capacity (capacity: usize) -> Self {
+        SlabList { slots: Vec::with_capacity(capacity), free: Vec::new(), first: None, last: None, len: 0 }
+    }
+    pub fn len (&self) -> usize {
+        self.len
+    }
+    pub fn is_empty (&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn front (&self) -> Option<Handle> {
+        self.first.map(|index| self.handle(index))
+    }
+    pub fn back (&self) -> Option<Handle> {
+        self.last.map(|index| self.handle(index))
+    }
+    /// The element after `handle`'s, or `None` at the back or if `handle`
+    /// refers to nothing.
+    pub fn next (&self, handle: Handle) -> Option<Handle> {
+        self.entry(handle)?.next.map(|index| self.handle(index))
+    }
+    pub fn previous (&self, handle: Handle) -> Option<Handle> {
+        self.entry(handle)?.previous.map(|index| self.handle(index))
+    }
+    pub fn get (&self, handle: Handle) -> Option<&T> {
+        self.entry(handle).map(|entry| &entry.data)
+    }
+    pub fn get_mut (&mut self, handle: Handle) -> Option<&mut T> {
+        self.check(handle)?;
+        Some(&mut self.entry_mut(handle.index).data)
+    }
+
+    pub fn push_front (&mut self, data: T) -> Handle {
+        let first = self.first;
+        self.link(None, data, first)
+    }
+    pub fn push_back (&mut self, data: T) -> Handle {
+        let last = self.last;
+        self.link(last, data, None)
+    }
+    /// Inserts `data` after `handle`'s element, or does nothing and returns
+    /// `None` if `handle` refers to nothing.
+    pub fn insert_after (&mut self, handle: Handle, data: T) -> Option<Handle> {
+        let next = self.entry(handle)?.next;
+        Some(self.link(Some(handle.index), data, next))
+    }
+    pub fn insert_before (&mut self, handle: Handle, data: T) -> Option<Handle> {
+        let previous = self.entry(handle)?.previous;
+        Some(self.link(previous, data, Some(handle.index)))
+    }
+
+    pub fn remove (&mut self, handle: Handle) -> Option<T> {
+        self.check(handle)?;
+        self.unlink(handle.index);
+        let slot = &mut self.slots[handle.index];
+        slot.generation += 1;
+        self.free.push(handle.index);
+        self.len -= 1;
+        slot.entry.take().map(|entry| entry.data)
+    }
+    pub fn pop_front (&mut self) -> Option<T> {
+        self.front().and_then(|handle| self.remove(handle))
+    }
+    pub fn pop_back (&mut self) -> Option<T> {
+        self.back().and_then(|handle| self.remove(handle))
+    }
+    /// Moves `handle`'s element to the front, keeping its handle. Returns
+    /// whether `handle` refers to anything.
+    pub fn move_to_front (&mut self, handle: Handle) -> bool {
+        if self.check(handle).is_none() {
+            return false;
+        }
+        self.unlink(handle.index);
+        let first = self.first;
+        self.relink(None, handle.index, first);
+        true
+    }
+
+    /// The elements from front to back.
+    pub fn iter (&self) -> Iter<'_, T> {
+        Iter { list: self, next: self.first }
+    }
+
+    fn handle (&self, index: usize) -> Handle {
+        Handle { index, generation: self.slots[index].generation }
+    }
+    fn check (&self, handle: Handle) -> Option<()> {
+        self.entry(handle).map(|_| ())
+    }
+    fn entry (&self, handle: Handle) -> Option<&Entry<T>> {
+        self.slots.get(handle.index)
+            .filter(|slot| slot.generation == handle.generation)
+            .and_then(|slot| slot.entry.as_ref())
+    }
+    fn entry_mut (&mut self, index: usize) -> &mut Entry<T> {
+        self.slots[index].entry.as_mut().expect("SlabList: link to an empty slot.")
+    }
+
+    /// Points the next link of the element at `index` at `next`, or the
+    /// front of the list if there's no index.
+    fn set_next (&mut self, index: Option<usize>, next: Option<usize>) {
+        match index {
+            Some(index) => self.entry_mut(index).next = next,
+            None => self.first = next,
+        }
+    }
+    fn set_previous (&mut self, index: Option<usize>, previous: Option<usize>) {
+        match index {
+            Some(index) => self.entry_mut(index).previous = previous,
+            None => self.last = previous,
+        }
+    }
+    /// Puts `data` in a slot between `previous` and `next`, which must be
+    /// next to each other.
+    fn link (&mut self, previous: Option<usize>, data: T, next: Option<usize>) -> Handle {
+        let entry = Some(Entry { previous, next, data });
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.slots[index].entry = entry;
+                index
+            }
+            None => {
+                self.slots.push(Slot { generation: 0, entry });
+                self.slots.len() - 1
+            }
+        };
+        self.relink(previous, index, next);
+        self.len += 1;
+        self.handle(index)
+    }
+    /// Puts the element at `index`, which isn't in the list, between
+    /// `previous` and `next`.
+    fn relink (&mut self, previous: Option<usize>, index: usize, next: Option<usize>) {
+        let entry = self.entry_mut(index);
+        entry.previous = previous;
+        entry.next = next;
+        self.set_next(previous, Some(index));
+        self.set_previous(next, Some(index));
+    }
+    /// Takes the element at `index` out of the list, joining the elements
+    /// either side of it, but leaves it in its slot.
+    fn unlink (&mut self, index: usize) {
+        let Entry { previous, next, .. } = *self.entry_mut(index);
+        self.set_next(previous, next);
+        self.set_previous(next, previous);
+    }
+}
+impl<T> Default for SlabList<T> {
+    fn default () -> Self {
+        SlabList::new()
+    }
+}
+
+pub struct Iter<'a, T> {
+    list: &'a SlabList<T>,
+    next: Option<usize>,
+}
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+    fn next (&mut self) -> Option<&'a T> {
+        let entry = self.list.slots[self.next?].entry.as_ref().unwrap();
+        self.next = entry.next;
+        Some(&entry.data)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn contents (list: &SlabList<u32>) -> Vec<u32> {
+        list.iter().copied().collect()
+    }
+
+    #[test]
+    fn link_and_remove () {
+        let mut list = SlabList::new();
+        let two = list.push_back(2);
+        let one = list.push_front(1);
+        let four = list.push_back(4);
+        let three = list.insert_after(two, 3).unwrap();
+        assert_eq!(contents(&list), vec![1, 2, 3, 4]);
+        assert_eq!(list.next(one), Some(two));
+        assert_eq!(list.previous(four), Some(three));
+
+        assert_eq!(list.remove(two), Some(2));
+        assert_eq!(list.remove(two), None);
+        // the slot is used again, but the old handle stays dead.
+        let five = list.insert_before(one, 5).unwrap();
+        assert_eq!(five.index, two.index);
+        assert_eq!(list.get(two), None);
+        assert!(list.insert_after(two, 6).is_none());
+        *list.get_mut(five).unwrap() += 10;
+        assert_eq!(contents(&list), vec![15, 1, 3, 4]);
+        assert_eq!((list.pop_front(), list.pop_back()), (Some(15), Some(4)));
+        assert_eq!(list.len(), 2);
+    }
+
+    #[test]
+    fn least_recently_used () {
+        let mut list = SlabList::new();
+        let handles: Vec<Handle> = (0..4).map(|value| list.push_front(value)).collect();
+        assert!(list.move_to_front(handles[0]));
+        assert!(list.move_to_front(handles[2]));
+        assert!(list.move_to_front(handles[2]));
+        assert_eq!(contents(&list), vec![2, 0, 3, 1]);
+        assert_eq!(list.pop_back(), Some(1));
+        assert!(!list.move_to_front(handles[1]));
+        assert_eq!(list.back(), Some(handles[3]));
+    }
+}
@@ -0,0 +1,96 @@
+//! Random graphs, for trying algorithms and benchmarks on something bigger
+//! than a hand-built example. Each takes the random number generator to
+//! use, so a seeded one gives the same graph every time.
+use rand::Rng;
+
+use crate::{Graph, Task};
+
+/// The Erdős–Rényi graph G(n, p): `n` nodes, numbered from 0, with an edge
+/// from each to each other one with probability `p`.
+pub fn gnp<R: Rng>(n: usize, p: f64, rng: &mut R) -> Graph<usize, (), usize> {
+    let mut graph = nodes(n);
+    for start in 0..n {
+        for end in 0..n {
+            if start != end && rng.gen_bool(p) {
+                graph.push_edge(&start, &end, ());
+            }
+        }
+    }
+    graph
+}
+
+/// A graph with no cycles: `n` nodes, numbered from 0, with an edge from
+/// each to each later one with probability `density`.
+pub fn random_dag<R: Rng>(n: usize, density: f64, rng: &mut R) -> Graph<usize, (), usize> {
+    let mut graph = nodes(n);
+    for start in 0..n {
+        for end in start + 1..n {
+            if rng.gen_bool(density) {
+                graph.push_edge(&start, &end, ());
+            }
+        }
+    }
+    graph
+}
+
+/// A project of `layers` layers of `width` tasks, each lasting from 1 to
+/// `max_duration`. Each task after the first layer depends on each task in
+/// the layer before with probability `density`, and on at least one of
+/// them. Tasks are numbered from 0 a layer at a time.
+pub fn layered_tasks<R: Rng>(
+    layers: usize,
+    width: usize,
+    density: f64,
+    max_duration: u32,
+    rng: &mut R,
+) -> Graph<Task, (), usize> {
+    let mut graph = Graph::new();
+    for key in 0..layers * width {
+        graph.insert_node(key, Task::new("Task", rng.gen_range(1, max_duration + 1)));
+    }
+    for layer in 1..layers {
+        let before = (layer - 1) * width..layer * width;
+        for end in layer * width..(layer + 1) * width {
+            for start in before.clone() {
+                if rng.gen_bool(density) {
+                    graph.replace_edge(&start, &end, ());
+                }
+            }
+            if graph.predecessors(&end).next().is_none() {
+                graph.replace_edge(&rng.gen_range(before.start, before.end), &end, ());
+            }
+        }
+    }
+    graph
+}
+
+fn nodes(n: usize) -> Graph<usize, (), usize> {
+    let mut graph = Graph::new();
+    for key in 0..n {
+        graph.insert_node(key, key);
+    }
+    graph
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn generators () {
+        let mut rng = StdRng::seed_from_u64(7);
+        assert_eq!(gnp(10, 1.0, &mut rng).edges().count(), 90);
+        assert_eq!(gnp(10, 0.0, &mut rng).edges().count(), 0);
+
+        let dag = random_dag(50, 0.2, &mut rng);
+        assert_eq!(dag.node_ids().count(), 50);
+        assert_eq!(dag.find_cycle(), None);
+
+        let tasks = layered_tasks(5, 4, 0.3, 10, &mut rng);
+        let layers = tasks.layers().unwrap();
+        assert_eq!(layers.iter().map(Vec::len).collect::<Vec<usize>>(), vec![4; 5]);
+        assert!(tasks.nodes().all(|(_, task)| (1..=10).contains(&task.duration)));
+    }
+}
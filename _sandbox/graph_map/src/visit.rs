@@ -0,0 +1,125 @@
+//! One depth first search for the algorithms that need one. An algorithm
+//! implements `GraphVisitor` to hear about each node and edge as the
+//! search reaches them, and `Graph::depth_first` does the searching.
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::Graph;
+
+/// The events of a depth first search. Every method does nothing unless
+/// it is overridden.
+pub trait GraphVisitor<K> {
+    /// The search has reached `key` for the first time.
+    fn discover(&mut self, _key: &K) {}
+    /// Everything reachable from `key` has been searched.
+    fn finish(&mut self, _key: &K) {}
+    /// The search goes along the edge to a node it hasn't reached yet.
+    fn tree_edge(&mut self, _start: &K, _end: &K) {}
+    /// An edge to a node on the search's current path, closing a cycle.
+    fn back_edge(&mut self, _start: &K, _end: &K) {}
+    /// An edge to a node that is already finished.
+    fn cross_edge(&mut self, _start: &K, _end: &K) {}
+    /// Whether to stop searching, asked before each step.
+    fn done(&self) -> bool {
+        false
+    }
+}
+
+impl<T: Eq + Hash, E, K: Eq + Hash + Copy> Graph<T, E, K> {
+    /// Searches from every node in turn, skipping those reached already.
+    pub fn depth_first<V: GraphVisitor<K>>(&self, visitor: &mut V) {
+        let mut finished = HashMap::new();
+        for key in self.nodes.keys() {
+            if !finished.contains_key(key) && !self.depth_first_with(key, visitor, &mut finished) {
+                return;
+            }
+        }
+    }
+    /// Searches the nodes reachable from `start`.
+    pub fn depth_first_from<V: GraphVisitor<K>>(&self, start: &K, visitor: &mut V) {
+        if self.nodes.contains_key(start) {
+            self.depth_first_with(start, visitor, &mut HashMap::new());
+        }
+    }
+
+    /// The search itself, which keeps its own stack so deep graphs don't
+    /// overflow the real one. `finished` is `false` for the nodes on the
+    /// current path and `true` for those done with. Returns `false` if the
+    /// visitor stopped it.
+    fn depth_first_with<V: GraphVisitor<K>>(&self, start: &K, visitor: &mut V, finished: &mut HashMap<K, bool>) -> bool {
+        visitor.discover(start);
+        finished.insert(*start, false);
+        let mut stack = vec![(*start, self.nodes[start].outgoing.iter())];
+        while let Some((key, next)) = stack.last_mut() {
+            if visitor.done() {
+                return false;
+            }
+            let key = *key;
+            match next.next() {
+                Some(end) => match finished.get(end) {
+                    None => {
+                        visitor.tree_edge(&key, end);
+                        visitor.discover(end);
+                        finished.insert(*end, false);
+                        stack.push((*end, self.nodes[end].outgoing.iter()));
+                    }
+                    Some(false) => visitor.back_edge(&key, end),
+                    Some(true) => visitor.cross_edge(&key, end),
+                },
+                None => {
+                    stack.pop();
+                    finished.insert(key, true);
+                    visitor.finish(&key);
+                }
+            }
+        }
+        !visitor.done()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Vec<String>,
+    }
+    impl GraphVisitor<u32> for Recorder {
+        fn discover(&mut self, key: &u32) {
+            self.events.push(format!("discover {}", key));
+        }
+        fn finish(&mut self, key: &u32) {
+            self.events.push(format!("finish {}", key));
+        }
+        fn tree_edge(&mut self, start: &u32, end: &u32) {
+            self.events.push(format!("tree {}-{}", start, end));
+        }
+        fn back_edge(&mut self, start: &u32, end: &u32) {
+            self.events.push(format!("back {}-{}", start, end));
+        }
+    }
+
+    #[test]
+    fn depth_first () {
+        let mut graph = Graph::new();
+        for node in 1..=4 {
+            graph.insert_node(node, node);
+        }
+        graph.add_edge(&1, &2).unwrap();
+        graph.add_edge(&2, &3).unwrap();
+        graph.add_edge(&3, &1).unwrap();
+
+        let mut recorder = Recorder::default();
+        graph.depth_first_from(&1, &mut recorder);
+        assert_eq!(recorder.events, vec![
+            "discover 1", "tree 1-2", "discover 2", "tree 2-3", "discover 3",
+            "back 3-1", "finish 3", "finish 2", "finish 1",
+        ]);
+
+        let mut recorder = Recorder::default();
+        graph.depth_first(&mut recorder);
+        assert_eq!(recorder.events.iter().filter(|event| event.starts_with("finish")).count(), 4);
+        assert_eq!(recorder.events.iter().filter(|event| event.starts_with("back")).count(), 1);
+    }
+}
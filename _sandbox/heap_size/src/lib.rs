@@ -0,0 +1,142 @@
+//! Estimates of how much heap memory a value holds, shared by the
+//! collection crates so a structure from one can report its size inside a
+//! structure from another. Allocations behind an `Rc` or `Arc` are counted
+//! the first time they are reached and skipped after that, so snapshots
+//! measured together only pay for the nodes they share once. Builds without
+//! `std` when the default `std` feature is turned off, leaving out the hash
+//! tables.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeSet;
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::mem::size_of;
+
+/// The addresses of the shared allocations already counted.
+pub type Seen = BTreeSet<*const ()>;
+
+pub trait HeapSize {
+    /// The estimated bytes on the heap owned by `self`, not counting the
+    /// size of `self`.
+    fn heap_size(&self) -> usize {
+        self.heap_size_shared(&mut Seen::new())
+    }
+    /// As `heap_size`, skipping allocations in `seen` and adding the new
+    /// ones to it. Pass the same set to measure several values together.
+    fn heap_size_shared(&self, seen: &mut Seen) -> usize;
+}
+
+macro_rules! no_heap {
+    ($($t:ty),*) => {
+        $(impl HeapSize for $t {
+            fn heap_size_shared(&self, _: &mut Seen) -> usize {
+                0
+            }
+        })*
+    };
+}
+no_heap!((), bool, char, u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64, &str);
+
+impl HeapSize for String {
+    fn heap_size_shared(&self, _: &mut Seen) -> usize {
+        self.capacity()
+    }
+}
+impl<T: HeapSize> HeapSize for Vec<T> {
+    fn heap_size_shared(&self, seen: &mut Seen) -> usize {
+        self.capacity() * size_of::<T>()
+            + self.iter().map(|elem| elem.heap_size_shared(seen)).sum::<usize>()
+    }
+}
+impl<T: HeapSize> HeapSize for Option<T> {
+    fn heap_size_shared(&self, seen: &mut Seen) -> usize {
+        self.as_ref().map_or(0, |value| value.heap_size_shared(seen))
+    }
+}
+impl<A: HeapSize, B: HeapSize> HeapSize for (A, B) {
+    fn heap_size_shared(&self, seen: &mut Seen) -> usize {
+        self.0.heap_size_shared(seen) + self.1.heap_size_shared(seen)
+    }
+}
+impl<T: HeapSize> HeapSize for Box<T> {
+    fn heap_size_shared(&self, seen: &mut Seen) -> usize {
+        size_of::<T>() + (**self).heap_size_shared(seen)
+    }
+}
+impl<T: HeapSize> HeapSize for Rc<T> {
+    fn heap_size_shared(&self, seen: &mut Seen) -> usize {
+        if !seen.insert(Rc::as_ptr(self) as *const ()) {
+            return 0;
+        }
+        rc_allocation::<T>() + (**self).heap_size_shared(seen)
+    }
+}
+impl<T: HeapSize> HeapSize for Arc<T> {
+    fn heap_size_shared(&self, seen: &mut Seen) -> usize {
+        if !seen.insert(Arc::as_ptr(self) as *const ()) {
+            return 0;
+        }
+        arc_allocation::<T>() + (**self).heap_size_shared(seen)
+    }
+}
+/// A slot and a control byte for each entry the table has room for.
+#[cfg(feature = "std")]
+impl<K: HeapSize, V: HeapSize, S> HeapSize for std::collections::HashMap<K, V, S> {
+    fn heap_size_shared(&self, seen: &mut Seen) -> usize {
+        table_allocation::<(K, V)>(self.capacity())
+            + self.iter().map(|(key, value)| key.heap_size_shared(seen) + value.heap_size_shared(seen)).sum::<usize>()
+    }
+}
+#[cfg(feature = "std")]
+impl<T: HeapSize, S> HeapSize for std::collections::HashSet<T, S> {
+    fn heap_size_shared(&self, seen: &mut Seen) -> usize {
+        table_allocation::<T>(self.capacity())
+            + self.iter().map(|elem| elem.heap_size_shared(seen)).sum::<usize>()
+    }
+}
+
+/// An `Rc` allocates its strong and weak counts next to the value.
+pub fn rc_allocation<T>() -> usize {
+    2 * size_of::<usize>() + size_of::<T>()
+}
+/// An `Arc` allocates its counts next to the value, as an `Rc` does.
+pub fn arc_allocation<T>() -> usize {
+    2 * size_of::<usize>() + size_of::<T>()
+}
+/// A hash table with room for `capacity` entries of `T`, not counting what
+/// the entries hold.
+pub fn table_allocation<T>(capacity: usize) -> usize {
+    capacity * (size_of::<T>() + 1)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn owned() {
+        let strings = vec![String::with_capacity(10), String::new()];
+        assert_eq!(strings.heap_size(), 2 * size_of::<String>() + 10);
+        assert_eq!(Box::new(5u64).heap_size(), 8);
+        assert_eq!(Some((1u8, String::with_capacity(3))).heap_size(), 3);
+    }
+
+    #[test]
+    fn shared_counted_once() {
+        let shared = Rc::new(vec![0u32; 4]);
+        let both = vec![shared.clone(), shared];
+        let node = rc_allocation::<Vec<u32>>();
+        assert_eq!(both.heap_size(), 2 * size_of::<Rc<Vec<u32>>>() + node + 16);
+
+        let first = Arc::new(1u32);
+        let mut seen = Seen::new();
+        assert_eq!(first.heap_size_shared(&mut seen), arc_allocation::<u32>());
+        assert_eq!(first.clone().heap_size_shared(&mut seen), 0);
+    }
+}
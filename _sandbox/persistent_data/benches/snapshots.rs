@@ -0,0 +1,84 @@
+//! Snapshot and mutate: keep a copy of the structure, change it a little,
+//! and repeat. The boxed structures and the std collections copy
+//! everything for each snapshot, where the persistent ones copy one path.
+//! The `build` groups do the same changes without snapshots, to show what
+//! persistence costs when nothing is shared.
+use std::collections::BTreeSet;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use persistent_data::rbtree::RbTree;
+use persistent_data::set::PersistentSet;
+use persistent_data::stack::PersistentStack;
+use persistent_data::{List, ListBox, Tree, TreeBox};
+
+const SIZES: [usize; 3] = [100, 1_000, 10_000];
+/// Snapshots taken after the structure is built.
+const SNAPSHOTS: usize = 100;
+
+/// xorshift, so every run sees the same keys. `Tree` does not balance
+/// itself, so its keys must not arrive in order.
+fn keys(count: usize) -> Vec<u64> {
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    (0..count).map(|_| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    }).collect()
+}
+
+/// Builds from `keys`, then inserts `extra` one at a time, keeping a
+/// snapshot before each insert if `snapshot` is set.
+fn run<S, F: Fn(&mut S, u64)>(mut value: S, keys: &[u64], extra: &[u64], snapshot: bool, add: F) -> Vec<S>
+    where S: Clone
+{
+    for &key in keys {
+        add(&mut value, key);
+    }
+    let mut snapshots = Vec::new();
+    for &key in extra {
+        if snapshot {
+            snapshots.push(value.clone());
+        }
+        add(&mut value, key);
+    }
+    snapshots.push(value);
+    snapshots
+}
+
+fn bench_stack<S: PersistentStack<u64>>(c: &mut Criterion, name: &str) {
+    for &size in SIZES.iter() {
+        let keys = keys(size + SNAPSHOTS);
+        let (keys, extra) = keys.split_at(size);
+        for &(group, snapshot) in [("stack_snapshots", true), ("stack_build", false)].iter() {
+            c.benchmark_group(group).bench_with_input(
+                BenchmarkId::new(name, size), &size,
+                |b, _| b.iter(|| run(S::default(), keys, extra, snapshot, S::push)));
+        }
+    }
+}
+
+fn bench_set<S: PersistentSet<u64>>(c: &mut Criterion, name: &str) {
+    for &size in SIZES.iter() {
+        let keys = keys(size + SNAPSHOTS);
+        let (keys, extra) = keys.split_at(size);
+        for &(group, snapshot) in [("set_snapshots", true), ("set_build", false)].iter() {
+            c.benchmark_group(group).bench_with_input(
+                BenchmarkId::new(name, size), &size,
+                |b, _| b.iter(|| run(S::default(), keys, extra, snapshot, S::insert)));
+        }
+    }
+}
+
+fn snapshots(c: &mut Criterion) {
+    bench_stack::<ListBox<u64>>(c, "list_box");
+    bench_stack::<List<u64>>(c, "list");
+    bench_stack::<Vec<u64>>(c, "vec");
+    bench_set::<TreeBox<u64>>(c, "tree_box");
+    bench_set::<Tree<u64>>(c, "tree");
+    bench_set::<RbTree<u64>>(c, "rb_tree");
+    bench_set::<BTreeSet<u64>>(c, "btree_set");
+}
+
+criterion_group!(benches, snapshots);
+criterion_main!(benches);
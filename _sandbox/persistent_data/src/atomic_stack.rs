@@ -0,0 +1,164 @@
+//! A lock-free stack (Treiber's) that threads can push to and pop from at
+//! the same time. It is a cons list of `Arc` nodes, like `sync::List`,
+//! whose head is swapped with compare-and-swap. Nodes are never changed
+//! once they are on the stack, so a pop only has to move the head on to
+//! the next node, and a snapshot is just a copy of the head.
+use std::ptr;
+use std::sync::Arc;
+
+use arc_swap::ArcSwapOption;
+
+#[derive(Debug)]
+struct Node<A> {
+    value: A,
+    next: Option<Arc<Node<A>>>,
+}
+
+#[derive(Debug)]
+pub struct AtomicStack<A> {
+    head: ArcSwapOption<Node<A>>,
+}
+impl<A: Clone> Default for AtomicStack<A> {
+    fn default() -> Self {
+        AtomicStack::new()
+    }
+}
+impl<A: Clone> AtomicStack<A> {
+    pub fn new() -> Self {
+        AtomicStack { head: ArcSwapOption::empty() }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.head.load().is_none()
+    }
+    pub fn push(&self, value: A) {
+        let mut node = Arc::new(Node { value, next: None });
+        loop {
+            let head = self.head.load_full();
+            // the node is only ours until the swap succeeds, so its next
+            // pointer can still be set.
+            Arc::get_mut(&mut node).expect("node shared before it was pushed").next = head.clone();
+            let previous = self.head.compare_and_swap(&head, Some(node.clone()));
+            if address(&previous) == address(&head) {
+                return;
+            }
+        }
+    }
+    /// Removes the top value. It is cloned, as a snapshot may still hold
+    /// the node it was in.
+    pub fn pop(&self) -> Option<A> {
+        loop {
+            let head = self.head.load();
+            let next = head.as_ref()?.next.clone();
+            let previous = self.head.compare_and_swap(&head, next);
+            if address(&previous) == address(&head) {
+                return head.as_ref().map(|node| node.value.clone());
+            }
+        }
+    }
+    pub fn peek(&self) -> Option<A> {
+        self.head.load().as_ref().map(|node| node.value.clone())
+    }
+    /// A separate stack holding the values this one holds now. It shares
+    /// all of their nodes, and neither stack sees the other's changes.
+    pub fn snapshot(&self) -> AtomicStack<A> {
+        AtomicStack { head: ArcSwapOption::new(self.head.load_full()) }
+    }
+    /// The values from the top down, as they were when this was called.
+    pub fn to_vec(&self) -> Vec<A> {
+        let mut values = Vec::new();
+        let mut node = self.head.load_full();
+        while let Some(current) = node {
+            values.push(current.value.clone());
+            node = current.next.clone();
+        }
+        values
+    }
+}
+
+/// Frees the nodes no snapshot shares one at a time, rather than each
+/// dropping the next in turn, which overflows the stack on long ones.
+impl<A> Drop for AtomicStack<A> {
+    fn drop(&mut self) {
+        let mut next = self.head.swap(None);
+        while let Some(node) = next {
+            next = match Arc::try_unwrap(node) {
+                Ok(mut node) => node.next.take(),
+                Err(_) => None,
+            };
+        }
+    }
+}
+
+fn address<A>(head: &Option<Arc<Node<A>>>) -> *const Node<A> {
+    head.as_ref().map_or(ptr::null(), Arc::as_ptr)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn push_pop_snapshot() {
+        let stack = AtomicStack::new();
+        stack.push(1);
+        stack.push(2);
+        let snapshot = stack.snapshot();
+        assert_eq!(stack.pop(), Some(2));
+        stack.push(3);
+
+        assert_eq!(stack.peek(), Some(3));
+        assert_eq!(stack.to_vec(), vec![3, 1]);
+        assert_eq!(snapshot.to_vec(), vec![2, 1]);
+        assert_eq!(stack.pop(), Some(3));
+        assert_eq!(stack.pop(), Some(1));
+        assert_eq!(stack.pop(), None);
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn drop_long_stack() {
+        let stack = AtomicStack::new();
+        for value in 0..1_000_000u32 {
+            stack.push(value);
+        }
+        let snapshot = stack.snapshot();
+        stack.push(1_000_000);
+        drop(stack);
+        assert_eq!(snapshot.peek(), Some(999_999));
+        drop(snapshot);
+    }
+
+    #[test]
+    fn concurrent_push_and_pop() {
+        let stack = Arc::new(AtomicStack::new());
+        let pushers: Vec<_> = (0..4u64).map(|thread| {
+            let stack = stack.clone();
+            thread::spawn(move || {
+                for value in 0..1000 {
+                    stack.push(thread * 1000 + value);
+                }
+            })
+        }).collect();
+        let poppers: Vec<_> = (0..2).map(|_| {
+            let stack = stack.clone();
+            thread::spawn(move || {
+                let mut popped = Vec::new();
+                while popped.len() < 1000 {
+                    popped.extend(stack.pop());
+                }
+                popped
+            })
+        }).collect();
+        for pusher in pushers {
+            pusher.join().unwrap();
+        }
+
+        let mut values: Vec<u64> = poppers.into_iter()
+            .flat_map(|popper| popper.join().unwrap())
+            .collect();
+        values.extend(stack.to_vec());
+        values.sort_unstable();
+        assert_eq!(values, (0..4000).collect::<Vec<u64>>());
+    }
+}
@@ -0,0 +1,128 @@
+//! Counts clones, so tests can check how much of a structure is shared
+//! rather than copied.
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::Arc;
+
+/// A tally of clones, shared by every value it wraps.
+#[derive(Debug, Clone, Default)]
+pub struct Counter(Arc<AtomicUsize>);
+impl Counter {
+    pub fn new() -> Self {
+        Counter::default()
+    }
+    /// The number of clones made so far of values wrapped by this counter.
+    pub fn count(&self) -> usize {
+        self.0.load(AtomicOrdering::SeqCst)
+    }
+    pub fn reset(&self) {
+        self.0.store(0, AtomicOrdering::SeqCst);
+    }
+    pub fn wrap<T>(&self, value: T) -> CloneCounter<T> {
+        CloneCounter {
+            value,
+            counter: self.clone(),
+        }
+    }
+}
+
+/// A value that adds to its `Counter` each time it is cloned. Comparisons
+/// and hashing only look at the value.
+#[derive(Debug)]
+pub struct CloneCounter<T> {
+    value: T,
+    counter: Counter,
+}
+impl<T> CloneCounter<T> {
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+impl<T: Clone> Clone for CloneCounter<T> {
+    fn clone(&self) -> Self {
+        self.counter.0.fetch_add(1, AtomicOrdering::SeqCst);
+        self.counter.wrap(self.value.clone())
+    }
+}
+impl<T> Deref for CloneCounter<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+impl<T: PartialEq> PartialEq for CloneCounter<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+impl<T: Eq> Eq for CloneCounter<T> {}
+impl<T: PartialOrd> PartialOrd for CloneCounter<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+impl<T: Ord> Ord for CloneCounter<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.value.cmp(&other.value)
+    }
+}
+impl<T: Hash> Hash for CloneCounter<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.hash(state);
+    }
+}
+
+/// Evaluates `$body`, asserting that it made exactly `$expected` clones of
+/// values wrapped by `$counter`, and returns the body's value.
+#[macro_export]
+macro_rules! assert_clones {
+    ($counter:expr, $expected:expr, $body:expr) => {{
+        let before = $counter.count();
+        let result = $body;
+        let clones = $counter.count() - before;
+        assert_eq!(clones, $expected, "expected {} clones but {} were made", $expected, clones);
+        result
+    }};
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{List, Tree, TreeBox};
+
+    #[test]
+    fn counts_clones() {
+        let counter = Counter::new();
+        let value = counter.wrap(5);
+        let copy = assert_clones!(counter, 1, value.clone());
+        assert_eq!(*copy, 5);
+        assert_eq!(copy, value);
+        counter.reset();
+        assert_eq!(counter.count(), 0);
+    }
+
+    #[test]
+    fn persistent_structures_share() {
+        let counter = Counter::new();
+        let mut boxed = TreeBox::new();
+        let mut tree = Tree::new();
+        let mut list = List::new();
+        for value in [5, 3, 8, 1, 4, 7, 9].iter() {
+            boxed.insert(counter.wrap(*value));
+            tree.insert(counter.wrap(*value));
+            list.cons(counter.wrap(*value));
+        }
+
+        let _boxed = assert_clones!(counter, 7, boxed.clone());
+        // only the root value is copied, the rest is shared.
+        let _tree_snapshot = assert_clones!(counter, 1, tree.clone());
+        let _list_snapshot = assert_clones!(counter, 1, list.clone());
+
+        // the snapshot still holds the nodes below the root, so inserting 6
+        // copies the two on its path, holding 8 and 7.
+        assert_clones!(counter, 2, tree.insert(counter.wrap(6)));
+        assert_clones!(counter, 1, list.uncons());
+    }
+}
@@ -0,0 +1,48 @@
+//! Ending an iterator pipeline in a persistent structure without naming
+//! the type, as in `values.iter().map(f).collect_ptree()`.
+use crate::{List, Tree};
+
+pub trait PersistentCollect: Iterator + Sized {
+    /// A `List` of the items in the iterator's order.
+    fn collect_plist(self) -> List<Self::Item>
+        where Self::Item: Clone
+    {
+        self.collect()
+    }
+    /// A balanced `Tree` of the items, built in one pass over them sorted
+    /// rather than by inserting each one.
+    fn collect_ptree(self) -> Tree<Self::Item>
+        where Self::Item: Ord + Clone
+    {
+        self.collect()
+    }
+}
+impl<I: Iterator> PersistentCollect for I {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn depth<A>(tree: &Tree<A>) -> usize {
+        match *tree {
+            Tree::Leaf => 0,
+            Tree::Node(ref left, _, ref right) => 1 + depth(left).max(depth(right)),
+        }
+    }
+
+    #[test]
+    fn collect_balanced() {
+        // inserting in order would make a tree 1000 deep.
+        let tree = (0..1000u32).collect_ptree();
+        assert_eq!(depth(&tree), 10);
+        assert!(tree.iter().copied().eq(0..1000));
+
+        let tree = vec![3, 1, 3, 2, 1].into_iter().collect_ptree();
+        assert_eq!(tree.iter().collect::<Vec<_>>(), vec![&1, &2, &3]);
+        assert_eq!(depth(&Tree::<u32>::new()), 0);
+
+        let list = (1..4u32).map(|elem| elem * 2).collect_plist();
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.head(), Some(&2));
+    }
+}
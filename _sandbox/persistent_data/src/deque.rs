@@ -0,0 +1,334 @@
+//! A persistent double ended queue, stored as a 2-3 finger tree. Values
+//! at either end sit in short "digits" that can be reached in O(1), while
+//! the middle of the queue is a finger tree of 2-3 nodes one level down.
+//!
+//! Rust can not monomorphise the usual nested `FingerTree<Node<A>>`, so
+//! the depth is tracked by the shape of the data instead: values are only
+//! found at the top level, and every level below holds `Item::Node`s.
+use std::rc::Rc;
+
+#[derive(Debug, Clone)]
+enum Item<A> {
+    Value(A),
+    /// Two or three items from the level above.
+    Node(Rc<Vec<Item<A>>>),
+}
+impl<A> Item<A> {
+    fn value(&self) -> &A {
+        match *self {
+            Item::Value(ref value) => value,
+            Item::Node(_) => panic!("Expected a value, found a node"),
+        }
+    }
+    fn into_value(self) -> A {
+        match self {
+            Item::Value(value) => value,
+            Item::Node(_) => panic!("Expected a value, found a node"),
+        }
+    }
+    fn for_each<F: FnMut(&A)>(&self, func: &mut F) {
+        match *self {
+            Item::Value(ref value) => func(value),
+            Item::Node(ref items) => items.iter().for_each(|item| item.for_each(func)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+enum FingerTree<A> {
+    #[default]
+    Empty,
+    Single(Item<A>),
+    /// A prefix and suffix of one to four items each, around a tree of
+    /// nodes.
+    Deep(Vec<Item<A>>, Rc<FingerTree<A>>, Vec<Item<A>>),
+}
+impl<A: Clone> FingerTree<A> {
+    fn from_items(items: Vec<Item<A>>) -> Self {
+        let mut tree = FingerTree::Empty;
+        for item in items {
+            tree.push_back(item);
+        }
+        tree
+    }
+    fn push_front(&mut self, item: Item<A>) {
+        match std::mem::take(self) {
+            FingerTree::Empty => *self = FingerTree::Single(item),
+            FingerTree::Single(other) => {
+                *self = FingerTree::Deep(vec![item], Rc::new(FingerTree::Empty), vec![other]);
+            }
+            FingerTree::Deep(mut prefix, mut middle, suffix) => {
+                if prefix.len() == 4 {
+                    let node = prefix.drain(1..).collect();
+                    Rc::make_mut(&mut middle).push_front(Item::Node(Rc::new(node)));
+                }
+                prefix.insert(0, item);
+                *self = FingerTree::Deep(prefix, middle, suffix);
+            }
+        }
+    }
+    fn push_back(&mut self, item: Item<A>) {
+        match std::mem::take(self) {
+            FingerTree::Empty => *self = FingerTree::Single(item),
+            FingerTree::Single(other) => {
+                *self = FingerTree::Deep(vec![other], Rc::new(FingerTree::Empty), vec![item]);
+            }
+            FingerTree::Deep(prefix, mut middle, mut suffix) => {
+                if suffix.len() == 4 {
+                    let node = suffix.drain(..3).collect();
+                    Rc::make_mut(&mut middle).push_back(Item::Node(Rc::new(node)));
+                }
+                suffix.push(item);
+                *self = FingerTree::Deep(prefix, middle, suffix);
+            }
+        }
+    }
+    fn pop_front(&mut self) -> Option<Item<A>> {
+        match std::mem::take(self) {
+            FingerTree::Empty => None,
+            FingerTree::Single(item) => Some(item),
+            FingerTree::Deep(mut prefix, mut middle, suffix) => {
+                let item = prefix.remove(0);
+                if !prefix.is_empty() {
+                    *self = FingerTree::Deep(prefix, middle, suffix);
+                }
+                else {
+                    match Rc::make_mut(&mut middle).pop_front() {
+                        Some(Item::Node(node)) => {
+                            *self = FingerTree::Deep(Rc::unwrap_or_clone(node), middle, suffix);
+                        }
+                        Some(Item::Value(_)) => panic!("Found a value below the top level"),
+                        None => *self = FingerTree::from_items(suffix),
+                    }
+                }
+                Some(item)
+            }
+        }
+    }
+    fn pop_back(&mut self) -> Option<Item<A>> {
+        match std::mem::take(self) {
+            FingerTree::Empty => None,
+            FingerTree::Single(item) => Some(item),
+            FingerTree::Deep(prefix, mut middle, mut suffix) => {
+                let item = suffix.pop().expect("Digits are never empty");
+                if !suffix.is_empty() {
+                    *self = FingerTree::Deep(prefix, middle, suffix);
+                }
+                else {
+                    match Rc::make_mut(&mut middle).pop_back() {
+                        Some(Item::Node(node)) => {
+                            *self = FingerTree::Deep(prefix, middle, Rc::unwrap_or_clone(node));
+                        }
+                        Some(Item::Value(_)) => panic!("Found a value below the top level"),
+                        None => *self = FingerTree::from_items(prefix),
+                    }
+                }
+                Some(item)
+            }
+        }
+    }
+    fn front(&self) -> Option<&Item<A>> {
+        match *self {
+            FingerTree::Empty => None,
+            FingerTree::Single(ref item) => Some(item),
+            FingerTree::Deep(ref prefix, _, _) => prefix.first(),
+        }
+    }
+    fn back(&self) -> Option<&Item<A>> {
+        match *self {
+            FingerTree::Empty => None,
+            FingerTree::Single(ref item) => Some(item),
+            FingerTree::Deep(_, _, ref suffix) => suffix.last(),
+        }
+    }
+    /// Joins `left`, then `items`, then `right`. The items between two deep
+    /// trees are grouped into nodes and pushed into the joined middle, so
+    /// this recurses once per level: O(log n) overall.
+    fn concat(left: Self, items: Vec<Item<A>>, right: Self) -> Self {
+        match (left, right) {
+            (FingerTree::Empty, mut right) => {
+                items.into_iter().rev().for_each(|item| right.push_front(item));
+                right
+            }
+            (mut left, FingerTree::Empty) => {
+                items.into_iter().for_each(|item| left.push_back(item));
+                left
+            }
+            (FingerTree::Single(first), mut right) => {
+                items.into_iter().rev().for_each(|item| right.push_front(item));
+                right.push_front(first);
+                right
+            }
+            (mut left, FingerTree::Single(last)) => {
+                items.into_iter().for_each(|item| left.push_back(item));
+                left.push_back(last);
+                left
+            }
+            (FingerTree::Deep(prefix, left, mut between), FingerTree::Deep(right_prefix, right, suffix)) => {
+                between.extend(items);
+                between.extend(right_prefix);
+                let middle = FingerTree::concat(
+                    Rc::unwrap_or_clone(left), nodes(between), Rc::unwrap_or_clone(right));
+                FingerTree::Deep(prefix, Rc::new(middle), suffix)
+            }
+        }
+    }
+    fn for_each<F: FnMut(&A)>(&self, func: &mut F) {
+        match *self {
+            FingerTree::Empty => (),
+            FingerTree::Single(ref item) => item.for_each(func),
+            FingerTree::Deep(ref prefix, ref middle, ref suffix) => {
+                prefix.iter().for_each(|item| item.for_each(func));
+                middle.for_each(func);
+                suffix.iter().for_each(|item| item.for_each(func));
+            }
+        }
+    }
+}
+
+/// Groups at least two items into nodes of two or three.
+fn nodes<A>(items: Vec<Item<A>>) -> Vec<Item<A>> {
+    let mut remaining = items.len();
+    let mut items = items.into_iter();
+    let mut nodes = Vec::new();
+    while remaining > 0 {
+        let size = match remaining {
+            2 | 4 => 2,
+            _ => 3,
+        };
+        nodes.push(Item::Node(Rc::new(items.by_ref().take(size).collect())));
+        remaining -= size;
+    }
+    nodes
+}
+
+/// A persistent deque with amortised O(1) access at both ends and
+/// O(log n) concatenation. Clones share their structure like `List`.
+#[derive(Debug, Clone)]
+pub struct PDeque<A> {
+    tree: FingerTree<A>,
+    len: usize,
+}
+impl<A: Clone> Default for PDeque<A> {
+    fn default() -> Self {
+        PDeque::new()
+    }
+}
+impl<A: Clone> PDeque<A> {
+    pub fn new() -> Self {
+        PDeque {
+            tree: FingerTree::Empty,
+            len: 0,
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    pub fn push_front(&mut self, elem: A) {
+        self.tree.push_front(Item::Value(elem));
+        self.len += 1;
+    }
+    pub fn push_back(&mut self, elem: A) {
+        self.tree.push_back(Item::Value(elem));
+        self.len += 1;
+    }
+    pub fn pop_front(&mut self) -> Option<A> {
+        let elem = self.tree.pop_front()?.into_value();
+        self.len -= 1;
+        Some(elem)
+    }
+    pub fn pop_back(&mut self) -> Option<A> {
+        let elem = self.tree.pop_back()?.into_value();
+        self.len -= 1;
+        Some(elem)
+    }
+    pub fn front(&self) -> Option<&A> {
+        self.tree.front().map(Item::value)
+    }
+    pub fn back(&self) -> Option<&A> {
+        self.tree.back().map(Item::value)
+    }
+    /// Puts the elements of `other` after those of this deque.
+    pub fn append(&mut self, other: &PDeque<A>) {
+        let left = std::mem::take(&mut self.tree);
+        self.tree = FingerTree::concat(left, Vec::new(), other.tree.clone());
+        self.len += other.len;
+    }
+    /// Calls `func` on each element from front to back.
+    pub fn for_each<F: FnMut(&A)>(&self, func: &mut F) {
+        self.tree.for_each(func);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::VecDeque;
+
+    fn to_vec(deque: &PDeque<u32>) -> Vec<u32> {
+        let mut elems = Vec::new();
+        deque.for_each(&mut |&elem| elems.push(elem));
+        elems
+    }
+
+    #[test]
+    fn matches_vec_deque() {
+        let mut deque = PDeque::new();
+        let mut expected = VecDeque::new();
+        let mut snapshots = Vec::new();
+
+        // a small linear congruential generator keeps the test deterministic.
+        let mut seed: u32 = 4321;
+        for step in 0..5000 {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            match (seed >> 16) % 6 {
+                0 | 1 => {
+                    deque.push_front(step);
+                    expected.push_front(step);
+                }
+                2 | 3 => {
+                    deque.push_back(step);
+                    expected.push_back(step);
+                }
+                4 => assert_eq!(deque.pop_front(), expected.pop_front()),
+                _ => assert_eq!(deque.pop_back(), expected.pop_back()),
+            }
+            assert_eq!(deque.front(), expected.front());
+            assert_eq!(deque.back(), expected.back());
+            assert_eq!(deque.len(), expected.len());
+            if step % 1000 == 0 {
+                snapshots.push((deque.clone(), expected.clone()));
+            }
+        }
+        for (deque, expected) in snapshots {
+            assert_eq!(to_vec(&deque), expected.into_iter().collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn append() {
+        for &(left_len, right_len) in &[(0, 5), (5, 0), (1, 7), (7, 1), (30, 45), (200, 3)] {
+            let mut left = PDeque::new();
+            (0..left_len).for_each(|elem| left.push_back(elem));
+            let mut right = PDeque::new();
+            (0..right_len).for_each(|elem| right.push_front(1000 - elem));
+
+            let original = left.clone();
+            left.append(&right);
+            let expected: Vec<u32> = (0..left_len).chain(1001 - right_len..1001).collect();
+            assert_eq!(to_vec(&left), expected);
+            assert_eq!(left.len(), expected.len());
+            assert_eq!(to_vec(&original).len(), left_len as usize);
+
+            let mut popped = Vec::new();
+            while let Some(elem) = left.pop_back() {
+                popped.push(elem);
+            }
+            popped.reverse();
+            assert_eq!(popped, expected);
+        }
+    }
+}
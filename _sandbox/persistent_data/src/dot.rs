@@ -0,0 +1,80 @@
+//! Graphviz output showing what two snapshots of a `Tree` share. Each node
+//! is drawn once however many snapshots reach it, and nodes that `after`
+//! does not share with `before` are filled in, so the path copied by an
+//! update stands out. Render the output with `dot -Tsvg`.
+use std::collections::HashSet;
+use std::fmt::{Display, Write};
+
+use crate::Tree;
+
+/// The two snapshots as a DOT digraph, with `after`'s copied nodes filled.
+pub fn snapshots<A: Ord + Clone + Display>(before: &Tree<A>, after: &Tree<A>) -> String {
+    let mut old_nodes = HashSet::new();
+    before.collect_nodes(&mut old_nodes);
+
+    let mut dot = String::from("digraph {\n    node [shape=circle];\n");
+    let mut drawn = HashSet::new();
+    for (name, tree) in [("before", before), ("after", after)].iter() {
+        writeln!(dot, "    {} [shape=plaintext];", name).unwrap();
+        if let Tree::Node(_, _, _) = **tree {
+            writeln!(dot, "    {} -> {};", name, id(tree)).unwrap();
+        }
+        draw(tree, &old_nodes, &mut drawn, &mut dot);
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+fn draw<A: Display>(
+    tree: &Tree<A>,
+    old_nodes: &HashSet<*const Tree<A>>,
+    drawn: &mut HashSet<*const Tree<A>>,
+    dot: &mut String,
+) {
+    if let Tree::Node(ref left, ref value, ref right) = *tree {
+        if !drawn.insert(tree as *const Tree<A>) {
+            return;
+        }
+        let style = if old_nodes.contains(&(tree as *const Tree<A>)) {
+            ""
+        }
+        else {
+            ", style=filled, fillcolor=lightblue"
+        };
+        writeln!(dot, "    {} [label=\"{}\"{}];", id(tree), value, style).unwrap();
+        for child in [left, right].iter() {
+            if let Tree::Node(_, _, _) = ***child {
+                writeln!(dot, "    {} -> {};", id(tree), id(child)).unwrap();
+                draw(child, old_nodes, drawn, dot);
+            }
+        }
+    }
+}
+
+fn id<A>(tree: &Tree<A>) -> String {
+    format!("n{:p}", tree)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn copied_path_is_filled() {
+        let before: Tree<u32> = vec![50, 30, 70, 20, 40, 60, 80].into_iter().collect();
+        let mut after = before.clone();
+        after.insert(65);
+
+        let dot = snapshots(&before, &after);
+        assert!(dot.starts_with("digraph {"));
+        // 7 nodes in `before`, then copies of 50, 70 and 60, and the new 65.
+        assert_eq!(dot.matches("label=").count(), 11);
+        assert_eq!(dot.matches("filled").count(), 4);
+        for value in &["50", "70", "60", "65"] {
+            assert!(dot.contains(&format!("label=\"{}\", style=filled", value)));
+        }
+        // each snapshot's root edge, 6 edges within `before`, and 5 from the
+        // copied nodes.
+        assert_eq!(dot.matches("->").count(), 2 + 6 + 5);
+    }
+}
@@ -0,0 +1,264 @@
+//! A persistent hash map stored as a hash array mapped trie. Each level of
+//! the trie uses five more bits of the key's hash to pick a child, and
+//! branches only store the children that exist, found through a bitmap.
+//! As with `Tree`, cloning a map is cheap and modifying it copies only the
+//! nodes on the path to the change.
+use std::borrow::Borrow;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+
+const BITS: u32 = 5;
+const MASK: u64 = (1 << BITS) - 1;
+
+#[derive(Debug, Clone)]
+enum Node<K, V> {
+    /// `children[i]` holds the `i`th set bit of `bitmap`.
+    Branch(u32, Vec<Rc<Node<K, V>>>),
+    /// Entries whose keys all have the same full hash.
+    Leaf(u64, Vec<(K, V)>),
+}
+
+#[derive(Debug, Clone)]
+pub struct PHashMap<K, V> {
+    root: Rc<Node<K, V>>,
+    len: usize,
+}
+impl<K: Hash + Eq + Clone, V: Clone> Default for PHashMap<K, V> {
+    fn default() -> Self {
+        PHashMap::new()
+    }
+}
+impl<K: Hash + Eq + Clone, V: Clone> PHashMap<K, V> {
+    pub fn new() -> Self {
+        PHashMap {
+            root: Rc::new(Node::Branch(0, Vec::new())),
+            len: 0,
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    pub fn get<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<&V>
+        where K: Borrow<Q>
+    {
+        let hash = hash(key);
+        let mut node = &*self.root;
+        let mut shift = 0;
+        loop {
+            match *node {
+                Node::Branch(bitmap, ref children) => {
+                    let bit = bit(hash, shift);
+                    if bitmap & bit == 0 {
+                        return None;
+                    }
+                    node = &children[index(bitmap, bit)];
+                    shift += BITS;
+                }
+                Node::Leaf(leaf_hash, ref entries) => {
+                    if leaf_hash != hash {
+                        return None;
+                    }
+                    return entries.iter()
+                        .find(|(stored, _)| stored.borrow() == key)
+                        .map(|(_, value)| value);
+                }
+            }
+        }
+    }
+    pub fn contains_key<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> bool
+        where K: Borrow<Q>
+    {
+        self.get(key).is_some()
+    }
+    /// Inserts `value` under `key`, returning the value it replaced.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let hash = hash(&key);
+        let old = Rc::make_mut(&mut self.root).insert(hash, 0, key, value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+    /// Removes `key`, returning its value. Nothing is copied if the key is
+    /// not there.
+    pub fn remove<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<V>
+        where K: Borrow<Q>
+    {
+        if !self.contains_key(key) {
+            return None;
+        }
+        let hash = hash(key);
+        let removed = Rc::make_mut(&mut self.root).remove(hash, 0, key);
+        self.len -= 1;
+        Some(removed)
+    }
+    /// Calls `func` on every entry, in no particular order.
+    pub fn for_each<F: FnMut(&K, &V)>(&self, func: &mut F) {
+        self.root.for_each(func);
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> Node<K, V> {
+    fn insert(&mut self, hash: u64, shift: u32, key: K, value: V) -> Option<V> {
+        match *self {
+            Node::Branch(ref mut bitmap, ref mut children) => {
+                let bit = bit(hash, shift);
+                let index = index(*bitmap, bit);
+                if *bitmap & bit == 0 {
+                    *bitmap |= bit;
+                    children.insert(index, Rc::new(Node::Leaf(hash, vec![(key, value)])));
+                    return None;
+                }
+                let child = Rc::make_mut(&mut children[index]);
+                if let Node::Leaf(leaf_hash, _) = *child {
+                    if leaf_hash != hash {
+                        // two different hashes share a prefix, so push the
+                        // existing leaf down a level to tell them apart.
+                        let leaf = std::mem::replace(child, Node::Branch(0, Vec::new()));
+                        let leaf_bit = self::bit(leaf_hash, shift + BITS);
+                        *child = Node::Branch(leaf_bit, vec![Rc::new(leaf)]);
+                    }
+                }
+                child.insert(hash, shift + BITS, key, value)
+            }
+            Node::Leaf(_, ref mut entries) => {
+                match entries.iter_mut().find(|(stored, _)| *stored == key) {
+                    Some((_, old)) => Some(std::mem::replace(old, value)),
+                    None => {
+                        entries.push((key, value));
+                        None
+                    }
+                }
+            }
+        }
+    }
+    /// Removes a key that is known to be present.
+    fn remove<Q: Hash + Eq + ?Sized>(&mut self, hash: u64, shift: u32, key: &Q) -> V
+        where K: Borrow<Q>
+    {
+        match *self {
+            Node::Branch(ref mut bitmap, ref mut children) => {
+                let bit = bit(hash, shift);
+                let index = index(*bitmap, bit);
+                let child = Rc::make_mut(&mut children[index]);
+                let removed = child.remove(hash, shift + BITS, key);
+                match *child {
+                    Node::Leaf(_, ref entries) if entries.is_empty() => {
+                        *bitmap &= !bit;
+                        children.remove(index);
+                    }
+                    // a branch left holding a single leaf is replaced by
+                    // the leaf, keeping the trie as shallow as it can be.
+                    Node::Branch(_, ref grandchildren)
+                        if grandchildren.len() == 1 && grandchildren[0].is_leaf() =>
+                    {
+                        children[index] = grandchildren[0].clone();
+                    }
+                    _ => (),
+                }
+                removed
+            }
+            Node::Leaf(_, ref mut entries) => {
+                let position = entries.iter()
+                    .position(|(stored, _)| stored.borrow() == key)
+                    .expect("Tried to remove a key that is not in the map");
+                entries.swap_remove(position).1
+            }
+        }
+    }
+    fn is_leaf(&self) -> bool {
+        match *self {
+            Node::Leaf(_, _) => true,
+            Node::Branch(_, _) => false,
+        }
+    }
+    fn for_each<F: FnMut(&K, &V)>(&self, func: &mut F) {
+        match *self {
+            Node::Branch(_, ref children) => {
+                for child in children {
+                    child.for_each(func);
+                }
+            }
+            Node::Leaf(_, ref entries) => {
+                for (key, value) in entries {
+                    func(key, value);
+                }
+            }
+        }
+    }
+}
+
+fn hash<Q: Hash + ?Sized>(key: &Q) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+/// The bit of a branch's bitmap that `hash` selects at depth `shift`.
+fn bit(hash: u64, shift: u32) -> u32 {
+    1 << ((hash >> shift) & MASK)
+}
+/// The position in a branch's children of the child selected by `bit`.
+fn index(bitmap: u32, bit: u32) -> usize {
+    (bitmap & (bit - 1)).count_ones() as usize
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn insert_get_remove() {
+        let mut map = PHashMap::new();
+        for key in 0..2000u32 {
+            assert_eq!(map.insert(key, key * 2), None);
+        }
+        let snapshot = map.clone();
+        assert_eq!(map.insert(7, 0), Some(14));
+        for key in 0..1000 {
+            assert_eq!(map.remove(&key), Some(if key == 7 { 0 } else { key * 2 }));
+        }
+        assert_eq!(map.remove(&0), None);
+
+        assert_eq!(map.len(), 1000);
+        assert_eq!(snapshot.len(), 2000);
+        assert!((0..1000).all(|key| map.get(&key).is_none()));
+        assert!((1000..2000).all(|key| map.get(&key) == Some(&(key * 2))));
+        assert!((0..2000).all(|key| snapshot.get(&key) == Some(&(key * 2))));
+
+        let mut sum = 0;
+        map.for_each(&mut |_, &value| sum += value);
+        assert_eq!(sum, (1000..2000).map(|key| key * 2).sum::<u32>());
+    }
+
+    /// Every value hashes the same, to exercise the collision buckets.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Collide(u32);
+    impl Hash for Collide {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            0.hash(state);
+        }
+    }
+
+    #[test]
+    fn collisions() {
+        let mut map = PHashMap::new();
+        for key in 0..10 {
+            map.insert(Collide(key), key);
+        }
+        assert_eq!(map.get(&Collide(3)), Some(&3));
+        assert_eq!(map.remove(&Collide(3)), Some(3));
+        assert_eq!(map.get(&Collide(3)), None);
+        assert_eq!(map.get(&Collide(9)), Some(&9));
+        assert_eq!(map.len(), 9);
+
+        let mut map = PHashMap::new();
+        map.insert("key".to_string(), 1);
+        assert_eq!(map.get("key"), Some(&1));
+        assert_eq!(map.remove("key"), Some(1));
+        assert!(map.is_empty());
+    }
+}
@@ -0,0 +1,98 @@
+//! `HeapSize` for the lists and trees, from the `heap_size` crate the
+//! other collection crates share. Nodes behind an `Rc` are counted the
+//! first time they are reached and skipped after that, so several
+//! snapshots measured together only pay for the nodes they share once.
+use std::mem::size_of;
+
+pub use heap_size::{HeapSize, Seen};
+use heap_size::rc_allocation;
+
+use crate::{List, ListBox, Tree, TreeBox};
+
+// the lists are walked in a loop rather than through the `Box` and `Rc`
+// impls, so long lists do not overflow the stack.
+impl<A: HeapSize> HeapSize for ListBox<A> {
+    fn heap_size_shared(&self, seen: &mut Seen) -> usize {
+        let mut size = 0;
+        let mut list = self;
+        while let ListBox::Cons(ref elem, ref tail) = *list {
+            size += elem.heap_size_shared(seen) + size_of::<ListBox<A>>();
+            list = tail;
+        }
+        size
+    }
+}
+impl<A: HeapSize> HeapSize for List<A> {
+    fn heap_size_shared(&self, seen: &mut Seen) -> usize {
+        let mut size = 0;
+        let mut list = self;
+        while let List::Cons(ref elem, ref tail, _) = *list {
+            size += elem.heap_size_shared(seen);
+            if !seen.insert(tail.as_ptr() as *const ()) {
+                break;
+            }
+            size += rc_allocation::<List<A>>();
+            list = tail;
+        }
+        size
+    }
+}
+impl<A: HeapSize> HeapSize for TreeBox<A> {
+    fn heap_size_shared(&self, seen: &mut Seen) -> usize {
+        match *self {
+            TreeBox::Leaf => 0,
+            TreeBox::Node(ref left, ref value, ref right) =>
+                left.heap_size_shared(seen) + value.heap_size_shared(seen) + right.heap_size_shared(seen),
+        }
+    }
+}
+impl<A: HeapSize> HeapSize for Tree<A> {
+    fn heap_size_shared(&self, seen: &mut Seen) -> usize {
+        match *self {
+            Tree::Leaf => 0,
+            Tree::Node(ref left, ref value, ref right) =>
+                left.heap_size_shared(seen) + value.heap_size_shared(seen) + right.heap_size_shared(seen),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn shared_nodes_counted_once() {
+        let node = rc_allocation::<List<u32>>();
+        let list: List<u32> = (0..10).collect();
+        // the empty list at the end is a node too.
+        assert_eq!(list.heap_size(), 10 * node);
+
+        let mut longer = list.clone();
+        longer.cons(10);
+        let mut seen = Seen::new();
+        let both = list.heap_size_shared(&mut seen) + longer.heap_size_shared(&mut seen);
+        assert_eq!(both, 11 * node);
+
+        let strings: List<String> = vec![String::with_capacity(100)].into_iter().collect();
+        assert_eq!(strings.heap_size(), 100 + rc_allocation::<List<String>>());
+        let long: List<u8> = (0..10_000).map(|elem| elem as u8).collect();
+        assert!(long.heap_size() > 0);
+    }
+
+    #[test]
+    fn tree_snapshots() {
+        let tree: Tree<u32> = (0..100).map(|value| (value * 37) % 100).collect();
+        let node = rc_allocation::<Tree<u32>>();
+        // every node holds two `Rc`s, to a node or a leaf of its own.
+        assert_eq!(tree.heap_size(), (2 * 100) * node);
+
+        let mut changed = tree.clone();
+        changed.insert(1000);
+        let mut seen = Seen::new();
+        tree.heap_size_shared(&mut seen);
+        assert!(changed.heap_size_shared(&mut seen) < 20 * node);
+
+        let boxed: TreeBox<u32> = (0..10).collect();
+        assert_eq!(boxed.heap_size(), 20 * size_of::<TreeBox<u32>>());
+    }
+}
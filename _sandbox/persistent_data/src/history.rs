@@ -0,0 +1,163 @@
+//! Undo and redo for any value that is cheap to clone. With the persistent
+//! structures a clone only copies the root, so keeping every committed
+//! version costs little more than the nodes that actually changed.
+
+/// A working value together with the versions committed so far.
+#[derive(Debug, Clone)]
+pub struct History<T> {
+    working: T,
+    /// Committed versions, oldest first, each with an optional label.
+    versions: Vec<(T, Option<String>)>,
+    /// The index of the version the working value was last set from.
+    position: usize,
+    /// Whether the working value may have changed since then.
+    dirty: bool,
+}
+impl<T: Clone> History<T> {
+    /// Starts a history whose first version is `value`.
+    pub fn new(value: T) -> Self {
+        History {
+            working: value.clone(),
+            versions: vec![(value, None)],
+            position: 0,
+            dirty: false,
+        }
+    }
+    pub fn get(&self) -> &T {
+        &self.working
+    }
+    /// The working value. Changes are kept by `commit` and lost by `undo`
+    /// or `redo`. Once this has been called the working value counts as
+    /// changed, whether or not it was.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.dirty = true;
+        &mut self.working
+    }
+    /// Records the working value as a new version. Any versions that had
+    /// been undone can no longer be redone.
+    pub fn commit(&mut self) {
+        self.push(None);
+    }
+    /// Commits the working value under `label`, so it can be returned to
+    /// with `restore`.
+    pub fn checkpoint(&mut self, label: &str) {
+        self.push(Some(label.to_string()));
+    }
+    /// Goes back to the previous version, returning whether there was one.
+    /// If the working value has changed since the last commit, it only goes
+    /// back to that commit.
+    pub fn undo(&mut self) -> bool {
+        if self.dirty {
+            self.reset();
+            return true;
+        }
+        if self.position == 0 {
+            return false;
+        }
+        self.position -= 1;
+        self.reset();
+        true
+    }
+    /// Goes forward to the version last undone, returning whether there was
+    /// one.
+    pub fn redo(&mut self) -> bool {
+        if self.position + 1 == self.versions.len() {
+            return false;
+        }
+        self.position += 1;
+        self.reset();
+        true
+    }
+    /// Goes to the latest version checkpointed as `label`, as if by undoing
+    /// or redoing to it. Returns whether there was one.
+    pub fn restore(&mut self, label: &str) -> bool {
+        let found = self.versions.iter()
+            .rposition(|(_, version)| version.as_deref() == Some(label));
+        match found {
+            Some(position) => {
+                self.position = position;
+                self.reset();
+                true
+            }
+            None => false,
+        }
+    }
+    /// Labels of the checkpoints that can currently be restored, oldest first.
+    pub fn checkpoints(&self) -> Vec<&str> {
+        self.versions.iter().filter_map(|(_, label)| label.as_deref()).collect()
+    }
+
+    fn push(&mut self, label: Option<String>) {
+        self.versions.truncate(self.position + 1);
+        self.versions.push((self.working.clone(), label));
+        self.position += 1;
+        self.dirty = false;
+    }
+    fn reset(&mut self) {
+        self.working = self.versions[self.position].0.clone();
+        self.dirty = false;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Tree;
+
+    fn values(history: &History<Tree<u32>>) -> Vec<u32> {
+        let mut values = Vec::new();
+        history.get().for_each(&mut |&value| values.push(value));
+        values
+    }
+
+    #[test]
+    fn undo_and_redo() {
+        let mut history = History::new(Tree::new());
+        history.get_mut().insert(1);
+        history.commit();
+        history.get_mut().insert(2);
+        history.commit();
+        history.get_mut().insert(3);
+
+        // the first undo only drops the uncommitted 3.
+        assert!(history.undo());
+        assert_eq!(values(&history), vec![1, 2]);
+        assert!(history.undo());
+        assert_eq!(values(&history), vec![1]);
+        assert!(history.undo());
+        assert!(!history.undo());
+        assert_eq!(values(&history), Vec::<u32>::new());
+        assert!(history.redo());
+        assert!(history.redo());
+        assert!(!history.redo());
+        assert_eq!(values(&history), vec![1, 2]);
+
+        history.undo();
+        history.get_mut().insert(4);
+        history.commit();
+        assert!(!history.redo());
+        assert_eq!(values(&history), vec![1, 4]);
+    }
+
+    #[test]
+    fn checkpoints() {
+        let mut history = History::new(Tree::new());
+        history.get_mut().insert(1);
+        history.checkpoint("one");
+        history.get_mut().insert(2);
+        history.checkpoint("two");
+        history.get_mut().insert(3);
+        history.commit();
+
+        assert_eq!(history.checkpoints(), vec!["one", "two"]);
+        assert!(history.restore("one"));
+        assert_eq!(values(&history), vec![1]);
+        assert!(history.restore("two"));
+        assert_eq!(values(&history), vec![1, 2]);
+        assert!(!history.restore("three"));
+
+        history.restore("one");
+        history.commit();
+        assert_eq!(history.checkpoints(), vec!["one"]);
+    }
+}
@@ -0,0 +1,1090 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::iter::FromIterator;
+use std::rc::Rc;
+
+use crate::shared::Shared;
+
+pub mod atomic_stack;
+pub mod clone_counter;
+pub mod collect;
+pub mod deque;
+pub mod dot;
+pub mod hamt;
+pub mod heap_size;
+pub mod map;
+pub mod history;
+pub mod patch;
+pub mod rbtree;
+pub mod set;
+pub mod shared;
+pub mod stack;
+pub mod stream;
+pub mod sync;
+#[cfg(feature = "serde")]
+pub mod table;
+pub mod versions;
+
+#[derive(Debug, Clone)]
+pub enum ListBox<A> {
+    Nil,
+    Cons(A, Box<ListBox<A>>),
+}
+impl<A> Default for ListBox<A> {
+    fn default() -> Self {
+        ListBox::new()
+    }
+}
+impl<A> ListBox<A> {
+    pub fn new() -> Self {
+        ListBox::Nil
+    }
+    pub fn cons(&mut self, elem: A) {
+        let tail = std::mem::take(self);
+        let mut list = ListBox::Cons(elem, Box::new(tail));
+        std::mem::swap(self, &mut list)
+    }
+    pub fn uncons(&mut self) -> Option<A> {
+        let list = std::mem::take(self);
+        match list {
+            ListBox::Nil => None,
+            ListBox::Cons(elem, mut tail) => {
+                std::mem::swap(self, &mut tail);
+                Some(elem)
+            }
+        }
+    }
+}
+
+/// Collects into a list in the same order as the iterator.
+impl<A> FromIterator<A> for ListBox<A> {
+    fn from_iter<I: IntoIterator<Item = A>>(iter: I) -> Self {
+        let elems: Vec<A> = iter.into_iter().collect();
+        let mut list = ListBox::new();
+        for elem in elems.into_iter().rev() {
+            list.cons(elem);
+        }
+        list
+    }
+}
+/// Adds the elements to the end of the list.
+impl<A> Extend<A> for ListBox<A> {
+    fn extend<I: IntoIterator<Item = A>>(&mut self, iter: I) {
+        let mut end = self;
+        while let ListBox::Cons(_, ref mut tail) = *end {
+            end = tail;
+        }
+        *end = iter.into_iter().collect();
+    }
+}
+
+/// A persistent cons list. Clones share their tails, and a node is only
+/// copied when it is modified while shared.
+#[derive(Debug, Clone)]
+pub enum List<A> {
+    Nil,
+    /// The head, the tail, and the length of the list from here.
+    Cons(A, Shared<List<A>>, usize),
+}
+impl<A: Clone> Default for List<A> {
+    fn default() -> Self {
+        List::new()
+    }
+}
+impl<A: Clone> List<A> {
+    pub fn new() -> Self {
+        List::Nil
+    }
+    /// The number of elements, kept in each node so this is O(1).
+    pub fn len(&self) -> usize {
+        match *self {
+            List::Nil => 0,
+            List::Cons(_, _, len) => len,
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    pub fn cons(&mut self, elem: A) {
+        let tail = std::mem::take(self);
+        let len = tail.len() + 1;
+        let mut list = List::Cons(elem, Shared::new(tail), len);
+        std::mem::swap(self, &mut list)
+    }
+    pub fn uncons(&mut self) -> Option<A> {
+        let list = std::mem::take(self);
+        match list {
+            List::Nil => None,
+            List::Cons(elem, mut tail, _) => {
+                std::mem::swap(self, tail.make_mut());
+                Some(elem)
+            }
+        }
+    }
+    pub fn head(&self) -> Option<&A> {
+        match *self {
+            List::Nil => None,
+            List::Cons(ref elem, _, _) => Some(elem),
+        }
+    }
+    /// The list without its head, sharing this list's nodes.
+    pub fn tail(&self) -> Option<List<A>> {
+        match *self {
+            List::Nil => None,
+            List::Cons(_, ref tail, _) => Some((**tail).clone()),
+        }
+    }
+    /// The list without its first `count` elements, sharing this list's
+    /// nodes. Empty if the list is shorter than that.
+    pub fn skip(&self, count: usize) -> List<A> {
+        let mut list = self;
+        for _ in 0..count {
+            match *list {
+                List::Nil => break,
+                List::Cons(_, ref tail, _) => list = tail,
+            }
+        }
+        list.clone()
+    }
+    /// The first `count` elements, and the rest of the list. The rest
+    /// shares this list's nodes, and the first part is copied.
+    pub fn split_at(&self, count: usize) -> (List<A>, List<A>) {
+        let mut first = Vec::with_capacity(count.min(self.len()));
+        let mut list = self;
+        while let List::Cons(ref elem, ref tail, _) = *list {
+            if first.len() == count {
+                break;
+            }
+            first.push(elem.clone());
+            list = tail;
+        }
+        (first.into_iter().collect(), list.clone())
+    }
+    /// Puts `other` on the end of this list. The nodes of `other` are shared
+    /// rather than copied, so only this list's spine is rebuilt.
+    pub fn append(&mut self, other: &List<A>) {
+        let mut elems = Vec::with_capacity(self.len());
+        while let Some(elem) = self.uncons() {
+            elems.push(elem);
+        }
+        *self = other.clone();
+        for elem in elems.into_iter().rev() {
+            self.cons(elem);
+        }
+    }
+    /// Reverses the list in place.
+    pub fn rev(&mut self) {
+        let mut reversed = List::new();
+        while let Some(elem) = self.uncons() {
+            reversed.cons(elem);
+        }
+        *self = reversed;
+    }
+}
+
+/// Collects into a list in the same order as the iterator.
+impl<A: Clone> FromIterator<A> for List<A> {
+    fn from_iter<I: IntoIterator<Item = A>>(iter: I) -> Self {
+        let elems: Vec<A> = iter.into_iter().collect();
+        let mut list = List::new();
+        for elem in elems.into_iter().rev() {
+            list.cons(elem);
+        }
+        list
+    }
+}
+/// Adds the elements to the end of the list.
+impl<A: Clone> Extend<A> for List<A> {
+    fn extend<I: IntoIterator<Item = A>>(&mut self, iter: I) {
+        self.append(&iter.into_iter().collect());
+    }
+}
+/// Lists are compared element by element, stopping early at a tail the two
+/// lists share, so comparing a list with a copy that has a new head only
+/// looks at the new head.
+impl<A: PartialEq> PartialEq for List<A> {
+    fn eq(&self, other: &Self) -> bool {
+        let (mut ours, mut theirs) = (self, other);
+        loop {
+            match (ours, theirs) {
+                (List::Nil, List::Nil) => return true,
+                (List::Cons(elem, tail, len), List::Cons(other_elem, other_tail, other_len)) => {
+                    if len != other_len || elem != other_elem {
+                        return false;
+                    }
+                    if tail.ptr_eq(other_tail) {
+                        return true;
+                    }
+                    ours = tail;
+                    theirs = other_tail;
+                }
+                _ => return false,
+            }
+        }
+    }
+}
+impl<A: Eq> Eq for List<A> {}
+
+#[derive(Debug, Clone)]
+pub enum TreeBox<A> {
+    Leaf,
+    Node(Box<TreeBox<A>>, A, Box<TreeBox<A>>),
+}
+impl<A: Ord> Default for TreeBox<A> {
+    fn default() -> Self {
+        TreeBox::new()
+    }
+}
+impl<A: Ord> TreeBox<A> {
+    pub fn new() -> Self {
+        TreeBox::Leaf
+    }
+    pub fn singleton(value: A) -> Self {
+        TreeBox::Node(Box::new(TreeBox::Leaf), value, Box::new(TreeBox::Leaf))
+    }
+    pub fn insert(&mut self, input: A) {
+        match *self {
+            TreeBox::Leaf => *self = TreeBox::singleton(input),
+            TreeBox::Node(ref mut left, ref value, ref mut right) => {
+                if &input < value {
+                    left.insert(input);
+                } else if &input > value {
+                    right.insert(input);
+                }
+            }
+        }
+    }
+    pub fn find (&self, elem: &A) -> bool {
+        match *self {
+            TreeBox::Leaf => false,
+            TreeBox::Node(ref left, ref value, ref right) => {
+                if elem < value {
+                    left.find(elem)
+                } else if elem > value {
+                    right.find(elem)
+                } else {
+                    true
+                }
+            }
+        }
+    }
+    /// Removes `elem` from the tree, returning the stored value.
+    pub fn delete(&mut self, elem: &A) -> Option<A> {
+        match *self {
+            TreeBox::Leaf => None,
+            TreeBox::Node(ref mut left, ref value, _) if elem < value => left.delete(elem),
+            TreeBox::Node(_, ref value, ref mut right) if elem > value => right.delete(elem),
+            TreeBox::Node(_, _, _) => match std::mem::take(self) {
+                TreeBox::Node(left, value, mut right) => {
+                    match right.remove_smallest() {
+                        Some(smallest) => *self = TreeBox::Node(left, smallest, right),
+                        None => *self = *left,
+                    }
+                    Some(value)
+                }
+                TreeBox::Leaf => unreachable!(),
+            },
+        }
+    }
+    pub fn remove_smallest(&mut self) -> Option<A> {
+        let node = std::mem::take(self);
+        match node {
+            TreeBox::Leaf => None,
+            TreeBox::Node(mut left, value, right) => {
+                if let Some(leftmost) = left.remove_smallest() {
+                    *self = TreeBox::Node(left, value, right);
+                    Some(leftmost)
+                }
+                else {
+                    *self = *right;
+                    Some(value)
+                }
+            }
+        }
+    }
+    /// Shares the tree's nodes through `Rc` so it can be snapshotted
+    /// cheaply again, ending a burst of edits started by
+    /// `Tree::to_transient`. No values are cloned.
+    pub fn freeze(self) -> Tree<A> {
+        match self {
+            TreeBox::Leaf => Tree::Leaf,
+            TreeBox::Node(left, value, right) =>
+                Tree::Node(Rc::new(left.freeze()), value, Rc::new(right.freeze())),
+        }
+    }
+}
+
+impl<A: Ord> FromIterator<A> for TreeBox<A> {
+    fn from_iter<I: IntoIterator<Item = A>>(iter: I) -> Self {
+        let mut tree = TreeBox::new();
+        tree.extend(iter);
+        tree
+    }
+}
+impl<A: Ord> Extend<A> for TreeBox<A> {
+    fn extend<I: IntoIterator<Item = A>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Tree<A> {
+    Leaf,
+    Node(Rc<Tree<A>>, A, Rc<Tree<A>>),
+}
+impl<A: Ord + Clone> Default for Tree<A> {
+    fn default() -> Self {
+        Tree::new()
+    }
+}
+impl<A> Tree<A> {
+    /// The values in ascending order, by reference, so `A` need not be
+    /// `Clone`.
+    pub fn iter(&self) -> TreeIter<'_, A> {
+        let mut iter = TreeIter { stack: Vec::new() };
+        iter.push_left(self);
+        iter
+    }
+}
+impl<A: Ord + Clone> Tree<A> {
+    pub fn new() -> Self {
+        Tree::Leaf
+    }
+    pub fn singleton(value: A) -> Self {
+        Tree::Node(Rc::new(Tree::Leaf), value, Rc::new(Tree::Leaf))
+    }
+    pub fn insert(&mut self, input: A) {
+        match *self {
+            Tree::Leaf => *self = Tree::singleton(input),
+            Tree::Node(ref mut left, ref value, ref mut right) => {
+                if &input < value {
+                    Rc::make_mut(left).insert(input);
+                } else if &input > value {
+                    Rc::make_mut(right).insert(input);
+                }
+            }
+        }
+    }
+    /// The stored value equal to `elem`, which may carry more than `elem`
+    /// if `A` only compares part of itself.
+    pub fn get(&self, elem: &A) -> Option<&A> {
+        self.find_by(&|value| elem.cmp(value))
+    }
+    /// The stored value equal to `probe`, first inserting the one made by
+    /// `make` if there is none. `make` must return a value equal to
+    /// `probe`.
+    pub fn get_or_insert_with<F: FnOnce() -> A>(&mut self, probe: &A, make: F) -> &A {
+        if self.get(probe).is_none() {
+            self.insert(make());
+        }
+        self.get(probe).expect("get_or_insert_with made a value not equal to the probe")
+    }
+    pub fn remove_smallest(&mut self) -> Option<A> {
+        let node = std::mem::take(self);
+        match node {
+            Tree::Leaf => None,
+            Tree::Node(mut left, value, mut right) => {
+                if let Some(leftmost) = Rc::make_mut(&mut left).remove_smallest() {
+                    *self = Tree::Node(left, value, right);
+                    Some(leftmost)
+                }
+                else {
+                    std::mem::swap(self, Rc::make_mut(&mut right));
+                    Some(value)
+                }
+            }
+        }
+    }
+    /// A uniquely owned copy of the tree for a burst of edits. The copy
+    /// takes O(n), but its updates then skip the reference count checks
+    /// and path copying of `Rc::make_mut`. Use `TreeBox::freeze` to turn it
+    /// back into a `Tree`.
+    pub fn to_transient(&self) -> TreeBox<A> {
+        match *self {
+            Tree::Leaf => TreeBox::Leaf,
+            Tree::Node(ref left, ref value, ref right) => TreeBox::Node(
+                Box::new(left.to_transient()), value.clone(), Box::new(right.to_transient())),
+        }
+    }
+    /// Removes and returns the largest value, copying only the nodes on
+    /// the path to it.
+    pub fn remove_largest(&mut self) -> Option<A> {
+        let node = std::mem::take(self);
+        match node {
+            Tree::Leaf => None,
+            Tree::Node(mut left, value, mut right) => {
+                if let Some(rightmost) = Rc::make_mut(&mut right).remove_largest() {
+                    *self = Tree::Node(left, value, right);
+                    Some(rightmost)
+                }
+                else {
+                    std::mem::swap(self, Rc::make_mut(&mut left));
+                    Some(value)
+                }
+            }
+        }
+    }
+    /// Removes `elem` from the tree, returning the stored value. Only the
+    /// nodes on the path to `elem` are copied, and nothing is copied if it
+    /// is not there.
+    pub fn delete(&mut self, elem: &A) -> Option<A> {
+        self.delete_by(&|value| elem.cmp(value))
+    }
+    /// The smallest value, which `remove_smallest` would remove. With
+    /// `get_largest` and the two removals, the tree serves as a double-ended
+    /// priority queue.
+    pub fn get_smallest(&self) -> Option<&A> {
+        match *self {
+            Tree::Leaf => None,
+            Tree::Node(ref left, ref value, _) => left.get_smallest().or(Some(value)),
+        }
+    }
+    pub fn get_largest(&self) -> Option<&A> {
+        match *self {
+            Tree::Leaf => None,
+            Tree::Node(_, ref value, ref right) => right.get_largest().or(Some(value)),
+        }
+    }
+    /// Calls `func` on each value in ascending order.
+    pub fn for_each<F: FnMut(&A)>(&self, func: &mut F) {
+        match *self {
+            Tree::Leaf => (),
+            Tree::Node(ref left, ref value, ref right) => {
+                left.for_each(func);
+                func(value);
+                right.for_each(func);
+            }
+        }
+    }
+
+    /// The values that would have to be added to and removed from this
+    /// tree to get `other`. Subtrees the two trees share are skipped, so
+    /// comparing a snapshot with a lightly modified copy of it only visits
+    /// the modified paths.
+    pub fn diff<'a>(&'a self, other: &'a Tree<A>) -> Diff<'a, A> {
+        let mut diff = Diff { added: Vec::new(), removed: Vec::new() };
+        self.diff_into(other, &mut diff);
+        diff
+    }
+
+    /// Counts the nodes of the two trees that are stored once and shared
+    /// between them, and those that belong to only one of them.
+    pub fn sharing_stats(&self, other: &Tree<A>) -> SharingStats {
+        let (mut ours, mut theirs) = (HashSet::new(), HashSet::new());
+        self.collect_nodes(&mut ours);
+        other.collect_nodes(&mut theirs);
+        let shared = ours.intersection(&theirs).count();
+        SharingStats {
+            shared,
+            only_self: ours.len() - shared,
+            only_other: theirs.len() - shared,
+        }
+    }
+
+    /// The values in either tree. Subtrees the two trees share are reused
+    /// whole rather than visited, so combining a snapshot with a lightly
+    /// modified copy of it only walks the modified paths.
+    pub fn union(&self, other: &Tree<A>) -> Tree<A> {
+        Tree::combine(self, other, Tree::union_shared)
+    }
+    /// The values in both trees, reusing shared subtrees as `union` does.
+    pub fn intersection(&self, other: &Tree<A>) -> Tree<A> {
+        Tree::combine(self, other, Tree::intersection_shared)
+    }
+    /// The values in this tree but not in `other`, skipping shared subtrees
+    /// as `union` does.
+    pub fn difference(&self, other: &Tree<A>) -> Tree<A> {
+        Tree::combine(self, other, Tree::difference_shared)
+    }
+
+    /// Adds the address of every node in the tree to `nodes`. Children are
+    /// reached through `Rc`s, so a node shared by two trees has the same
+    /// address in both.
+    fn collect_nodes(&self, nodes: &mut HashSet<*const Tree<A>>) {
+        if let Tree::Node(ref left, _, ref right) = *self {
+            if nodes.insert(self as *const Tree<A>) {
+                left.collect_nodes(nodes);
+                right.collect_nodes(nodes);
+            }
+        }
+    }
+    /// A balanced tree of the next `len` values of `values`, which must be
+    /// ascending.
+    fn from_sorted<I: Iterator<Item = A>>(len: usize, values: &mut I) -> Tree<A> {
+        if len == 0 {
+            return Tree::Leaf;
+        }
+        let left = Tree::from_sorted(len / 2, values);
+        let value = values.next().expect("fewer values than expected");
+        let right = Tree::from_sorted(len - len / 2 - 1, values);
+        Tree::Node(Rc::new(left), value, Rc::new(right))
+    }
+    /// Runs a set operation on the two trees. Only their roots are copied
+    /// to put them behind `Rc`s.
+    fn combine<F>(ours: &Tree<A>, theirs: &Tree<A>, operation: F) -> Tree<A>
+        where F: Fn(&Rc<Tree<A>>, &Rc<Tree<A>>) -> Rc<Tree<A>>
+    {
+        let combined = operation(&Rc::new(ours.clone()), &Rc::new(theirs.clone()));
+        Rc::unwrap_or_clone(combined)
+    }
+    fn union_shared(ours: &Rc<Tree<A>>, theirs: &Rc<Tree<A>>) -> Rc<Tree<A>> {
+        match (&**ours, &**theirs) {
+            _ if Rc::ptr_eq(ours, theirs) => ours.clone(),
+            (_, Tree::Leaf) => ours.clone(),
+            (Tree::Leaf, _) => theirs.clone(),
+            (Tree::Node(left, value, right), _) => {
+                let (their_left, _, their_right) = Tree::split(theirs, value);
+                let new_left = Tree::union_shared(left, &their_left);
+                let new_right = Tree::union_shared(right, &their_right);
+                Tree::rebuild(ours, new_left, new_right)
+            }
+        }
+    }
+    fn intersection_shared(ours: &Rc<Tree<A>>, theirs: &Rc<Tree<A>>) -> Rc<Tree<A>> {
+        match (&**ours, &**theirs) {
+            _ if Rc::ptr_eq(ours, theirs) => ours.clone(),
+            (_, Tree::Leaf) => theirs.clone(),
+            (Tree::Leaf, _) => ours.clone(),
+            (Tree::Node(left, value, right), _) => {
+                let (their_left, found, their_right) = Tree::split(theirs, value);
+                let new_left = Tree::intersection_shared(left, &their_left);
+                let new_right = Tree::intersection_shared(right, &their_right);
+                if found {
+                    Tree::rebuild(ours, new_left, new_right)
+                }
+                else {
+                    Tree::join(new_left, new_right)
+                }
+            }
+        }
+    }
+    fn difference_shared(ours: &Rc<Tree<A>>, theirs: &Rc<Tree<A>>) -> Rc<Tree<A>> {
+        match (&**ours, &**theirs) {
+            _ if Rc::ptr_eq(ours, theirs) => Rc::new(Tree::Leaf),
+            (_, Tree::Leaf) | (Tree::Leaf, _) => ours.clone(),
+            (Tree::Node(left, value, right), _) => {
+                let (their_left, found, their_right) = Tree::split(theirs, value);
+                let new_left = Tree::difference_shared(left, &their_left);
+                let new_right = Tree::difference_shared(right, &their_right);
+                if found {
+                    Tree::join(new_left, new_right)
+                }
+                else {
+                    Tree::rebuild(ours, new_left, new_right)
+                }
+            }
+        }
+    }
+    /// The values of `tree` below and above `key`, and whether `key` is in
+    /// it. Subtrees entirely on one side of `key` are shared, not copied.
+    fn split(tree: &Rc<Tree<A>>, key: &A) -> (Rc<Tree<A>>, bool, Rc<Tree<A>>) {
+        match **tree {
+            Tree::Leaf => (tree.clone(), false, tree.clone()),
+            Tree::Node(ref left, ref value, ref right) => match key.cmp(value) {
+                Ordering::Less => {
+                    let (below, found, above) = Tree::split(left, key);
+                    (below, found, Rc::new(Tree::Node(above, value.clone(), right.clone())))
+                }
+                Ordering::Greater => {
+                    let (below, found, above) = Tree::split(right, key);
+                    (Rc::new(Tree::Node(left.clone(), value.clone(), below)), found, above)
+                }
+                Ordering::Equal => (left.clone(), true, right.clone()),
+            },
+        }
+    }
+    /// `node` with new children, or `node` itself if they are unchanged.
+    fn rebuild(node: &Rc<Tree<A>>, new_left: Rc<Tree<A>>, new_right: Rc<Tree<A>>) -> Rc<Tree<A>> {
+        match **node {
+            Tree::Node(ref left, _, ref right)
+                if Rc::ptr_eq(left, &new_left) && Rc::ptr_eq(right, &new_right) => node.clone(),
+            Tree::Node(_, ref value, _) => Rc::new(Tree::Node(new_left, value.clone(), new_right)),
+            Tree::Leaf => panic!("Tried to rebuild a leaf"),
+        }
+    }
+    /// Joins two trees where every value of `left` is below every value of
+    /// `right`.
+    fn join(left: Rc<Tree<A>>, mut right: Rc<Tree<A>>) -> Rc<Tree<A>> {
+        match (&*left, &*right) {
+            (_, Tree::Leaf) => left,
+            (Tree::Leaf, _) => right,
+            _ => {
+                let smallest = Rc::make_mut(&mut right).remove_smallest().unwrap();
+                Rc::new(Tree::Node(left, smallest, right))
+            }
+        }
+    }
+    fn diff_into<'a>(&'a self, other: &'a Tree<A>, diff: &mut Diff<'a, A>) {
+        match (self, other) {
+            (Tree::Node(left, value, right), Tree::Node(other_left, other_value, other_right))
+                if value == other_value =>
+            {
+                if !Rc::ptr_eq(left, other_left) {
+                    left.diff_into(other_left, diff);
+                }
+                if !Rc::ptr_eq(right, other_right) {
+                    right.diff_into(other_right, diff);
+                }
+            }
+            _ => {
+                // the trees are shaped differently here, so compare every value.
+                let (mut ours, mut theirs) = (self.iter().peekable(), other.iter().peekable());
+                loop {
+                    match (ours.peek(), theirs.peek()) {
+                        (None, None) => break,
+                        (Some(_), None) => diff.removed.extend(ours.by_ref()),
+                        (None, Some(_)) => diff.added.extend(theirs.by_ref()),
+                        (Some(a), Some(b)) => match a.cmp(b) {
+                            Ordering::Less => diff.removed.extend(ours.next()),
+                            Ordering::Greater => diff.added.extend(theirs.next()),
+                            Ordering::Equal => {
+                                ours.next();
+                                theirs.next();
+                            }
+                        },
+                    }
+                }
+            }
+        }
+    }
+    /// Looks up a value using `compare`, which orders the value being
+    /// searched for relative to each value in the tree.
+    pub(crate) fn find_by<F: Fn(&A) -> Ordering>(&self, compare: &F) -> Option<&A> {
+        match *self {
+            Tree::Leaf => None,
+            Tree::Node(ref left, ref value, ref right) => match compare(value) {
+                Ordering::Less => left.find_by(compare),
+                Ordering::Greater => right.find_by(compare),
+                Ordering::Equal => Some(value),
+            },
+        }
+    }
+    /// As `find_by`, copying the nodes on the path to the value if they are
+    /// shared. The caller must not change how the value is ordered.
+    pub(crate) fn find_by_mut<F: Fn(&A) -> Ordering>(&mut self, compare: &F) -> Option<&mut A> {
+        self.find_by(compare)?;
+        Some(self.find_present_mut(compare))
+    }
+    /// As `delete`, comparing with `compare` as in `find_by`.
+    pub(crate) fn delete_by<F: Fn(&A) -> Ordering>(&mut self, compare: &F) -> Option<A> {
+        self.find_by(compare)?;
+        Some(self.delete_present(compare))
+    }
+
+    fn find_present_mut<F: Fn(&A) -> Ordering>(&mut self, compare: &F) -> &mut A {
+        match *self {
+            Tree::Leaf => panic!("Tried to find a value that is not in the tree"),
+            Tree::Node(ref mut left, ref mut value, ref mut right) => match compare(value) {
+                Ordering::Less => Rc::make_mut(left).find_present_mut(compare),
+                Ordering::Greater => Rc::make_mut(right).find_present_mut(compare),
+                Ordering::Equal => value,
+            },
+        }
+    }
+    fn delete_present<F: Fn(&A) -> Ordering>(&mut self, compare: &F) -> A {
+        let node = std::mem::take(self);
+        match node {
+            Tree::Leaf => panic!("Tried to delete a value that is not in the tree"),
+            Tree::Node(mut left, value, mut right) => match compare(&value) {
+                Ordering::Less => {
+                    let removed = Rc::make_mut(&mut left).delete_present(compare);
+                    *self = Tree::Node(left, value, right);
+                    removed
+                }
+                Ordering::Greater => {
+                    let removed = Rc::make_mut(&mut right).delete_present(compare);
+                    *self = Tree::Node(left, value, right);
+                    removed
+                }
+                Ordering::Equal => {
+                    match Rc::make_mut(&mut right).remove_smallest() {
+                        Some(smallest) => *self = Tree::Node(left, smallest, right),
+                        None => std::mem::swap(self, Rc::make_mut(&mut left)),
+                    }
+                    value
+                }
+            },
+        }
+    }
+}
+
+/// Collects into a balanced tree, whatever order the values come in. Of
+/// equal values, the first is kept, as with `insert`.
+impl<A: Ord + Clone> FromIterator<A> for Tree<A> {
+    fn from_iter<I: IntoIterator<Item = A>>(iter: I) -> Self {
+        let mut values: Vec<A> = iter.into_iter().collect();
+        values.sort();
+        values.dedup();
+        Tree::from_sorted(values.len(), &mut values.into_iter())
+    }
+}
+impl<A: Ord + Clone> Extend<A> for Tree<A> {
+    fn extend<I: IntoIterator<Item = A>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+/// Trees are equal when they hold the same values, whatever their shape.
+/// Where both trees have the same value at a node, children they share are
+/// skipped, so comparing snapshots only visits the paths that differ.
+impl<A: PartialEq> PartialEq for Tree<A> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Tree::Node(left, value, right), Tree::Node(other_left, other_value, other_right))
+                if value == other_value =>
+            {
+                (Rc::ptr_eq(left, other_left) || left == other_left)
+                    && (Rc::ptr_eq(right, other_right) || right == other_right)
+            }
+            _ => self.iter().eq(other.iter()),
+        }
+    }
+}
+impl<A: Eq> Eq for Tree<A> {}
+
+/// The values of a `Tree` in ascending order.
+#[derive(Debug, Clone)]
+pub struct TreeIter<'a, A> {
+    /// Nodes whose value and right subtree are still to be visited, with the
+    /// next node on top.
+    stack: Vec<&'a Tree<A>>,
+}
+impl<'a, A> TreeIter<'a, A> {
+    fn push_left(&mut self, mut tree: &'a Tree<A>) {
+        while let Tree::Node(ref left, _, _) = *tree {
+            self.stack.push(tree);
+            tree = left;
+        }
+    }
+}
+impl<'a, A> Iterator for TreeIter<'a, A> {
+    type Item = &'a A;
+    fn next(&mut self) -> Option<&'a A> {
+        match *self.stack.pop()? {
+            Tree::Leaf => unreachable!("only nodes are pushed"),
+            Tree::Node(_, ref value, ref right) => {
+                self.push_left(right);
+                Some(value)
+            }
+        }
+    }
+}
+impl<'a, A> IntoIterator for &'a Tree<A> {
+    type Item = &'a A;
+    type IntoIter = TreeIter<'a, A>;
+    fn into_iter(self) -> TreeIter<'a, A> {
+        self.iter()
+    }
+}
+
+/// The result of `Tree::diff`, each side in ascending order.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Diff<'a, A> {
+    pub added: Vec<&'a A>,
+    pub removed: Vec<&'a A>,
+}
+
+/// The result of `Tree::sharing_stats`, counted in nodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SharingStats {
+    pub shared: usize,
+    pub only_self: usize,
+    pub only_other: usize,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn list (elems: &[u32]) -> List<u32> {
+        let mut list = List::new();
+        for &elem in elems.iter().rev() {
+            list.cons(elem);
+        }
+        list
+    }
+    fn to_vec (mut list: List<u32>) -> Vec<u32> {
+        let mut elems = Vec::new();
+        while let Some(elem) = list.uncons() {
+            elems.push(elem);
+        }
+        elems
+    }
+
+    #[test]
+    fn append_shares_second_list () {
+        let mut first = list(&[1, 2, 3]);
+        let second = list(&[4, 5]);
+        first.append(&second);
+        assert_eq!(first.len(), 5);
+        assert_eq!(to_vec(first.clone()), vec![1, 2, 3, 4, 5]);
+
+        let mut tail = &first;
+        for _ in 0..3 {
+            match *tail {
+                List::Cons(_, ref next, _) => tail = next,
+                List::Nil => panic!("list too short"),
+            }
+        }
+        match (tail, &second) {
+            (List::Cons(_, next, _), List::Cons(_, second_next, _)) =>
+                assert!(next.ptr_eq(second_next)),
+            _ => panic!("list too short"),
+        }
+    }
+
+    fn tree (values: &[u32]) -> Tree<u32> {
+        let mut tree = Tree::new();
+        for &value in values {
+            tree.insert(value);
+        }
+        tree
+    }
+    fn tree_values (tree: &Tree<u32>) -> Vec<u32> {
+        let mut values = Vec::new();
+        tree.for_each(&mut |&value| values.push(value));
+        values
+    }
+
+    #[test]
+    fn tree_set_operations () {
+        let mut tree = tree(&[50, 30, 70, 20, 40, 60, 80, 35]);
+        let snapshot = tree.clone();
+        assert_eq!(tree.get_smallest(), Some(&20));
+        assert_eq!(tree.get_largest(), Some(&80));
+
+        assert_eq!(tree.get(&35), Some(&35));
+        assert_eq!(tree.get(&36), None);
+
+        assert_eq!(tree.delete(&30), Some(30));
+        assert_eq!(tree.delete(&50), Some(50));
+        assert_eq!(tree.delete(&50), None);
+        assert_eq!(tree.remove_largest(), Some(80));
+        assert_eq!(tree.remove_smallest(), Some(20));
+        assert_eq!(tree_values(&tree), vec![35, 40, 60, 70]);
+        assert_eq!(tree.get_smallest(), Some(&35));
+        assert_eq!(tree.get_largest(), Some(&70));
+        assert_eq!(tree_values(&snapshot), vec![20, 30, 35, 40, 50, 60, 70, 80]);
+    }
+
+    #[test]
+    fn get_stored_payloads () {
+        /// Ordered by name alone, so a probe only needs the name.
+        #[derive(Debug, Clone)]
+        struct Named(&'static str, u32);
+        impl PartialEq for Named {
+            fn eq(&self, other: &Self) -> bool {
+                self.0 == other.0
+            }
+        }
+        impl Eq for Named {}
+        impl PartialOrd for Named {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+        impl Ord for Named {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.0.cmp(other.0)
+            }
+        }
+
+        let mut tree = Tree::new();
+        tree.insert(Named("b", 2));
+        assert_eq!(tree.get(&Named("b", 0)).map(|named| named.1), Some(2));
+        assert_eq!(tree.get_or_insert_with(&Named("b", 0), || Named("b", 20)).1, 2);
+        assert_eq!(tree.get_or_insert_with(&Named("a", 0), || Named("a", 1)).1, 1);
+        assert_eq!(tree.get(&Named("a", 0)).map(|named| named.1), Some(1));
+    }
+
+    #[test]
+    fn delete_missing_keeps_sharing () {
+        let mut tree = tree(&[2, 1, 3]);
+        let snapshot = tree.clone();
+        tree.delete(&4);
+        match (&tree, &snapshot) {
+            (Tree::Node(left, _, right), Tree::Node(old_left, _, old_right)) => {
+                assert!(Rc::ptr_eq(left, old_left));
+                assert!(Rc::ptr_eq(right, old_right));
+            }
+            _ => panic!("Unexpected leaf"),
+        }
+    }
+
+    #[test]
+    fn diff_snapshots () {
+        let original = tree(&[50, 30, 70, 20, 40, 60, 80]);
+        let mut changed = original.clone();
+        changed.insert(65);
+        changed.delete(&20);
+        changed.delete(&50);
+        let diff = original.diff(&changed);
+        assert_eq!(diff.added, vec![&65]);
+        assert_eq!(diff.removed, vec![&20, &50]);
+
+        let reversed = changed.diff(&original);
+        assert_eq!(reversed.added, diff.removed);
+        assert_eq!(original.diff(&original.clone()), Diff { added: vec![], removed: vec![] });
+    }
+
+    #[test]
+    fn sharing_stats () {
+        let original = tree(&[50, 30, 70, 20, 40, 60, 80]);
+        let mut changed = original.clone();
+        assert_eq!(original.sharing_stats(&changed), SharingStats { shared: 6, only_self: 1, only_other: 1 });
+
+        changed.insert(65);
+        assert_eq!(original.sharing_stats(&changed), SharingStats { shared: 4, only_self: 3, only_other: 4 });
+        assert_eq!(changed.sharing_stats(&original).only_self, 4);
+    }
+
+    #[test]
+    fn union_intersection_difference () {
+        let odd = tree(&[5, 3, 7, 1, 9]);
+        let low = tree(&[4, 2, 6, 1, 3, 5]);
+        assert_eq!(tree_values(&odd.union(&low)), vec![1, 2, 3, 4, 5, 6, 7, 9]);
+        assert_eq!(tree_values(&odd.intersection(&low)), vec![1, 3, 5]);
+        assert_eq!(tree_values(&odd.difference(&low)), vec![7, 9]);
+        assert_eq!(tree_values(&low.difference(&odd)), vec![2, 4, 6]);
+        assert_eq!(tree_values(&odd.difference(&odd.clone())), Vec::<u32>::new());
+        assert_eq!(tree_values(&odd.union(&Tree::new())), vec![1, 3, 5, 7, 9]);
+    }
+
+    #[test]
+    fn set_operations_reuse_shared_subtrees () {
+        let original: Tree<u32> = (0..100).map(|value| (value * 37) % 100).collect();
+        let mut changed = original.clone();
+        changed.insert(1000);
+        changed.delete(&74);
+
+        let union = original.union(&changed);
+        let intersection = original.intersection(&changed);
+        assert_eq!(union.iter().count(), 101);
+        assert_eq!(intersection.iter().count(), 99);
+        assert_eq!(tree_values(&changed.difference(&original)), vec![1000]);
+        assert_eq!(tree_values(&original.difference(&changed)), vec![74]);
+
+        // only the paths to 74 and 1000 are new.
+        assert!(union.sharing_stats(&original).only_self < 20);
+        assert!(intersection.sharing_stats(&changed).only_self < 20);
+    }
+
+    #[test]
+    fn equality () {
+        let elems = list(&[1, 2, 3]);
+        let mut longer = elems.clone();
+        longer.cons(0);
+        assert_eq!(elems, list(&[1, 2, 3]));
+        assert_ne!(elems, longer);
+        assert_ne!(elems, list(&[1, 2, 4]));
+        longer.uncons();
+        assert_eq!(elems, longer);
+
+        // NaN is not equal to itself, so only a shared tail can compare equal.
+        let mut nans = List::new();
+        nans.cons(f64::NAN);
+        nans.cons(1.0);
+        assert_eq!(nans, nans.clone());
+        let mut other = List::new();
+        other.cons(f64::NAN);
+        other.cons(1.0);
+        assert_ne!(nans, other);
+
+        let original = tree(&[50, 30, 70, 20, 40]);
+        let mut changed = original.clone();
+        changed.insert(60);
+        assert_ne!(original, changed);
+        changed.delete(&60);
+        assert_eq!(original, changed);
+        assert_eq!(original, tree(&[20, 30, 40, 50, 70]));
+    }
+
+    #[test]
+    fn tree_iter () {
+        let tree = tree(&[50, 30, 70, 20, 40, 60, 80, 35]);
+        let values: Vec<u32> = tree.iter().cloned().collect();
+        assert_eq!(values, tree_values(&tree));
+        assert_eq!((&tree).into_iter().nth(2), Some(&35));
+
+        // no `Clone` needed to iterate.
+        struct Opaque(u32);
+        let leaf = || Rc::new(Tree::Leaf);
+        let tree = Tree::Node(Rc::new(Tree::Node(leaf(), Opaque(1), leaf())), Opaque(2), leaf());
+        assert_eq!(tree.iter().map(|value| value.0).collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    #[test]
+    fn collect_and_extend () {
+        let mut boxed: ListBox<u32> = (1..4).collect();
+        boxed.extend(4..6);
+        let mut elems = Vec::new();
+        while let Some(elem) = boxed.uncons() {
+            elems.push(elem);
+        }
+        assert_eq!(elems, vec![1, 2, 3, 4, 5]);
+
+        let mut elems: List<u32> = (1..4).collect();
+        elems.extend(4..6);
+        assert_eq!(elems.len(), 5);
+        assert_eq!(to_vec(elems), vec![1, 2, 3, 4, 5]);
+
+        let mut tree: Tree<u32> = vec![5, 2, 8].into_iter().collect();
+        tree.extend(vec![1, 9, 2]);
+        assert_eq!(tree_values(&tree), vec![1, 2, 5, 8, 9]);
+
+        let mut boxed: TreeBox<u32> = vec![5, 2, 8].into_iter().collect();
+        boxed.extend(vec![1, 9]);
+        assert_eq!(boxed.remove_smallest(), Some(1));
+        assert!(boxed.find(&9));
+    }
+
+    #[test]
+    fn transient_round_trip () {
+        let original = tree(&[50, 30, 70]);
+        let mut transient = original.to_transient();
+        transient.extend(vec![20, 40, 60, 80]);
+        assert_eq!(transient.remove_smallest(), Some(20));
+        let frozen = transient.freeze();
+
+        assert_eq!(tree_values(&frozen), vec![30, 40, 50, 60, 70, 80]);
+        assert_eq!(tree_values(&original), vec![30, 50, 70]);
+    }
+
+    #[test]
+    fn head_tail_split () {
+        let elems = list(&[1, 2, 3, 4]);
+        assert_eq!(elems.head(), Some(&1));
+        assert_eq!(elems.tail(), Some(list(&[2, 3, 4])));
+        assert_eq!(List::<u32>::new().tail(), None);
+        assert_eq!(elems.skip(3), list(&[4]));
+        assert!(elems.skip(10).is_empty());
+
+        let (first, rest) = elems.split_at(1);
+        assert_eq!(first, list(&[1]));
+        assert_eq!(rest.len(), 3);
+        match (&rest, &elems) {
+            (List::Cons(_, rest_tail, _), List::Cons(_, tail, _)) => match **tail {
+                List::Cons(_, ref tail_tail, _) => assert!(rest_tail.ptr_eq(tail_tail)),
+                List::Nil => panic!("Unexpected nil"),
+            },
+            _ => panic!("Unexpected nil"),
+        }
+        assert_eq!(elems.split_at(9), (elems.clone(), List::new()));
+        assert_eq!(to_vec(elems), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn rev_and_len () {
+        let mut elems = list(&[1, 2, 3, 4]);
+        let original = elems.clone();
+        elems.rev();
+        assert_eq!(elems.len(), 4);
+        assert_eq!(to_vec(elems), vec![4, 3, 2, 1]);
+        assert_eq!(to_vec(original), vec![1, 2, 3, 4]);
+        assert!(List::<u32>::new().is_empty());
+    }
+}
@@ -0,0 +1,117 @@
+//! A persistent map from keys to values, stored in a `Tree` of entries
+//! ordered by key alone. Updates return a new map and leave the old one
+//! as it was, sharing every node off the path to the change.
+use crate::Tree;
+
+#[derive(Debug, Clone)]
+struct Entry<K, V> {
+    key: K,
+    value: V,
+}
+// entries are only compared by key, so a map can not hold two entries with
+// the same key.
+impl<K: Ord, V> PartialEq for Entry<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+impl<K: Ord, V> Eq for Entry<K, V> {}
+impl<K: Ord, V> PartialOrd for Entry<K, V> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl<K: Ord, V> Ord for Entry<K, V> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PMap<K, V> {
+    tree: Tree<Entry<K, V>>,
+    len: usize,
+}
+impl<K: Ord + Clone, V: Clone> Default for PMap<K, V> {
+    fn default() -> Self {
+        PMap::new()
+    }
+}
+impl<K: Ord + Clone, V: Clone> PMap<K, V> {
+    pub fn new() -> Self {
+        PMap {
+            tree: Tree::new(),
+            len: 0,
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.tree.find_by(&|entry| key.cmp(&entry.key)).map(|entry| &entry.value)
+    }
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+    /// A map with `value` stored under `key`, and the value it displaced.
+    pub fn insert(&self, key: K, value: V) -> (Self, Option<V>) {
+        let mut map = self.clone();
+        let old = match map.tree.find_by_mut(&|entry| key.cmp(&entry.key)) {
+            Some(entry) => Some(std::mem::replace(&mut entry.value, value)),
+            None => {
+                map.tree.insert(Entry { key, value });
+                map.len += 1;
+                None
+            }
+        };
+        (map, old)
+    }
+    /// A map without `key`, and the value that was stored under it.
+    pub fn remove(&self, key: &K) -> (Self, Option<V>) {
+        let mut map = self.clone();
+        let old = map.tree.delete_by(&|entry| key.cmp(&entry.key)).map(|entry| entry.value);
+        if old.is_some() {
+            map.len -= 1;
+        }
+        (map, old)
+    }
+    /// The entries in ascending order of key.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.tree.iter().map(|entry| (&entry.key, &entry.value))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn insert_and_remove() {
+        let empty = PMap::new();
+        let (one, old) = empty.insert("one", 1);
+        assert_eq!(old, None);
+        let (two, _) = one.insert("two", 2);
+        let (changed, old) = two.insert("one", 10);
+        assert_eq!(old, Some(1));
+
+        assert_eq!(one.get(&"one"), Some(&1));
+        assert_eq!(one.get(&"two"), None);
+        assert_eq!(two.len(), 2);
+        assert_eq!(changed.len(), 2);
+        assert_eq!(changed.get(&"one"), Some(&10));
+        assert_eq!(two.get(&"one"), Some(&1));
+
+        let (removed, old) = changed.remove(&"two");
+        assert_eq!(old, Some(2));
+        assert_eq!(removed.iter().collect::<Vec<_>>(), vec![(&"one", &10)]);
+        assert!(changed.contains_key(&"two"));
+
+        let (same, old) = removed.remove(&"three");
+        assert_eq!(old, None);
+        assert_eq!(same.len(), 1);
+        assert!(empty.is_empty());
+    }
+}
@@ -0,0 +1,177 @@
+//! Changes to a `Tree` as data, so they can be stored, sent elsewhere, and
+//! replayed onto other snapshots or undone.
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::Tree;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Change<A> {
+    Insert(A),
+    Remove(A),
+}
+impl<A: Clone> Change<A> {
+    /// The change that undoes this one.
+    pub fn invert(&self) -> Change<A> {
+        match *self {
+            Change::Insert(ref value) => Change::Remove(value.clone()),
+            Change::Remove(ref value) => Change::Insert(value.clone()),
+        }
+    }
+}
+
+/// A sequence of changes, applied in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Patch<A> {
+    changes: Vec<Change<A>>,
+}
+impl<A: Ord + Clone> Default for Patch<A> {
+    fn default() -> Self {
+        Patch::new()
+    }
+}
+impl<A: Ord + Clone> Patch<A> {
+    pub fn new() -> Self {
+        Patch { changes: Vec::new() }
+    }
+    /// The changes that turn `before` into `after`, found with `Tree::diff`
+    /// so subtrees the two share are skipped.
+    pub fn between(before: &Tree<A>, after: &Tree<A>) -> Self {
+        let diff = before.diff(after);
+        let removed = diff.removed.into_iter().map(|value| Change::Remove(value.clone()));
+        let added = diff.added.into_iter().map(|value| Change::Insert(value.clone()));
+        Patch { changes: removed.chain(added).collect() }
+    }
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+    pub fn changes(&self) -> &[Change<A>] {
+        &self.changes
+    }
+    pub fn insert(&mut self, value: A) {
+        self.changes.push(Change::Insert(value));
+    }
+    pub fn remove(&mut self, value: A) {
+        self.changes.push(Change::Remove(value));
+    }
+    /// Makes each change to `tree` in turn. Inserting a value that is
+    /// already there or removing one that is not does nothing, so the
+    /// changes that did something are returned: inverting those gives a
+    /// patch that puts `tree` back as it was. A removal is returned with the
+    /// value that was in the tree, which may differ from the one asked for
+    /// in the parts `Ord` ignores.
+    pub fn apply(&self, tree: &mut Tree<A>) -> Patch<A> {
+        let mut applied = Patch::new();
+        for change in &self.changes {
+            let done = match *change {
+                Change::Insert(ref value) => {
+                    if tree.get(value).is_some() {
+                        continue;
+                    }
+                    tree.insert(value.clone());
+                    change.clone()
+                }
+                Change::Remove(ref value) => match tree.delete(value) {
+                    Some(removed) => Change::Remove(removed),
+                    None => continue,
+                },
+            };
+            applied.changes.push(done);
+        }
+        applied
+    }
+    /// The patch that undoes this one: each change inverted, in reverse
+    /// order.
+    pub fn invert(&self) -> Patch<A> {
+        Patch { changes: self.changes.iter().rev().map(Change::invert).collect() }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn values(tree: &Tree<u32>) -> Vec<u32> {
+        tree.iter().copied().collect()
+    }
+
+    #[test]
+    fn apply_and_invert() {
+        let original: Tree<u32> = vec![1, 2, 3].into_iter().collect();
+        let mut patch = Patch::new();
+        patch.insert(4);
+        patch.remove(1);
+        patch.insert(2);
+        patch.remove(9);
+
+        let mut tree = original.clone();
+        let applied = patch.apply(&mut tree);
+        assert_eq!(values(&tree), vec![2, 3, 4]);
+        assert_eq!(applied.changes(), &[Change::Insert(4), Change::Remove(1)]);
+
+        applied.invert().apply(&mut tree);
+        assert_eq!(tree, original);
+
+        // the same patch replays onto another snapshot.
+        let mut other: Tree<u32> = vec![1, 5].into_iter().collect();
+        patch.apply(&mut other);
+        assert_eq!(values(&other), vec![2, 4, 5]);
+    }
+
+    /// Ordered by key alone.
+    #[derive(Debug, Clone)]
+    struct Entry(u32, &'static str);
+    impl PartialEq for Entry {
+        fn eq(&self, other: &Self) -> bool {
+            self.0 == other.0
+        }
+    }
+    impl Eq for Entry {}
+    impl PartialOrd for Entry {
+        fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+    impl Ord for Entry {
+        fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+            self.0.cmp(&other.0)
+        }
+    }
+
+    #[test]
+    fn undo_restores_stored_values() {
+        let mut tree: Tree<Entry> = vec![Entry(1, "one"), Entry(2, "two")].into_iter().collect();
+        let mut patch = Patch::new();
+        patch.remove(Entry(1, "probe"));
+
+        let applied = patch.apply(&mut tree);
+        match applied.changes() {
+            [Change::Remove(removed)] => assert_eq!(removed.1, "one"),
+            changes => panic!("unexpected changes {:?}", changes),
+        }
+        applied.invert().apply(&mut tree);
+        let payloads: Vec<&str> = tree.iter().map(|entry| entry.1).collect();
+        assert_eq!(payloads, vec!["one", "two"]);
+    }
+
+    #[test]
+    fn between_snapshots() {
+        let before: Tree<u32> = vec![50, 30, 70, 20, 40].into_iter().collect();
+        let mut after = before.clone();
+        after.insert(60);
+        after.delete(&20);
+
+        let patch = Patch::between(&before, &after);
+        assert_eq!(patch.changes(), &[Change::Remove(20), Change::Insert(60)]);
+        let mut replayed = before.clone();
+        patch.apply(&mut replayed);
+        assert_eq!(replayed, after);
+        patch.invert().apply(&mut replayed);
+        assert_eq!(replayed, before);
+    }
+}
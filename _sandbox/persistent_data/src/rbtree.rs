@@ -0,0 +1,349 @@
+//! A persistent red-black tree. Unlike `Tree`, it stays balanced whatever
+//! order values arrive in, so every operation is O(log n). Insertion is
+//! Okasaki's, and deletion is Kahrs's, which rebalances on the way back up
+//! with the same `balance` step. Each update rebuilds only the path it
+//! walks and shares everything else with the tree it started from.
+use std::cmp::Ordering;
+use std::iter::FromIterator;
+use std::rc::Rc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Color {
+    Red,
+    Black,
+}
+use Color::{Black, Red};
+
+type Link<A> = Rc<Node<A>>;
+
+#[derive(Debug)]
+enum Node<A> {
+    Leaf,
+    /// No red node has a red child, and every path from a node down to a
+    /// leaf passes through the same number of black nodes.
+    Branch(Color, Link<A>, A, Link<A>),
+}
+
+#[derive(Debug, Clone)]
+pub struct RbTree<A> {
+    root: Link<A>,
+    len: usize,
+}
+impl<A: Ord + Clone> Default for RbTree<A> {
+    fn default() -> Self {
+        RbTree::new()
+    }
+}
+impl<A: Ord + Clone> RbTree<A> {
+    pub fn new() -> Self {
+        RbTree { root: Rc::new(Node::Leaf), len: 0 }
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    pub fn get(&self, value: &A) -> Option<&A> {
+        let mut node = &*self.root;
+        while let Node::Branch(_, ref left, ref stored, ref right) = *node {
+            node = match value.cmp(stored) {
+                Ordering::Less => left,
+                Ordering::Greater => right,
+                Ordering::Equal => return Some(stored),
+            };
+        }
+        None
+    }
+    pub fn contains(&self, value: &A) -> bool {
+        self.get(value).is_some()
+    }
+    /// Adds `value`, returning whether it was new. Nothing is copied if it
+    /// was already there.
+    pub fn insert(&mut self, value: A) -> bool {
+        if self.contains(&value) {
+            return false;
+        }
+        self.root = blacken(&insert(&self.root, value));
+        self.len += 1;
+        true
+    }
+    /// Removes `value`, returning the stored value. Nothing is copied if it
+    /// is not there.
+    pub fn delete(&mut self, value: &A) -> Option<A> {
+        let removed = self.get(value)?.clone();
+        self.root = blacken(&delete(&self.root, value));
+        self.len -= 1;
+        Some(removed)
+    }
+    pub fn iter(&self) -> RbIter<'_, A> {
+        let mut iter = RbIter { stack: Vec::new() };
+        iter.push_left(&self.root);
+        iter
+    }
+}
+
+fn branch<A>(color: Color, left: Link<A>, value: A, right: Link<A>) -> Link<A> {
+    Rc::new(Node::Branch(color, left, value, right))
+}
+fn is_black<A>(node: &Link<A>) -> bool {
+    matches!(**node, Node::Branch(Black, _, _, _))
+}
+fn blacken<A: Clone>(node: &Link<A>) -> Link<A> {
+    match **node {
+        Node::Branch(Red, ref left, ref value, ref right) =>
+            branch(Black, left.clone(), value.clone(), right.clone()),
+        _ => node.clone(),
+    }
+}
+/// Turns a black node red, taking one from its black height.
+fn redden<A: Clone>(node: &Link<A>) -> Link<A> {
+    match **node {
+        Node::Branch(Black, ref left, ref value, ref right) =>
+            branch(Red, left.clone(), value.clone(), right.clone()),
+        _ => panic!("Tried to redden a node that is not black"),
+    }
+}
+
+/// Builds a black node, fixing a red child that has a red child of its own
+/// by rotating the three into a red node with two black children.
+fn balance<A: Clone>(left: Link<A>, value: A, right: Link<A>) -> Link<A> {
+    if let (Node::Branch(Red, a, x, b), Node::Branch(Red, c, z, d)) = (&*left, &*right) {
+        return branch(Red,
+            branch(Black, a.clone(), x.clone(), b.clone()),
+            value,
+            branch(Black, c.clone(), z.clone(), d.clone()));
+    }
+    if let Node::Branch(Red, ref inner, ref x, ref outer) = *left {
+        if let Node::Branch(Red, ref a, ref w, ref b) = **inner {
+            return branch(Red,
+                branch(Black, a.clone(), w.clone(), b.clone()),
+                x.clone(),
+                branch(Black, outer.clone(), value, right));
+        }
+        if let Node::Branch(Red, ref b, ref y, ref c) = **outer {
+            return branch(Red,
+                branch(Black, inner.clone(), x.clone(), b.clone()),
+                y.clone(),
+                branch(Black, c.clone(), value, right));
+        }
+    }
+    if let Node::Branch(Red, ref inner, ref z, ref outer) = *right {
+        if let Node::Branch(Red, ref c, ref w, ref d) = **outer {
+            return branch(Red,
+                branch(Black, left, value, inner.clone()),
+                z.clone(),
+                branch(Black, c.clone(), w.clone(), d.clone()));
+        }
+        if let Node::Branch(Red, ref b, ref y, ref c) = **inner {
+            return branch(Red,
+                branch(Black, left, value, b.clone()),
+                y.clone(),
+                branch(Black, c.clone(), z.clone(), outer.clone()));
+        }
+    }
+    branch(Black, left, value, right)
+}
+
+/// Inserts a value that is not in the tree. The result may have a red root
+/// with a red child, which the caller's `balance` or `blacken` fixes.
+fn insert<A: Ord + Clone>(node: &Link<A>, value: A) -> Link<A> {
+    match **node {
+        Node::Leaf => branch(Red, node.clone(), value, node.clone()),
+        Node::Branch(color, ref left, ref stored, ref right) => {
+            let (left, right) = if value < *stored {
+                (insert(left, value), right.clone())
+            }
+            else {
+                (left.clone(), insert(right, value))
+            };
+            match color {
+                Black => balance(left, stored.clone(), right),
+                Red => branch(Red, left, stored.clone(), right),
+            }
+        }
+    }
+}
+
+/// Deletes a value that is in the tree. Deleting from below a black node
+/// shortens that side, which `balance_left` and `balance_right` make up.
+fn delete<A: Ord + Clone>(node: &Link<A>, value: &A) -> Link<A> {
+    match **node {
+        Node::Leaf => panic!("Tried to delete a value that is not in the tree"),
+        Node::Branch(_, ref left, ref stored, ref right) => match value.cmp(stored) {
+            Ordering::Less if is_black(left) => balance_left(delete(left, value), stored.clone(), right.clone()),
+            Ordering::Less => branch(Red, delete(left, value), stored.clone(), right.clone()),
+            Ordering::Greater if is_black(right) => balance_right(left.clone(), stored.clone(), delete(right, value)),
+            Ordering::Greater => branch(Red, left.clone(), stored.clone(), delete(right, value)),
+            Ordering::Equal => join(left, right),
+        },
+    }
+}
+/// Builds a node whose left side is one black node shorter than its right.
+fn balance_left<A: Clone>(left: Link<A>, value: A, right: Link<A>) -> Link<A> {
+    if let Node::Branch(Red, ref a, ref x, ref b) = *left {
+        return branch(Red, branch(Black, a.clone(), x.clone(), b.clone()), value, right);
+    }
+    match *right {
+        Node::Branch(Black, ref a, ref y, ref b) =>
+            balance(left, value, branch(Red, a.clone(), y.clone(), b.clone())),
+        Node::Branch(Red, ref inner, ref z, ref c) => match **inner {
+            Node::Branch(Black, ref a, ref y, ref b) => branch(Red,
+                branch(Black, left, value, a.clone()),
+                y.clone(),
+                balance(b.clone(), z.clone(), redden(c))),
+            _ => panic!("Red-black invariant broken"),
+        },
+        Node::Leaf => panic!("Red-black invariant broken"),
+    }
+}
+/// Builds a node whose right side is one black node shorter than its left.
+fn balance_right<A: Clone>(left: Link<A>, value: A, right: Link<A>) -> Link<A> {
+    if let Node::Branch(Red, ref b, ref y, ref c) = *right {
+        return branch(Red, left, value, branch(Black, b.clone(), y.clone(), c.clone()));
+    }
+    match *left {
+        Node::Branch(Black, ref a, ref x, ref b) =>
+            balance(branch(Red, a.clone(), x.clone(), b.clone()), value, right),
+        Node::Branch(Red, ref a, ref x, ref inner) => match **inner {
+            Node::Branch(Black, ref b, ref y, ref c) => branch(Red,
+                balance(redden(a), x.clone(), b.clone()),
+                y.clone(),
+                branch(Black, c.clone(), value, right)),
+            _ => panic!("Red-black invariant broken"),
+        },
+        Node::Leaf => panic!("Red-black invariant broken"),
+    }
+}
+/// Joins the two children of a deleted node, every value of `left` being
+/// below every value of `right`.
+fn join<A: Clone>(left: &Link<A>, right: &Link<A>) -> Link<A> {
+    match (&**left, &**right) {
+        (Node::Leaf, _) => right.clone(),
+        (_, Node::Leaf) => left.clone(),
+        (Node::Branch(Red, a, x, b), Node::Branch(Red, c, y, d)) => {
+            let middle = join(b, c);
+            match *middle {
+                Node::Branch(Red, ref b, ref z, ref c) => branch(Red,
+                    branch(Red, a.clone(), x.clone(), b.clone()),
+                    z.clone(),
+                    branch(Red, c.clone(), y.clone(), d.clone())),
+                _ => branch(Red, a.clone(), x.clone(), branch(Red, middle, y.clone(), d.clone())),
+            }
+        }
+        (Node::Branch(Black, a, x, b), Node::Branch(Black, c, y, d)) => {
+            let middle = join(b, c);
+            match *middle {
+                Node::Branch(Red, ref b, ref z, ref c) => branch(Red,
+                    branch(Black, a.clone(), x.clone(), b.clone()),
+                    z.clone(),
+                    branch(Black, c.clone(), y.clone(), d.clone())),
+                _ => balance_left(a.clone(), x.clone(), branch(Black, middle, y.clone(), d.clone())),
+            }
+        }
+        (_, Node::Branch(Red, b, x, c)) => branch(Red, join(left, b), x.clone(), c.clone()),
+        (Node::Branch(Red, a, x, b), _) => branch(Red, a.clone(), x.clone(), join(b, right)),
+    }
+}
+
+impl<A: Ord + Clone> FromIterator<A> for RbTree<A> {
+    fn from_iter<I: IntoIterator<Item = A>>(iter: I) -> Self {
+        let mut tree = RbTree::new();
+        tree.extend(iter);
+        tree
+    }
+}
+impl<A: Ord + Clone> Extend<A> for RbTree<A> {
+    fn extend<I: IntoIterator<Item = A>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+/// The values of an `RbTree` in ascending order.
+#[derive(Debug, Clone)]
+pub struct RbIter<'a, A> {
+    stack: Vec<&'a Node<A>>,
+}
+impl<'a, A> RbIter<'a, A> {
+    fn push_left(&mut self, mut node: &'a Node<A>) {
+        while let Node::Branch(_, ref left, _, _) = *node {
+            self.stack.push(node);
+            node = left;
+        }
+    }
+}
+impl<'a, A> Iterator for RbIter<'a, A> {
+    type Item = &'a A;
+    fn next(&mut self) -> Option<&'a A> {
+        match *self.stack.pop()? {
+            Node::Leaf => unreachable!("only branches are pushed"),
+            Node::Branch(_, _, ref value, ref right) => {
+                self.push_left(right);
+                Some(value)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Checks the red-black invariants, returning the black height.
+    fn black_height<A>(node: &Link<A>) -> usize {
+        match **node {
+            Node::Leaf => 1,
+            Node::Branch(color, ref left, _, ref right) => {
+                if color == Red {
+                    assert!(!matches!(**left, Node::Branch(Red, _, _, _)), "red node with a red child");
+                    assert!(!matches!(**right, Node::Branch(Red, _, _, _)), "red node with a red child");
+                }
+                let height = black_height(left);
+                assert_eq!(height, black_height(right), "uneven black height");
+                height + (color == Black) as usize
+            }
+        }
+    }
+
+    #[test]
+    fn stays_balanced() {
+        let mut tree = RbTree::new();
+        for value in 0..1000u32 {
+            assert!(tree.insert(value));
+            black_height(&tree.root);
+        }
+        assert!(!tree.insert(10));
+        let snapshot = tree.clone();
+        // sorted input would make `Tree` a list, but here no path has more
+        // than about log2(1000) black nodes.
+        assert!(black_height(&tree.root) <= 11);
+
+        for value in (0..1000).filter(|value| value % 3 != 0) {
+            assert_eq!(tree.delete(&value), Some(value));
+            black_height(&tree.root);
+        }
+        assert_eq!(tree.delete(&1), None);
+        assert_eq!(tree.len(), 334);
+        assert!(tree.iter().copied().eq((0..1000).filter(|value| value % 3 == 0)));
+        assert_eq!(snapshot.len(), 1000);
+        assert!(snapshot.iter().copied().eq(0..1000));
+    }
+
+    #[test]
+    fn scrambled_order() {
+        let values: Vec<u32> = (0..500).map(|value| (value * 7919) % 500).collect();
+        let mut tree: RbTree<u32> = values.iter().copied().collect();
+        black_height(&tree.root);
+        for value in values.iter().rev().take(250) {
+            tree.delete(value);
+            black_height(&tree.root);
+        }
+        let mut remaining: Vec<u32> = values[..250].to_vec();
+        remaining.sort_unstable();
+        assert!(tree.iter().eq(remaining.iter()));
+        assert_eq!(tree.get(&remaining[0]), Some(&remaining[0]));
+        assert!(!tree.contains(&values[400]));
+    }
+}
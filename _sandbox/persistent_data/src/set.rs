@@ -0,0 +1,91 @@
+//! The operations the persistent sets have in common, so code such as a
+//! benchmark can be written once and run against each of them.
+use std::collections::BTreeSet;
+
+use crate::rbtree::RbTree;
+use crate::{Tree, TreeBox};
+
+/// A set where changes to one clone are not seen by the others. Clones of
+/// `Tree` and `RbTree` are cheap and share structure; `TreeBox` and
+/// `BTreeSet` copy everything, as a baseline to compare them with.
+pub trait PersistentSet<A>: Clone + Default {
+    /// Adds `value` if it is not already there.
+    fn insert(&mut self, value: A);
+    /// Removes `value`, returning the stored value.
+    fn delete(&mut self, value: &A) -> Option<A>;
+    fn contains(&self, value: &A) -> bool;
+}
+
+impl<A: Ord + Clone> PersistentSet<A> for Tree<A> {
+    fn insert(&mut self, value: A) {
+        Tree::insert(self, value)
+    }
+    fn delete(&mut self, value: &A) -> Option<A> {
+        Tree::delete(self, value)
+    }
+    fn contains(&self, value: &A) -> bool {
+        self.get(value).is_some()
+    }
+}
+impl<A: Ord + Clone> PersistentSet<A> for RbTree<A> {
+    fn insert(&mut self, value: A) {
+        RbTree::insert(self, value);
+    }
+    fn delete(&mut self, value: &A) -> Option<A> {
+        RbTree::delete(self, value)
+    }
+    fn contains(&self, value: &A) -> bool {
+        RbTree::contains(self, value)
+    }
+}
+impl<A: Ord + Clone> PersistentSet<A> for TreeBox<A> {
+    fn insert(&mut self, value: A) {
+        TreeBox::insert(self, value)
+    }
+    fn delete(&mut self, value: &A) -> Option<A> {
+        TreeBox::delete(self, value)
+    }
+    fn contains(&self, value: &A) -> bool {
+        self.find(value)
+    }
+}
+impl<A: Ord + Clone> PersistentSet<A> for BTreeSet<A> {
+    fn insert(&mut self, value: A) {
+        BTreeSet::insert(self, value);
+    }
+    fn delete(&mut self, value: &A) -> Option<A> {
+        BTreeSet::take(self, value)
+    }
+    fn contains(&self, value: &A) -> bool {
+        BTreeSet::contains(self, value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn snapshots_are_independent<S: PersistentSet<u32>>() {
+        let mut set = S::default();
+        for value in 0..100 {
+            set.insert((value * 37) % 100);
+        }
+        let snapshot = set.clone();
+        for value in 0..50 {
+            assert_eq!(set.delete(&value), Some(value));
+        }
+        set.insert(500);
+
+        assert!((0..50).all(|value| !set.contains(&value) && snapshot.contains(&value)));
+        assert!((50..100).all(|value| set.contains(&value) && snapshot.contains(&value)));
+        assert!(set.contains(&500) && !snapshot.contains(&500));
+    }
+
+    #[test]
+    fn every_set() {
+        snapshots_are_independent::<Tree<u32>>();
+        snapshots_are_independent::<RbTree<u32>>();
+        snapshots_are_independent::<TreeBox<u32>>();
+        snapshots_are_independent::<BTreeSet<u32>>();
+    }
+}
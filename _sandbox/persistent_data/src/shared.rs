@@ -0,0 +1,122 @@
+//! `Shared`, a copy-on-write handle for building persistent structures.
+//! Reads go straight through the `Rc`, and writes copy the value first if
+//! any other handle can see it: the `Rc::make_mut` pattern, which `List`
+//! uses for each of its tails.
+use std::ops::Deref;
+use std::rc::Rc;
+
+/// A value that is cheap to clone and copied only when written while
+/// shared.
+#[derive(Debug)]
+pub struct Shared<T>(Rc<T>);
+impl<T> Clone for Shared<T> {
+    fn clone(&self) -> Self {
+        Shared(self.0.clone())
+    }
+}
+impl<T: Default> Default for Shared<T> {
+    fn default() -> Self {
+        Shared::new(T::default())
+    }
+}
+impl<T> Shared<T> {
+    pub fn new(value: T) -> Self {
+        Shared(Rc::new(value))
+    }
+    /// Whether the two handles point at the same value, so one can stand in
+    /// for the other without comparing them.
+    pub fn ptr_eq(&self, other: &Shared<T>) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+    /// The address of the value, the same for every handle to it.
+    pub fn as_ptr(&self) -> *const T {
+        Rc::as_ptr(&self.0)
+    }
+    /// Whether this is the only handle to the value, so writing to it will
+    /// not copy it.
+    pub fn is_unique(&self) -> bool {
+        Rc::strong_count(&self.0) == 1
+    }
+    /// A new value computed from this one.
+    pub fn map<U, F: FnOnce(&T) -> U>(&self, func: F) -> Shared<U> {
+        Shared::new(func(&self.0))
+    }
+}
+impl<T: Clone> Shared<T> {
+    /// Write access to the value, copying it first if it is shared.
+    pub fn make_mut(&mut self) -> &mut T {
+        Rc::make_mut(&mut self.0)
+    }
+    /// A handle to a copy of the value changed by `func`, leaving this one
+    /// as it was.
+    pub fn update<F: FnOnce(&mut T)>(&self, func: F) -> Shared<T> {
+        let mut updated = self.clone();
+        func(updated.make_mut());
+        updated
+    }
+    /// The value, copied only if other handles still point to it.
+    pub fn into_inner(self) -> T {
+        Rc::unwrap_or_clone(self.0)
+    }
+}
+impl<T> Deref for Shared<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+impl<T> AsRef<T> for Shared<T> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+impl<T> From<T> for Shared<T> {
+    fn from(value: T) -> Self {
+        Shared::new(value)
+    }
+}
+impl<T> From<Rc<T>> for Shared<T> {
+    fn from(value: Rc<T>) -> Self {
+        Shared(value)
+    }
+}
+/// Handles to the same value are equal without looking at it.
+impl<T: PartialEq> PartialEq for Shared<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr_eq(other) || *self.0 == *other.0
+    }
+}
+impl<T: Eq> Eq for Shared<T> {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn copies_on_shared_write() {
+        let mut first = Shared::new(vec![1, 2, 3]);
+        first.make_mut().push(4);
+        assert!(first.is_unique());
+
+        let second = first.clone();
+        assert!(first.ptr_eq(&second));
+        first.make_mut().push(5);
+        assert!(!first.ptr_eq(&second));
+        assert_eq!(*first, vec![1, 2, 3, 4, 5]);
+        assert_eq!(*second, vec![1, 2, 3, 4]);
+
+        let updated = second.update(|elems| elems.clear());
+        assert!(updated.is_empty());
+        assert_eq!(second.len(), 4);
+        assert_eq!(second.map(|elems| elems.len()), Shared::new(4));
+        assert_eq!(second.into_inner(), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn equal_by_pointer() {
+        // NaN is not equal to itself, so only the pointer check can pass.
+        let nan = Shared::new(f64::NAN);
+        assert!(nan == nan.clone());
+        assert!(nan != Shared::new(f64::NAN));
+    }
+}
@@ -0,0 +1,63 @@
+//! The operations the stacks have in common, so code such as a benchmark
+//! can be written once and run against each of them.
+use crate::{List, ListBox};
+
+/// A stack where changes to one clone are not seen by the others. Clones of
+/// `List` are cheap and share structure; `ListBox` and `Vec` copy
+/// everything, as a baseline to compare it with.
+pub trait PersistentStack<A>: Clone + Default {
+    fn push(&mut self, value: A);
+    /// Removes and returns the value pushed last.
+    fn pop(&mut self) -> Option<A>;
+}
+
+impl<A: Clone> PersistentStack<A> for List<A> {
+    fn push(&mut self, value: A) {
+        self.cons(value)
+    }
+    fn pop(&mut self) -> Option<A> {
+        self.uncons()
+    }
+}
+impl<A: Clone> PersistentStack<A> for ListBox<A> {
+    fn push(&mut self, value: A) {
+        self.cons(value)
+    }
+    fn pop(&mut self) -> Option<A> {
+        self.uncons()
+    }
+}
+impl<A: Clone> PersistentStack<A> for Vec<A> {
+    fn push(&mut self, value: A) {
+        Vec::push(self, value)
+    }
+    fn pop(&mut self) -> Option<A> {
+        Vec::pop(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn snapshots_are_independent<S: PersistentStack<u32>>() {
+        let mut stack = S::default();
+        for value in 0..10 {
+            stack.push(value);
+        }
+        let mut snapshot = stack.clone();
+        assert_eq!(stack.pop(), Some(9));
+        stack.push(100);
+
+        assert_eq!(stack.pop(), Some(100));
+        assert_eq!(snapshot.pop(), Some(9));
+        assert_eq!(stack.pop(), snapshot.pop());
+    }
+
+    #[test]
+    fn every_stack() {
+        snapshots_are_independent::<List<u32>>();
+        snapshots_are_independent::<ListBox<u32>>();
+        snapshots_are_independent::<Vec<u32>>();
+    }
+}
@@ -0,0 +1,208 @@
+//! A lazy cons list. Each tail is computed the first time it is needed and
+//! then remembered, so clones of a stream share the work as well as the
+//! nodes, and a stream may go on forever.
+use std::cell::RefCell;
+use std::rc::Rc;
+
+type Node<A> = Option<(A, Stream<A>)>;
+
+enum Thunk<A> {
+    Delayed(Box<dyn FnOnce() -> Node<A>>),
+    /// Being computed, so asking for it again would never finish.
+    Forcing,
+    Forced(Node<A>),
+}
+
+/// A lazy, memoised list. Cloning it is O(1) and shares the thunks.
+pub struct Stream<A>(Rc<RefCell<Thunk<A>>>);
+impl<A> Clone for Stream<A> {
+    fn clone(&self) -> Self {
+        Stream(self.0.clone())
+    }
+}
+/// Unlinks the forced nodes no other stream shares one at a time, so a
+/// long stream doesn't overflow the stack dropping each tail in turn.
+impl<A> Drop for Stream<A> {
+    fn drop(&mut self) {
+        let mut next = self.take_tail();
+        while let Some(stream) = next {
+            next = stream.take_tail();
+        }
+    }
+}
+impl<A> Stream<A> {
+    /// Moves the tail out of a forced node only this stream refers to.
+    fn take_tail(&self) -> Option<Stream<A>> {
+        if Rc::strong_count(&self.0) != 1 {
+            return None;
+        }
+        let mut thunk = self.0.try_borrow_mut().ok()?;
+        match std::mem::replace(&mut *thunk, Thunk::Forcing) {
+            Thunk::Forced(Some((_, tail))) => Some(tail),
+            other => {
+                *thunk = other;
+                None
+            }
+        }
+    }
+}
+impl<A: Clone + 'static> Default for Stream<A> {
+    fn default() -> Self {
+        Stream::empty()
+    }
+}
+impl<A: Clone + 'static> Stream<A> {
+    pub fn empty() -> Self {
+        Stream(Rc::new(RefCell::new(Thunk::Forced(None))))
+    }
+    pub fn cons(head: A, tail: Stream<A>) -> Self {
+        Stream(Rc::new(RefCell::new(Thunk::Forced(Some((head, tail))))))
+    }
+    /// A stream whose first node is only computed, by `func`, when needed.
+    pub fn lazy<F: FnOnce() -> Option<(A, Stream<A>)> + 'static>(func: F) -> Self {
+        Stream(Rc::new(RefCell::new(Thunk::Delayed(Box::new(func)))))
+    }
+    /// The infinite stream `seed`, `func(seed)`, `func(func(seed))`, ...
+    pub fn iterate<F: Fn(&A) -> A + 'static>(seed: A, func: F) -> Self {
+        Stream::iterate_rc(seed, Rc::new(func))
+    }
+    /// The values produced by `func` from each state in turn, until it
+    /// returns `None`.
+    pub fn unfold<S: 'static, F: Fn(S) -> Option<(A, S)> + 'static>(state: S, func: F) -> Self {
+        Stream::unfold_rc(state, Rc::new(func))
+    }
+    /// The head and tail, computing them if they have not been already.
+    pub fn uncons(&self) -> Option<(A, Stream<A>)> {
+        self.force();
+        match *self.0.borrow() {
+            Thunk::Forced(ref node) => node.clone(),
+            _ => unreachable!("the stream was just forced"),
+        }
+    }
+    pub fn head(&self) -> Option<A> {
+        self.uncons().map(|(head, _)| head)
+    }
+    pub fn is_empty(&self) -> bool {
+        self.uncons().is_none()
+    }
+    /// The first `count` values, lazily.
+    pub fn take(&self, count: usize) -> Stream<A> {
+        let source = self.clone();
+        Stream::lazy(move || {
+            if count == 0 {
+                return None;
+            }
+            source.uncons().map(|(head, tail)| (head, tail.take(count - 1)))
+        })
+    }
+    /// Applies `func` to each value, lazily. Each value is mapped at most
+    /// once, however many times the result is read.
+    pub fn map<B: Clone + 'static, F: Fn(&A) -> B + 'static>(&self, func: F) -> Stream<B> {
+        self.map_rc(Rc::new(func))
+    }
+    pub fn iter(&self) -> StreamIter<A> {
+        StreamIter(self.clone())
+    }
+
+    fn force(&self) {
+        let func = {
+            let mut thunk = self.0.borrow_mut();
+            match std::mem::replace(&mut *thunk, Thunk::Forcing) {
+                Thunk::Delayed(func) => func,
+                Thunk::Forcing => panic!("Stream depends on itself"),
+                forced => {
+                    *thunk = forced;
+                    return;
+                }
+            }
+        };
+        // the borrow is released while `func` runs, as it may force other
+        // streams.
+        let node = func();
+        *self.0.borrow_mut() = Thunk::Forced(node);
+    }
+    fn iterate_rc(seed: A, func: Rc<dyn Fn(&A) -> A>) -> Self {
+        Stream::lazy(move || {
+            let next = func(&seed);
+            Some((seed, Stream::iterate_rc(next, func)))
+        })
+    }
+    fn unfold_rc<S: 'static>(state: S, func: Rc<dyn Fn(S) -> Option<(A, S)>>) -> Self {
+        Stream::lazy(move || {
+            let (value, next) = func(state)?;
+            Some((value, Stream::unfold_rc(next, func)))
+        })
+    }
+    fn map_rc<B: Clone + 'static>(&self, func: Rc<dyn Fn(&A) -> B>) -> Stream<B> {
+        let source = self.clone();
+        Stream::lazy(move || {
+            source.uncons().map(|(head, tail)| (func(&head), tail.map_rc(func)))
+        })
+    }
+}
+
+/// The values of a `Stream`, forcing it as it goes.
+pub struct StreamIter<A>(Stream<A>);
+impl<A: Clone + 'static> Iterator for StreamIter<A> {
+    type Item = A;
+    fn next(&mut self) -> Option<A> {
+        let (head, tail) = self.0.uncons()?;
+        self.0 = tail;
+        Some(head)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn infinite_streams() {
+        let naturals = Stream::iterate(0u64, |n| n + 1);
+        let squares = naturals.map(|n| n * n);
+        assert_eq!(squares.take(5).iter().collect::<Vec<_>>(), vec![0, 1, 4, 9, 16]);
+
+        let fibonacci = Stream::unfold((0u64, 1u64), |(a, b)| Some((a, (b, a + b))));
+        assert_eq!(fibonacci.iter().nth(50), Some(12_586_269_025));
+
+        let countdown = Stream::unfold(3, |n| if n == 0 { None } else { Some((n, n - 1)) });
+        assert_eq!(countdown.iter().collect::<Vec<_>>(), vec![3, 2, 1]);
+        assert!(Stream::<u32>::empty().is_empty());
+        assert_eq!(Stream::cons(1, Stream::empty()).head(), Some(1));
+    }
+
+    #[test]
+    fn values_are_computed_once() {
+        let calls = Rc::new(Cell::new(0));
+        let counted = calls.clone();
+        let stream = Stream::iterate(0u32, |n| n + 1).map(move |&n| {
+            counted.set(counted.get() + 1);
+            n * 10
+        });
+        let copy = stream.clone();
+
+        assert_eq!(calls.get(), 0);
+        assert_eq!(stream.take(10).iter().last(), Some(90));
+        assert_eq!(calls.get(), 10);
+        assert_eq!(copy.take(10).iter().sum::<u32>(), 450);
+        assert_eq!(calls.get(), 10);
+    }
+
+    #[test]
+    fn drop_long_stream() {
+        let mut built = Stream::empty();
+        for n in 0..1_000_000u32 {
+            built = Stream::cons(n, built);
+        }
+        drop(built);
+
+        let forced = Stream::iterate(0u32, |n| n + 1);
+        assert_eq!(forced.iter().nth(999_999), Some(999_999));
+        // a clone keeps the nodes alive when the first one goes.
+        let shared = forced.clone();
+        drop(forced);
+        assert_eq!(shared.take(3).iter().collect::<Vec<_>>(), vec![0, 1, 2]);
+        drop(shared);
+    }
+}
@@ -0,0 +1,236 @@
+//! Versions of `List` and `Tree` that share their nodes through `Arc`, so
+//! snapshots can be sent to other threads. The `Rc` versions at the crate
+//! root avoid the atomic reference counting and remain the default.
+use std::sync::Arc;
+
+/// A persistent cons list, as `crate::List`.
+#[derive(Debug, Clone)]
+pub enum List<A> {
+    Nil,
+    /// The head, the tail, and the length of the list from here.
+    Cons(A, Arc<List<A>>, usize),
+}
+impl<A: Clone> Default for List<A> {
+    fn default() -> Self {
+        List::new()
+    }
+}
+impl<A: Clone> List<A> {
+    pub fn new() -> Self {
+        List::Nil
+    }
+    /// The number of elements, kept in each node so this is O(1).
+    pub fn len(&self) -> usize {
+        match *self {
+            List::Nil => 0,
+            List::Cons(_, _, len) => len,
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    pub fn cons(&mut self, elem: A) {
+        let tail = std::mem::take(self);
+        let len = tail.len() + 1;
+        let mut list = List::Cons(elem, Arc::new(tail), len);
+        std::mem::swap(self, &mut list)
+    }
+    pub fn uncons(&mut self) -> Option<A> {
+        let list = std::mem::take(self);
+        match list {
+            List::Nil => None,
+            List::Cons(elem, mut tail, _) => {
+                std::mem::swap(self, Arc::make_mut(&mut tail));
+                Some(elem)
+            }
+        }
+    }
+    /// Puts `other` on the end of this list. The nodes of `other` are shared
+    /// rather than copied, so only this list's spine is rebuilt.
+    pub fn append(&mut self, other: &List<A>) {
+        let mut elems = Vec::with_capacity(self.len());
+        while let Some(elem) = self.uncons() {
+            elems.push(elem);
+        }
+        *self = other.clone();
+        for elem in elems.into_iter().rev() {
+            self.cons(elem);
+        }
+    }
+    /// Reverses the list in place.
+    pub fn rev(&mut self) {
+        let mut reversed = List::new();
+        while let Some(elem) = self.uncons() {
+            reversed.cons(elem);
+        }
+        *self = reversed;
+    }
+}
+
+/// A persistent binary search tree, as `crate::Tree`.
+#[derive(Debug, Clone)]
+pub enum Tree<A> {
+    Leaf,
+    Node(Arc<Tree<A>>, A, Arc<Tree<A>>),
+}
+impl<A: Ord + Clone> Default for Tree<A> {
+    fn default() -> Self {
+        Tree::new()
+    }
+}
+impl<A: Ord + Clone> Tree<A> {
+    pub fn new() -> Self {
+        Tree::Leaf
+    }
+    pub fn singleton(value: A) -> Self {
+        Tree::Node(Arc::new(Tree::Leaf), value, Arc::new(Tree::Leaf))
+    }
+    pub fn insert(&mut self, input: A) {
+        match *self {
+            Tree::Leaf => *self = Tree::singleton(input),
+            Tree::Node(ref mut left, ref value, ref mut right) => {
+                if &input < value {
+                    Arc::make_mut(left).insert(input);
+                } else if &input > value {
+                    Arc::make_mut(right).insert(input);
+                }
+            }
+        }
+    }
+    pub fn find (&self, elem: &A) -> bool {
+        match *self {
+            Tree::Leaf => false,
+            Tree::Node(ref left, ref value, ref right) => {
+                if elem < value {
+                    left.find(elem)
+                } else if elem > value {
+                    right.find(elem)
+                } else {
+                    true
+                }
+            }
+        }
+    }
+    pub fn remove_smallest(&mut self) -> Option<A> {
+        let node = std::mem::take(self);
+        match node {
+            Tree::Leaf => None,
+            Tree::Node(mut left, value, mut right) => {
+                if let Some(leftmost) = Arc::make_mut(&mut left).remove_smallest() {
+                    *self = Tree::Node(left, value, right);
+                    Some(leftmost)
+                }
+                else {
+                    std::mem::swap(self, Arc::make_mut(&mut right));
+                    Some(value)
+                }
+            }
+        }
+    }
+    pub fn remove_largest(&mut self) -> Option<A> {
+        let node = std::mem::take(self);
+        match node {
+            Tree::Leaf => None,
+            Tree::Node(mut left, value, mut right) => {
+                if let Some(rightmost) = Arc::make_mut(&mut right).remove_largest() {
+                    *self = Tree::Node(left, value, right);
+                    Some(rightmost)
+                }
+                else {
+                    std::mem::swap(self, Arc::make_mut(&mut left));
+                    Some(value)
+                }
+            }
+        }
+    }
+    /// Removes `elem` from the tree, returning the stored value. Only the
+    /// nodes on the path to `elem` are copied, and nothing is copied if it
+    /// is not there.
+    pub fn delete(&mut self, elem: &A) -> Option<A> {
+        if self.find(elem) {
+            Some(self.delete_present(elem))
+        }
+        else {
+            None
+        }
+    }
+    pub fn get_smallest(&self) -> Option<&A> {
+        match *self {
+            Tree::Leaf => None,
+            Tree::Node(ref left, ref value, _) => left.get_smallest().or(Some(value)),
+        }
+    }
+    pub fn get_largest(&self) -> Option<&A> {
+        match *self {
+            Tree::Leaf => None,
+            Tree::Node(_, ref value, ref right) => right.get_largest().or(Some(value)),
+        }
+    }
+    /// Calls `func` on each value in ascending order.
+    pub fn for_each<F: FnMut(&A)>(&self, func: &mut F) {
+        match *self {
+            Tree::Leaf => (),
+            Tree::Node(ref left, ref value, ref right) => {
+                left.for_each(func);
+                func(value);
+                right.for_each(func);
+            }
+        }
+    }
+
+    fn delete_present(&mut self, elem: &A) -> A {
+        let node = std::mem::take(self);
+        match node {
+            Tree::Leaf => panic!("Tried to delete a value that is not in the tree"),
+            Tree::Node(mut left, value, mut right) => {
+                if elem < &value {
+                    let removed = Arc::make_mut(&mut left).delete_present(elem);
+                    *self = Tree::Node(left, value, right);
+                    removed
+                } else if elem > &value {
+                    let removed = Arc::make_mut(&mut right).delete_present(elem);
+                    *self = Tree::Node(left, value, right);
+                    removed
+                } else {
+                    match Arc::make_mut(&mut right).remove_smallest() {
+                        Some(smallest) => *self = Tree::Node(left, smallest, right),
+                        None => std::mem::swap(self, Arc::make_mut(&mut left)),
+                    }
+                    value
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn snapshots_cross_threads () {
+        let mut list = List::new();
+        let mut tree = Tree::new();
+        for value in 0..100u32 {
+            list.cons(value);
+            tree.insert((value * 37) % 100);
+        }
+        let (list_snapshot, tree_snapshot) = (list.clone(), tree.clone());
+        let reader = thread::spawn(move || {
+            let mut values = Vec::new();
+            tree_snapshot.for_each(&mut |&value| values.push(value));
+            (list_snapshot.len(), values)
+        });
+
+        list.rev();
+        for value in 0..50 {
+            tree.delete(&value);
+        }
+        let (len, values) = reader.join().unwrap();
+        assert_eq!(len, 100);
+        assert_eq!(values, (0..100).collect::<Vec<_>>());
+        assert_eq!(list.uncons(), Some(0));
+        assert_eq!(tree.get_smallest(), Some(&50));
+    }
+}
@@ -0,0 +1,230 @@
+//! Serialization that keeps structural sharing. A set of `Tree`s or `List`s
+//! is written as one table of nodes, where each node refers to its children
+//! by their position in the table. A node shared by several snapshots is
+//! written once, and reading the table back shares it again.
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use serde::de::{Deserializer, Error};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+
+use crate::shared::Shared;
+use crate::{List, Tree};
+
+/// The nodes of some trees, each after its children, and the position of
+/// each tree's root. `None` stands for a leaf.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TreeTable<A> {
+    nodes: Vec<(Option<usize>, A, Option<usize>)>,
+    roots: Vec<Option<usize>>,
+}
+impl<A: Ord + Clone> TreeTable<A> {
+    pub fn new(trees: &[Tree<A>]) -> Self {
+        let mut table = TreeTable {
+            nodes: Vec::new(),
+            roots: Vec::new(),
+        };
+        let mut seen = HashMap::new();
+        for tree in trees {
+            let root = table.add(tree, &mut seen);
+            table.roots.push(root);
+        }
+        table
+    }
+    /// The number of distinct nodes in the table.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+    /// Rebuilds the trees, sharing each node between all the trees that
+    /// refer to it. Fails if a node refers to one that does not come before
+    /// it.
+    pub fn into_trees(self) -> Result<Vec<Tree<A>>, String> {
+        let leaf = Rc::new(Tree::Leaf);
+        let mut built: Vec<Rc<Tree<A>>> = Vec::with_capacity(self.nodes.len());
+        let child = |built: &Vec<Rc<Tree<A>>>, index: Option<usize>| match index {
+            None => Ok(leaf.clone()),
+            Some(index) => built.get(index).cloned()
+                .ok_or_else(|| format!("node {} refers to a later node {}", built.len(), index)),
+        };
+        for (left, value, right) in self.nodes {
+            let node = Tree::Node(child(&built, left)?, value, child(&built, right)?);
+            built.push(Rc::new(node));
+        }
+        self.roots.into_iter()
+            .map(|root| child(&built, root).map(Rc::unwrap_or_clone))
+            .collect()
+    }
+
+    fn add(&mut self, tree: &Tree<A>, seen: &mut HashMap<*const Tree<A>, usize>) -> Option<usize> {
+        match *tree {
+            Tree::Leaf => None,
+            Tree::Node(ref left, ref value, ref right) => {
+                let key = tree as *const Tree<A>;
+                if let Some(&index) = seen.get(&key) {
+                    return Some(index);
+                }
+                let left = self.add(left, seen);
+                let right = self.add(right, seen);
+                self.nodes.push((left, value.clone(), right));
+                seen.insert(key, self.nodes.len() - 1);
+                Some(self.nodes.len() - 1)
+            }
+        }
+    }
+}
+
+/// The nodes of some lists, each after its tail, and the position of each
+/// list's head. `None` stands for the empty list.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListTable<A> {
+    nodes: Vec<(A, Option<usize>)>,
+    heads: Vec<Option<usize>>,
+}
+impl<A: Clone> ListTable<A> {
+    pub fn new(lists: &[List<A>]) -> Self {
+        let mut table = ListTable {
+            nodes: Vec::new(),
+            heads: Vec::new(),
+        };
+        let mut seen = HashMap::new();
+        for list in lists {
+            let head = table.add(list, &mut seen);
+            table.heads.push(head);
+        }
+        table
+    }
+    /// The number of distinct nodes in the table.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+    /// Rebuilds the lists, sharing each node between all the lists that
+    /// refer to it. Fails if a node refers to one that does not come before
+    /// it.
+    pub fn into_lists(self) -> Result<Vec<List<A>>, String> {
+        let nil = Shared::new(List::Nil);
+        let mut built: Vec<Shared<List<A>>> = Vec::with_capacity(self.nodes.len());
+        let tail = |built: &Vec<Shared<List<A>>>, index: Option<usize>| match index {
+            None => Ok(nil.clone()),
+            Some(index) => built.get(index).cloned()
+                .ok_or_else(|| format!("node {} refers to a later node {}", built.len(), index)),
+        };
+        for (elem, next) in self.nodes {
+            let next = tail(&built, next)?;
+            let len = next.len() + 1;
+            built.push(Shared::new(List::Cons(elem, next, len)));
+        }
+        self.heads.into_iter()
+            .map(|head| tail(&built, head).map(Shared::into_inner))
+            .collect()
+    }
+
+    /// Adds the list's nodes from the end, so every node follows its tail.
+    fn add(&mut self, list: &List<A>, seen: &mut HashMap<*const List<A>, usize>) -> Option<usize> {
+        let mut missing = Vec::new();
+        let mut node = list;
+        let mut next = None;
+        while let List::Cons(ref elem, ref tail, _) = *node {
+            if let Some(&index) = seen.get(&(node as *const List<A>)) {
+                next = Some(index);
+                break;
+            }
+            missing.push((node as *const List<A>, elem));
+            node = tail;
+        }
+        for (key, elem) in missing.into_iter().rev() {
+            self.nodes.push((elem.clone(), next));
+            next = Some(self.nodes.len() - 1);
+            seen.insert(key, self.nodes.len() - 1);
+        }
+        next
+    }
+}
+
+/// A lone tree is written as a table holding just that tree.
+impl<A: Ord + Clone + Serialize> Serialize for Tree<A> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        TreeTable::new(std::slice::from_ref(self)).serialize(serializer)
+    }
+}
+impl<'de, A: Ord + Clone + Deserialize<'de>> Deserialize<'de> for Tree<A> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut trees = TreeTable::deserialize(deserializer)?.into_trees().map_err(D::Error::custom)?;
+        match trees.len() {
+            1 => Ok(trees.pop().unwrap()),
+            len => Err(D::Error::custom(format!("expected one tree, found {}", len))),
+        }
+    }
+}
+impl<A: Clone + Serialize> Serialize for List<A> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ListTable::new(std::slice::from_ref(self)).serialize(serializer)
+    }
+}
+impl<'de, A: Clone + Deserialize<'de>> Deserialize<'de> for List<A> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut lists = ListTable::deserialize(deserializer)?.into_lists().map_err(D::Error::custom)?;
+        match lists.len() {
+            1 => Ok(lists.pop().unwrap()),
+            len => Err(D::Error::custom(format!("expected one list, found {}", len))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn snapshots_are_written_once() {
+        let mut tree = Tree::new();
+        for value in 0..100u32 {
+            tree.insert((value * 37) % 100);
+        }
+        let mut snapshots = vec![tree.clone()];
+        for value in 100..110 {
+            tree.insert(value);
+            snapshots.push(tree.clone());
+        }
+
+        let table = TreeTable::new(&snapshots);
+        // the first snapshot, then each insert adds the path to its value.
+        assert!(table.len() < 100 + 10 * 20);
+        let json = serde_json::to_string(&table).unwrap();
+        let table: TreeTable<u32> = serde_json::from_str(&json).unwrap();
+        let restored = table.into_trees().unwrap();
+
+        assert_eq!(restored.len(), snapshots.len());
+        for (restored, snapshot) in restored.iter().zip(&snapshots) {
+            assert!(restored.iter().eq(snapshot.iter()));
+        }
+        let original = snapshots[0].sharing_stats(&snapshots[10]);
+        assert_eq!(restored[0].sharing_stats(&restored[10]), original);
+    }
+
+    #[test]
+    fn lists_share_tails() {
+        let mut list: List<u32> = (0..50).collect();
+        let first = list.clone();
+        list.cons(100);
+        let table = ListTable::new(&[first, list.clone()]);
+        // the heads of the lists are not behind an `Rc`, so `first`'s head is
+        // a copy of the node after 100, sharing only its tail.
+        assert_eq!(table.len(), 52);
+
+        let json = serde_json::to_string(&list).unwrap();
+        let mut restored: List<u32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.len(), 51);
+        assert_eq!(restored.uncons(), Some(100));
+        assert_eq!(restored.uncons(), Some(0));
+
+        let table: TreeTable<u32> = serde_json::from_str(r#"{"nodes":[[1,5,null]],"roots":[0]}"#).unwrap();
+        assert!(table.into_trees().is_err());
+    }
+}
@@ -0,0 +1,251 @@
+//! Branching history for any value that is cheap to clone, in the manner of
+//! git. Versions form a DAG: each has the version it was committed on top
+//! of as a parent, and a merge has two. As with `History`, the persistent
+//! structures make keeping every version cheap.
+use std::collections::{BTreeMap, HashSet, VecDeque};
+
+#[derive(Debug, Clone)]
+struct Version<T> {
+    value: T,
+    parents: Vec<usize>,
+}
+
+/// Named branches of committed versions, and a working value on the branch
+/// that is checked out.
+#[derive(Debug, Clone)]
+pub struct VersionTree<T> {
+    /// Every committed version, each after its parents.
+    versions: Vec<Version<T>>,
+    /// The latest version on each branch.
+    branches: BTreeMap<String, usize>,
+    current: String,
+    working: T,
+    /// Whether the working value may have changed since it was last set
+    /// from a version.
+    dirty: bool,
+}
+impl<T: Clone> VersionTree<T> {
+    /// Starts a tree whose first version is `value`, on a branch called
+    /// `main`.
+    pub fn new(value: T) -> Self {
+        let mut branches = BTreeMap::new();
+        branches.insert("main".to_string(), 0);
+        VersionTree {
+            versions: vec![Version { value: value.clone(), parents: Vec::new() }],
+            branches,
+            current: "main".to_string(),
+            working: value,
+            dirty: false,
+        }
+    }
+    pub fn get(&self) -> &T {
+        &self.working
+    }
+    /// The working value. Changes are kept by `commit` and `merge`, and
+    /// lost by `checkout`. Once this has been called the working value
+    /// counts as changed, whether or not it was.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.dirty = true;
+        &mut self.working
+    }
+    /// The name of the branch that is checked out.
+    pub fn current_branch(&self) -> &str {
+        &self.current
+    }
+    pub fn branches(&self) -> impl Iterator<Item = &str> {
+        self.branches.keys().map(|name| name.as_str())
+    }
+    /// The version at the end of the current branch.
+    pub fn head(&self) -> usize {
+        self.branches[&self.current]
+    }
+    pub fn version(&self, version: usize) -> Option<&T> {
+        self.versions.get(version).map(|version| &version.value)
+    }
+    /// The versions `version` was made from: none for the first version,
+    /// two for a merge, and one otherwise.
+    pub fn parents(&self, version: usize) -> &[usize] {
+        &self.versions[version].parents
+    }
+    /// Records the working value as a new version at the end of the
+    /// current branch, returning the version.
+    pub fn commit(&mut self) -> usize {
+        let parents = vec![self.head()];
+        self.push(self.working.clone(), parents)
+    }
+    /// Starts a branch called `name` at the current head, without checking
+    /// it out. Returns false if there is already a branch of that name.
+    pub fn branch(&mut self, name: &str) -> bool {
+        if self.branches.contains_key(name) {
+            return false;
+        }
+        let head = self.head();
+        self.branches.insert(name.to_string(), head);
+        true
+    }
+    /// Switches to the branch `name`, replacing the working value with its
+    /// head. Returns false if there is no such branch.
+    pub fn checkout(&mut self, name: &str) -> bool {
+        match self.branches.get(name) {
+            Some(&head) => {
+                self.current = name.to_string();
+                self.working = self.versions[head].value.clone();
+                self.dirty = false;
+                true
+            }
+            None => false,
+        }
+    }
+    /// Merges the branch `name` into the current one, and checks out the
+    /// result. If either branch already contains the other, the current
+    /// branch moves to the later head; otherwise `resolver` is called with
+    /// the versions at their common ancestor, at the current head and at
+    /// `name`'s head, and what it returns is committed with both heads as
+    /// parents. A working value changed since the last commit is committed
+    /// first, so the merge includes it. Returns false if there is no such
+    /// branch.
+    pub fn merge<F: FnOnce(&T, &T, &T) -> T>(&mut self, name: &str, resolver: F) -> bool {
+        let theirs = match self.branches.get(name) {
+            Some(&theirs) => theirs,
+            None => return false,
+        };
+        if self.dirty {
+            self.commit();
+        }
+        let ours = self.head();
+        let base = self.common_ancestor(ours, theirs);
+        if base == theirs {
+            // nothing to do.
+        }
+        else if base == ours {
+            self.branches.insert(self.current.clone(), theirs);
+        }
+        else {
+            let value = resolver(
+                &self.versions[base].value,
+                &self.versions[ours].value,
+                &self.versions[theirs].value,
+            );
+            self.push(value, vec![ours, theirs]);
+        }
+        self.working = self.versions[self.head()].value.clone();
+        self.dirty = false;
+        true
+    }
+
+    fn push(&mut self, value: T, parents: Vec<usize>) -> usize {
+        self.versions.push(Version { value, parents });
+        let version = self.versions.len() - 1;
+        self.branches.insert(self.current.clone(), version);
+        self.dirty = false;
+        version
+    }
+    /// The nearest version that both `ours` and `theirs` descend from. All
+    /// versions descend from the first, so there always is one.
+    fn common_ancestor(&self, ours: usize, theirs: usize) -> usize {
+        let ancestors = self.ancestors(ours).collect::<HashSet<_>>();
+        self.ancestors(theirs)
+            .find(|version| ancestors.contains(version))
+            .expect("Versions have no common ancestor")
+    }
+    /// `version` and the versions it descends from, nearest first.
+    fn ancestors(&self, version: usize) -> impl Iterator<Item = usize> + '_ {
+        let mut queue = VecDeque::new();
+        queue.push_back(version);
+        let mut seen = HashSet::new();
+        std::iter::from_fn(move || loop {
+            let version = queue.pop_front()?;
+            if seen.insert(version) {
+                queue.extend(self.versions[version].parents.iter().copied());
+                return Some(version);
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Tree;
+
+    fn values(tree: &Tree<u32>) -> Vec<u32> {
+        tree.iter().copied().collect()
+    }
+    /// Keeps `ours`, and applies the changes `theirs` made to `base`.
+    fn three_way(base: &Tree<u32>, ours: &Tree<u32>, theirs: &Tree<u32>) -> Tree<u32> {
+        ours.union(&theirs.difference(base)).difference(&base.difference(theirs))
+    }
+
+    #[test]
+    fn branch_and_checkout() {
+        let mut versions = VersionTree::new(Tree::new());
+        versions.get_mut().insert(1);
+        let first = versions.commit();
+
+        assert!(versions.branch("feature"));
+        assert!(!versions.branch("feature"));
+        assert!(versions.checkout("feature"));
+        versions.get_mut().insert(2);
+        versions.commit();
+        versions.get_mut().insert(3);
+
+        assert!(versions.checkout("main"));
+        assert_eq!(values(versions.get()), vec![1]);
+        assert!(versions.checkout("feature"));
+        assert_eq!(values(versions.get()), vec![1, 2]);
+        assert!(!versions.checkout("missing"));
+
+        assert_eq!(versions.branches().collect::<Vec<_>>(), vec!["feature", "main"]);
+        assert_eq!(versions.current_branch(), "feature");
+        assert_eq!(versions.parents(versions.head()), &[first]);
+        assert_eq!(versions.version(0).map(values), Some(Vec::new()));
+    }
+
+    #[test]
+    fn merges() {
+        let mut versions = VersionTree::new(vec![1, 2, 3].into_iter().collect::<Tree<u32>>());
+        versions.branch("feature");
+        versions.checkout("feature");
+        versions.get_mut().insert(4);
+        versions.commit();
+
+        // main has not moved, so it fast-forwards without resolving.
+        versions.checkout("main");
+        assert!(versions.merge("feature", |_, _, _| panic!("Nothing to resolve")));
+        assert_eq!(values(versions.get()), vec![1, 2, 3, 4]);
+
+        versions.get_mut().delete(&1);
+        versions.commit();
+        versions.checkout("feature");
+        versions.get_mut().insert(5);
+        versions.get_mut().delete(&2);
+        versions.commit();
+
+        versions.checkout("main");
+        assert!(versions.merge("feature", three_way));
+        assert_eq!(values(versions.get()), vec![3, 4, 5]);
+        assert_eq!(versions.parents(versions.head()).len(), 2);
+
+        // feature is now part of main, so merging it again changes nothing.
+        let head = versions.head();
+        assert!(versions.merge("feature", |_, _, _| panic!("Nothing to resolve")));
+        assert_eq!(versions.head(), head);
+        assert!(!versions.merge("missing", three_way));
+    }
+
+    #[test]
+    fn merge_keeps_uncommitted_edits() {
+        let mut versions = VersionTree::new(vec![1, 2].into_iter().collect::<Tree<u32>>());
+        versions.branch("feature");
+        versions.checkout("feature");
+        versions.get_mut().insert(3);
+        versions.commit();
+
+        versions.checkout("main");
+        versions.get_mut().insert(4);
+        assert!(versions.merge("feature", three_way));
+        assert_eq!(values(versions.get()), vec![1, 2, 3, 4]);
+        let parents = versions.parents(versions.head()).to_vec();
+        assert_eq!(versions.version(parents[0]).map(values), Some(vec![1, 2, 4]));
+    }
+}